@@ -0,0 +1,37 @@
+#!/usr/bin/env python3
+"""Copies cfn-lint's Lambda runtime lifecycle data into the server.
+
+Usage: bundle-runtimes.py [OUTPUT]
+
+Reads the dates each runtime was deprecated and its successor from the
+installed cfn-lint, so that the server flags the same runtimes cfn-lint does.
+"""
+
+import json
+import pathlib
+import sys
+
+import cfnlint
+
+DEFAULT_OUTPUT = pathlib.Path(__file__).parent.parent / "data" / "lambda-runtimes.json"
+
+SOURCE = pathlib.Path(cfnlint.__file__).parent / "data" / "AdditionalSpecs" / "LmbdRuntimeLifecycle.json"
+
+# Keys the server reads
+RUNTIME_KEYS = {"deprecated", "successor"}
+
+
+def main():
+    output = pathlib.Path(sys.argv[1]) if len(sys.argv) > 1 else DEFAULT_OUTPUT
+
+    lifecycle = json.loads(SOURCE.read_text())
+    runtimes = {
+        name: {key: value for key, value in runtime.items() if key in RUNTIME_KEYS}
+        for name, runtime in lifecycle.items()
+    }
+    output.write_text(json.dumps(runtimes, indent=2, sort_keys=True) + "\n")
+    print(f"Bundled {len(runtimes)} Lambda runtimes into {output}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,110 @@
+#!/usr/bin/env python3
+"""Builds the resource provider schema bundle embedded in the server.
+
+Usage: bundle-schemas.py SCHEMA_DIR [OUTPUT]
+
+SCHEMA_DIR holds resource provider schema JSON files, e.g. the extracted
+contents of https://schema.cloudformation.us-east-1.amazonaws.com/CloudformationSchema.zip.
+Copies with their descriptions stripped, such as cfn-lint's, are refused, as
+hover and completion show the descriptions. Schemas are trimmed to the keys the server reads, then written as a single
+zlib-compressed JSON array.
+"""
+
+import json
+import pathlib
+import re
+import sys
+import zlib
+
+DEFAULT_OUTPUT = pathlib.Path(__file__).parent.parent / "data" / "resource-schemas.json.zlib"
+
+RESOURCE_KEYS = {
+    "typeName",
+    "description",
+    "documentationUrl",
+    "properties",
+    "definitions",
+    "required",
+    "readOnlyProperties",
+    "createOnlyProperties",
+    "deprecatedProperties",
+    "additionalProperties",
+}
+
+SHAPE_KEYS = {
+    "type",
+    "$ref",
+    "description",
+    "items",
+    "properties",
+    "required",
+    "enum",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "patternProperties",
+    "additionalProperties",
+    "oneOf",
+    "anyOf",
+}
+
+# Resource type names, e.g. AWS::S3::Bucket. Module schemas, named
+# Vendor::Service::Name::MODULE, and stray schemas of other kinds don't match
+TYPE_NAME = re.compile(r"[A-Za-z0-9]+::[A-Za-z0-9]+::[A-Za-z0-9]+")
+
+# Keys whose values map names to shapes, rather than being a shape themselves
+NAMED_SHAPES = {"properties", "definitions", "patternProperties"}
+
+
+def trim(shape):
+    if not isinstance(shape, dict):
+        return shape
+    trimmed = {}
+    for key, value in shape.items():
+        if key not in SHAPE_KEYS:
+            continue
+        if key in NAMED_SHAPES:
+            value = {name: trim(inner) for name, inner in value.items()}
+        elif key in ("items", "additionalProperties"):
+            value = trim(value)
+        elif key in ("oneOf", "anyOf"):
+            value = [trim(inner) for inner in value]
+        trimmed[key] = value
+    return trimmed
+
+
+def trim_resource(schema):
+    trimmed = {key: value for key, value in schema.items() if key in RESOURCE_KEYS}
+    for key in ("properties", "definitions"):
+        if key in trimmed:
+            trimmed[key] = {name: trim(shape) for name, shape in trimmed[key].items()}
+    return trimmed
+
+
+def main():
+    if len(sys.argv) < 2:
+        sys.exit(__doc__)
+    output = pathlib.Path(sys.argv[2]) if len(sys.argv) > 2 else DEFAULT_OUTPUT
+
+    schemas = {}
+    for path in sorted(pathlib.Path(sys.argv[1]).rglob("*.json")):
+        schema = json.loads(path.read_text())
+        if isinstance(schema, dict) and TYPE_NAME.fullmatch(schema.get("typeName", "")):
+            schemas[schema["typeName"]] = trim_resource(schema)
+
+    if not any("description" in schema for schema in schemas.values()):
+        sys.exit(f"No schema in {sys.argv[1]} has a description; use the CloudFormation schema archive")
+
+    bundle = json.dumps(
+        [schemas[name] for name in sorted(schemas)],
+        separators=(",", ":"),
+        sort_keys=True,
+    )
+    output.write_bytes(zlib.compress(bundle.encode(), 9))
+    print(f"Bundled {len(schemas)} resource schemas into {output}")
+
+
+if __name__ == "__main__":
+    main()
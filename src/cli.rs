@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::{ArgAction, Parser, Subcommand};
 
 #[derive(Parser)]
@@ -13,6 +15,11 @@ pub struct Cli {
     #[arg(long, global = true, action = ArgAction::SetTrue)]
     debug: bool,
 
+    /// Directory of resource provider schemas, overriding bundled types of the
+    /// same name. May be repeated
+    #[arg(long = "schema-directory", global = true, value_name = "DIRECTORY")]
+    schema_directories: Vec<PathBuf>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -26,6 +33,10 @@ impl Cli {
         self.debug
     }
 
+    pub fn schema_directories(&self) -> &[PathBuf] {
+        &self.schema_directories
+    }
+
     pub fn command(&self) -> &Command {
         &self.command
     }
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+use ropey::Rope;
+
+use crate::{
+    model::method::{
+        diagnostic::{Position, Range},
+        did_change, did_open,
+        initialise::PositionEncoding,
+    },
+    template::{self, Span, Template},
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Document {
+    uri: String,
+    language_id: String,
+    version: usize,
+    text: Rope,
+    encoding: PositionEncoding,
+    template: Template,
+}
+
+impl Document {
+    pub fn new(
+        uri: &str,
+        language_id: &str,
+        version: usize,
+        text: &str,
+        encoding: PositionEncoding,
+    ) -> Self {
+        Self {
+            uri: uri.into(),
+            language_id: language_id.into(),
+            version,
+            text: Rope::from_str(text),
+            encoding,
+            template: template::parse(text),
+        }
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn language_id(&self) -> &str {
+        &self.language_id
+    }
+
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    pub fn text(&self) -> String {
+        self.text.to_string()
+    }
+
+    /// Text covered by a span. Offsets inside a character are moved back to
+    /// its start, so that a span measured wrongly can't split one
+    pub fn slice(&self, span: Span) -> String {
+        let length = self.text.len_bytes();
+        let end = self.text.byte_to_char(span.end().min(length));
+        let start = self.text.byte_to_char(span.start().min(length));
+        self.text.slice(start.min(end)..end).to_string()
+    }
+
+    /// The template as parsed from the current text, kept up to date as
+    /// changes are applied
+    pub fn template(&self) -> &Template {
+        &self.template
+    }
+
+    fn apply(&mut self, params: &did_change::Params) {
+        for change in params.content_changes() {
+            match change.range() {
+                Some(range) => self.edit(range, change.text()),
+                None => self.text = Rope::from_str(change.text()),
+            }
+        }
+        self.version = params.text_document().version();
+        self.template = template::parse(&self.text());
+    }
+
+    /// Byte offset into the text of a position in the negotiated encoding
+    pub fn offset(&self, position: &Position) -> usize {
+        self.text
+            .char_to_byte(self.char_index(position, self.encoding))
+    }
+
+    /// Position in the negotiated encoding of a byte offset into the text
+    pub fn position(&self, offset: usize) -> Position {
+        let index = self.text.byte_to_char(offset.min(self.text.len_bytes()));
+        self.position_of_char(index)
+    }
+
+    /// Range in the negotiated encoding covering a span of the text
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start()), self.position(span.end()))
+    }
+
+    /// Byte offset of the start of the line containing an offset
+    pub fn line_start(&self, offset: usize) -> usize {
+        let offset = offset.min(self.text.len_bytes());
+        self.text.line_to_byte(self.text.byte_to_line(offset))
+    }
+
+    /// Re-encodes a position expressed in another encoding into the
+    /// negotiated one
+    pub fn transcode(&self, position: &Position, encoding: PositionEncoding) -> Position {
+        self.position_of_char(self.char_index(position, encoding))
+    }
+
+    fn edit(&mut self, range: &Range, text: &str) {
+        let start = self.char_index(range.start(), self.encoding);
+        let end = self.char_index(range.end(), self.encoding).max(start);
+        self.text.remove(start..end);
+        self.text.insert(start, text);
+    }
+
+    // Positions past the end of a line or the document are clamped, as the
+    // specification requires
+    fn char_index(&self, position: &Position, encoding: PositionEncoding) -> usize {
+        if position.line() >= self.text.len_lines() {
+            return self.text.len_chars();
+        }
+        let line_start = self.text.line_to_char(position.line());
+        let line_end = self.line_end_char(position.line());
+        let index = match encoding {
+            PositionEncoding::Utf8 => {
+                let byte = self.text.char_to_byte(line_start) + position.character();
+                self.text
+                    .byte_to_char(byte.min(self.text.char_to_byte(line_end)))
+            }
+            PositionEncoding::Utf16 | PositionEncoding::Unsupported => {
+                let unit = self.text.char_to_utf16_cu(line_start) + position.character();
+                self.text
+                    .utf16_cu_to_char(unit.min(self.text.char_to_utf16_cu(line_end)))
+            }
+            PositionEncoding::Utf32 => line_start + position.character(),
+        };
+        index.min(line_end)
+    }
+
+    fn position_of_char(&self, index: usize) -> Position {
+        let line = self.text.char_to_line(index);
+        let line_start = self.text.line_to_char(line);
+        let character = match self.encoding {
+            PositionEncoding::Utf8 => {
+                self.text.char_to_byte(index) - self.text.char_to_byte(line_start)
+            }
+            PositionEncoding::Utf16 | PositionEncoding::Unsupported => {
+                self.text.char_to_utf16_cu(index) - self.text.char_to_utf16_cu(line_start)
+            }
+            PositionEncoding::Utf32 => index - line_start,
+        };
+        Position::new(line, character)
+    }
+
+    fn line_end_char(&self, line: usize) -> usize {
+        let content = self.text.line(line);
+        let mut length = content.len_chars();
+        while length > 0 && matches!(content.char(length - 1), '\n' | '\r') {
+            length -= 1;
+        }
+        self.text.line_to_char(line) + length
+    }
+}
+
+/// Open documents keyed by URI, holding the client's view of their content
+#[derive(Debug, Default)]
+pub struct DocumentStore {
+    documents: HashMap<String, Document>,
+    encoding: PositionEncoding,
+}
+
+impl DocumentStore {
+    pub fn new(encoding: PositionEncoding) -> Self {
+        Self {
+            documents: HashMap::new(),
+            encoding,
+        }
+    }
+
+    pub fn open(&mut self, item: &did_open::TextDocumentItem) -> &Document {
+        let document = Document::new(
+            item.uri(),
+            item.language_id(),
+            item.version(),
+            item.text(),
+            self.encoding,
+        );
+        self.documents.insert(item.uri().into(), document);
+        &self.documents[item.uri()]
+    }
+
+    pub fn change(&mut self, params: &did_change::Params) -> Option<&Document> {
+        let document = self.documents.get_mut(params.text_document().uri())?;
+        document.apply(params);
+        Some(document)
+    }
+
+    pub fn close(&mut self, uri: &str) -> Option<Document> {
+        self.documents.remove(uri)
+    }
+
+    pub fn get(&self, uri: &str) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Document> {
+        self.documents.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_params(uri: &str, text: &str) -> did_open::TextDocumentItem {
+        serde_json::from_value(serde_json::json!({
+            "uri": uri,
+            "languageId": "yaml",
+            "version": 1,
+            "text": text,
+        }))
+        .unwrap()
+    }
+
+    fn change_params(uri: &str, version: usize, text: &str) -> did_change::Params {
+        serde_json::from_value(serde_json::json!({
+            "textDocument": {"uri": uri, "version": version},
+            "contentChanges": [{"text": text}],
+        }))
+        .unwrap()
+    }
+
+    fn edit_params(uri: &str, version: usize, changes: serde_json::Value) -> did_change::Params {
+        serde_json::from_value(serde_json::json!({
+            "textDocument": {"uri": uri, "version": version},
+            "contentChanges": changes,
+        }))
+        .unwrap()
+    }
+
+    fn range(start: (usize, usize), end: (usize, usize)) -> serde_json::Value {
+        serde_json::json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    }
+
+    mod document_store_tests {
+        use super::*;
+
+        #[test]
+        fn opens_document() {
+            let mut store = DocumentStore::default();
+            store.open(&open_params("file:///cf.yaml", "Resources: {}"));
+
+            let actual = store.get("file:///cf.yaml").unwrap();
+            assert_eq!(
+                actual,
+                &Document::new(
+                    "file:///cf.yaml",
+                    "yaml",
+                    1,
+                    "Resources: {}",
+                    PositionEncoding::default()
+                )
+            )
+        }
+
+        #[test]
+        fn applies_changes_to_open_document() {
+            let mut store = DocumentStore::default();
+            store.open(&open_params("file:///cf.yaml", "Resources: {}"));
+
+            let actual = store
+                .change(&change_params("file:///cf.yaml", 2, "Outputs: {}"))
+                .unwrap();
+            assert_eq!(
+                actual,
+                &Document::new(
+                    "file:///cf.yaml",
+                    "yaml",
+                    2,
+                    "Outputs: {}",
+                    PositionEncoding::default()
+                )
+            )
+        }
+
+        #[test]
+        fn ignores_changes_to_unknown_document() {
+            let mut store = DocumentStore::default();
+
+            let actual = store.change(&change_params("file:///cf.yaml", 2, "Outputs: {}"));
+            assert!(actual.is_none())
+        }
+
+        #[test]
+        fn applies_incremental_changes_in_order() {
+            let mut store = DocumentStore::default();
+            store.open(&open_params(
+                "file:///cf.yaml",
+                "Resources:\n  Bucket:\n    Type: AWS::S3::Bucket\n",
+            ));
+
+            let changes = serde_json::json!([
+                {"range": range((1, 2), (1, 8)), "text": "Queue"},
+                {"range": range((2, 15), (2, 25)), "text": "SQS::Queue"},
+                {"range": range((3, 0), (3, 0)), "text": "Outputs: {}\n"},
+            ]);
+            let actual = store
+                .change(&edit_params("file:///cf.yaml", 2, changes))
+                .unwrap();
+            assert_eq!(
+                actual.text(),
+                "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\nOutputs: {}\n"
+            )
+        }
+
+        #[test]
+        fn clamps_changes_beyond_end_of_line() {
+            let mut store = DocumentStore::default();
+            store.open(&open_params(
+                "file:///cf.yaml",
+                "Resources: {}\r\nOutputs: {}",
+            ));
+
+            let changes = serde_json::json!([{"range": range((0, 9), (0, 99)), "text": ""}]);
+            let actual = store
+                .change(&edit_params("file:///cf.yaml", 2, changes))
+                .unwrap();
+            assert_eq!(actual.text(), "Resources\r\nOutputs: {}")
+        }
+
+        #[test]
+        fn drops_closed_document() {
+            let mut store = DocumentStore::default();
+            store.open(&open_params("file:///cf.yaml", "Resources: {}"));
+            store.close("file:///cf.yaml");
+
+            assert!(store.get("file:///cf.yaml").is_none())
+        }
+    }
+
+    mod position_encoding_tests {
+        use super::*;
+
+        const TEXT: &str = "Description: \u{1F600} caf\u{e9}\nOutputs: {}";
+
+        fn document(encoding: PositionEncoding) -> Document {
+            Document::new("file:///cf.yaml", "yaml", 1, TEXT, encoding)
+        }
+
+        #[test]
+        fn converts_utf8_positions() {
+            let document = document(PositionEncoding::Utf8);
+            let offset = TEXT.find("caf").unwrap();
+            assert_eq!(document.position(offset), Position::new(0, 18));
+            assert_eq!(document.offset(&Position::new(0, 18)), offset)
+        }
+
+        #[test]
+        fn converts_utf16_positions() {
+            let document = document(PositionEncoding::Utf16);
+            let offset = TEXT.find("caf").unwrap();
+            assert_eq!(document.position(offset), Position::new(0, 16));
+            assert_eq!(document.offset(&Position::new(0, 16)), offset)
+        }
+
+        #[test]
+        fn converts_utf32_positions() {
+            let document = document(PositionEncoding::Utf32);
+            let offset = TEXT.find("caf").unwrap();
+            assert_eq!(document.position(offset), Position::new(0, 15));
+            assert_eq!(document.offset(&Position::new(0, 15)), offset)
+        }
+
+        #[test]
+        fn transcodes_code_point_positions() {
+            let document = document(PositionEncoding::Utf16);
+            let actual = document.transcode(&Position::new(0, 19), PositionEncoding::Utf32);
+            assert_eq!(actual, Position::new(0, 20))
+        }
+
+        #[test]
+        fn clamps_positions_to_end_of_line() {
+            let document = document(PositionEncoding::Utf16);
+            let actual = document.transcode(&Position::new(1, 99), PositionEncoding::Utf16);
+            assert_eq!(actual, Position::new(1, 11))
+        }
+
+        #[test]
+        fn slices_from_character_boundaries() {
+            let document = document(PositionEncoding::Utf16);
+            let start = TEXT.find('\u{1F600}').unwrap();
+            let actual = document.slice(Span::new(start + 2, start + 5));
+            assert_eq!(actual, "\u{1F600} ")
+        }
+    }
+}
@@ -1,13 +1,39 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
 use crate::{
-    method::diagnostic::{CfnLinter, Lint},
+    document::{Document, DocumentStore},
+    method::{
+        code_action, completion, definition,
+        diagnostic::{self as lint, CfnLinter, Lint, LintError},
+        document_symbol, formatting, hover, references, rename, workspace_symbol,
+    },
     model::{
         Error, ErrorCode, ErrorResponse, Message, Notification, Request, RequestId, Response,
         ResponseResult, SuccessResponse,
         method::{
-            NotificationMethod, RequestMethod, diagnostic,
+            NotificationMethod, RequestMethod,
+            code_action::Params as CodeActionParams,
+            completion::Params as CompletionParams,
+            definition::Params as DefinitionParams,
+            diagnostic,
+            document_symbol::Params as DocumentSymbolParams,
+            formatting::Params as FormattingParams,
+            hover::Params as HoverParams,
             initialise::{self, ClientInfo},
+            prepare_rename::Params as PrepareRenameParams,
+            range_formatting::Params as RangeFormattingParams,
+            references::Params as ReferencesParams,
+            rename::Params as RenameParams,
+            workspace_symbol::Params as WorkspaceSymbolParams,
         },
     },
+    schema::SchemaRegistry,
+    settings::Settings,
+    uri,
+    workspace::Workspace,
 };
 
 #[derive(Debug, Clone)]
@@ -22,14 +48,27 @@ pub struct MessageHandler {
     client_process_id: Option<String>,
     state: State,
     linter: Box<dyn Lint>,
+    /// cfn-lint's findings from its last run on each open document
+    linted: HashMap<String, Vec<diagnostic::Diagnostic>>,
+    documents: DocumentStore,
+    schemas: SchemaRegistry,
+    schema_directories: Vec<PathBuf>,
+    settings: Settings,
+    workspace: Workspace,
 }
 
 impl MessageHandler {
-    pub fn new(client_process_id: Option<&String>) -> Self {
+    pub fn new(client_process_id: Option<&String>, schema_directories: &[PathBuf]) -> Self {
         Self {
             client_process_id: client_process_id.cloned(),
             state: State::Uninitialised,
             linter: Box::new(CfnLinter),
+            linted: HashMap::new(),
+            documents: DocumentStore::default(),
+            schemas: SchemaRegistry::bundled(),
+            schema_directories: schema_directories.to_vec(),
+            settings: Settings::default(),
+            workspace: Workspace::default(),
         }
     }
 
@@ -65,12 +104,27 @@ impl MessageHandler {
                 RequestMethod::PullDiagnostics(params) => {
                     self.pull_diagnostics(request.id(), params)
                 }
+                RequestMethod::Hover(params) => self.hover(request.id(), params),
+                RequestMethod::Completion(params) => self.completion(request.id(), params),
+                RequestMethod::Definition(params) => self.definition(request.id(), params),
+                RequestMethod::References(params) => self.references(request.id(), params),
+                RequestMethod::PrepareRename(params) => self.prepare_rename(request.id(), params),
+                RequestMethod::Rename(params) => self.rename(request.id(), params),
+                RequestMethod::DocumentSymbol(params) => self.document_symbol(request.id(), params),
+                RequestMethod::WorkspaceSymbol(params) => {
+                    self.workspace_symbol(request.id(), params)
+                }
+                RequestMethod::CodeAction(params) => self.code_action(request.id(), params),
+                RequestMethod::Formatting(params) => self.formatting(request.id(), params),
+                RequestMethod::RangeFormatting(params) => {
+                    self.range_formatting(request.id(), params)
+                }
                 RequestMethod::Initialise(_) => already_initialised(request.id()),
             },
         }
     }
 
-    fn handle_notification(&self, notification: &Notification) -> Option<Message> {
+    fn handle_notification(&mut self, notification: &Notification) -> Option<Message> {
         match self.state {
             State::Uninitialised | State::Shutdown => {
                 if let NotificationMethod::Exit = notification.method() {
@@ -81,15 +135,36 @@ impl MessageHandler {
                 }
             }
             State::Initialised(_) => match notification.method() {
-                NotificationMethod::DidOpen(params) => self
-                    .publish_diagnostics(
-                        params.text_document().uri(),
-                        Some(params.text_document().version()),
-                    )
-                    .map(Message::Notification),
-                NotificationMethod::DidSave(params) => self
-                    .publish_diagnostics(params.text_document().uri(), None)
-                    .map(Message::Notification),
+                NotificationMethod::DidOpen(params) => {
+                    let uri = params.text_document().uri();
+                    self.documents.open(params.text_document());
+                    self.publish_diagnostics(uri, true)
+                        .map(Message::Notification)
+                }
+                NotificationMethod::DidChange(params) => {
+                    let uri = params.text_document().uri();
+                    self.documents.change(params)?;
+                    self.publish_diagnostics(uri, false)
+                        .map(Message::Notification)
+                }
+                NotificationMethod::DidSave(params) => {
+                    let uri = params.text_document().uri();
+                    self.refresh_workspace(uri);
+                    self.publish_diagnostics(uri, true)
+                        .map(Message::Notification)
+                }
+                NotificationMethod::DidClose(params) => {
+                    let uri = params.text_document().uri();
+                    self.documents.close(uri);
+                    self.linted.remove(uri);
+                    self.refresh_workspace(uri);
+                    None
+                }
+                NotificationMethod::DidChangeConfiguration(params) => {
+                    self.settings = Settings::from_value(params.settings());
+                    self.load_schemas();
+                    None
+                }
                 _ => None,
             },
         }
@@ -101,12 +176,54 @@ impl MessageHandler {
             "Initialising server for client '{}'",
             params.client_info().unwrap_or(&ClientInfo::default())
         );
+        let position_encoding = params.position_encoding();
+        tracing::info!(
+            id = tracing::field::display(id),
+            "Negotiated position encoding '{position_encoding:?}'"
+        );
         self.state = State::Initialised(params.clone());
-        let result = initialise::Result::default();
+        self.documents = DocumentStore::new(position_encoding);
+        self.settings = params.settings();
+        self.load_schemas();
+        self.workspace = Workspace::index(&params.workspace_folders(), position_encoding);
+        let result = initialise::Result::new(position_encoding);
         let success = SuccessResponse::new(id, ResponseResult::Initialise(result));
         Response::Success(success)
     }
 
+    /// Rebuilds the schema registry from the bundled schemas and the
+    /// directories given on the command line and in the settings, in that
+    /// order, so that later definitions override earlier ones
+    fn load_schemas(&mut self) {
+        let root = match &self.state {
+            State::Initialised(params) => params.root_path(),
+            _ => None,
+        };
+        let directories = self
+            .schema_directories
+            .iter()
+            .cloned()
+            .chain(self.settings.schema_directories(root.as_deref()));
+
+        let mut schemas = SchemaRegistry::bundled();
+        for directory in directories {
+            match schemas.load_directory(&directory) {
+                Ok(count) => tracing::info!(
+                    "Loaded {count} resource schemas from '{}'",
+                    directory.display()
+                ),
+                Err(error) => tracing::error!("{error}"),
+            }
+        }
+        self.schemas = schemas;
+    }
+
+    fn refresh_workspace(&mut self, uri: &str) {
+        if let Some(path) = uri::to_path(uri) {
+            self.workspace.refresh(&path);
+        }
+    }
+
     fn shutdown(&mut self, id: &RequestId) -> Response {
         tracing::info!(id = tracing::field::display(id), "Shutting down server");
         self.state = State::Shutdown;
@@ -114,13 +231,16 @@ impl MessageHandler {
         Response::Success(success)
     }
 
-    fn pull_diagnostics(&self, id: &RequestId, params: &diagnostic::pull::Params) -> Response {
+    fn pull_diagnostics(&mut self, id: &RequestId, params: &diagnostic::pull::Params) -> Response {
         tracing::debug!(
             id = tracing::field::display(id),
             "Generating diagnostics for file '{}'",
             params.uri()
         );
-        match self.linter.lint(params.uri()) {
+        if self.documents.get(params.uri()).is_none() {
+            return document_not_open(id, params.uri());
+        }
+        match self.lint_diagnostics(params.uri()) {
             Ok(diagnostics) => {
                 let result = diagnostic::pull::Result::full("result", diagnostics);
                 let success = SuccessResponse::new(id, ResponseResult::PullDiagnostics(result));
@@ -137,28 +257,246 @@ impl MessageHandler {
         }
     }
 
-    fn publish_diagnostics(&self, uri: &str, version: Option<usize>) -> Option<Notification> {
-        tracing::debug!(
-            "Generating diagnostics for file '{}', version '{:?}'",
-            uri,
-            version,
+    fn hover(&self, id: &RequestId, params: &HoverParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = match hover::hover(document, &self.schemas, params.position()) {
+            Some(hover) => ResponseResult::Hover(hover),
+            None => ResponseResult::Null,
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn completion(&self, id: &RequestId, params: &CompletionParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let snippets = match &self.state {
+            State::Initialised(params) => params.snippet_support(),
+            _ => false,
+        };
+        let result =
+            match completion::completion(document, &self.schemas, params.position(), snippets) {
+                Some(list) => ResponseResult::Completion(list),
+                None => ResponseResult::Null,
+            };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn definition(&self, id: &RequestId, params: &DefinitionParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = match definition::definition(document, params.position()) {
+            Some(location) => ResponseResult::Definition(location),
+            None => ResponseResult::Null,
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn references(&self, id: &RequestId, params: &ReferencesParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = match references::references(
+            document,
+            &self.documents.iter().collect::<Vec<_>>(),
+            params.position(),
+            params.include_declaration(),
+        ) {
+            Some(locations) => ResponseResult::References(locations),
+            None => ResponseResult::Null,
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn prepare_rename(&self, id: &RequestId, params: &PrepareRenameParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = match rename::prepare_rename(document, params.position()) {
+            Some(result) => ResponseResult::PrepareRename(result),
+            None => ResponseResult::Null,
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn rename(&self, id: &RequestId, params: &RenameParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = match rename::rename(document, params.position(), params.new_name()) {
+            Ok(Some(edit)) => ResponseResult::Rename(edit),
+            Ok(None) => ResponseResult::Null,
+            Err(e) => {
+                let error = Error::new(ErrorCode::RequestFailed, &e.to_string(), None);
+                return Response::Error(ErrorResponse::new(id, error));
+            }
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn document_symbol(&self, id: &RequestId, params: &DocumentSymbolParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = ResponseResult::DocumentSymbol(document_symbol::document_symbol(document));
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    /// Searches the open documents along with the indexed templates which
+    /// aren't open, as the open copy may have unsaved changes
+    fn workspace_symbol(&self, id: &RequestId, params: &WorkspaceSymbolParams) -> Response {
+        let open: HashSet<_> = self
+            .documents
+            .iter()
+            .filter_map(|document| uri::to_path(document.uri()))
+            .collect();
+        let editing: Vec<_> = self
+            .documents
+            .iter()
+            .map(workspace_symbol::symbols)
+            .collect();
+        let indexed = self.workspace.symbols();
+        let symbols = editing.iter().flatten().chain(
+            indexed
+                .iter()
+                .filter(|(path, _)| !open.contains(path))
+                .flat_map(|(_, symbols)| symbols.iter()),
         );
-        if let Ok(diagnostics) = self.linter.lint(uri) {
-            let publish_diagnostics = diagnostic::publish::Params::new(uri, version, diagnostics);
-            Some(Notification::new(NotificationMethod::PublishDiagnostics(
-                publish_diagnostics,
-            )))
-        } else {
-            None
+        let result = ResponseResult::WorkspaceSymbol(workspace_symbol::workspace_symbol(
+            symbols,
+            params.query(),
+        ));
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn code_action(&self, id: &RequestId, params: &CodeActionParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let result = ResponseResult::CodeAction(code_action::code_action(
+            document,
+            params.range(),
+            params.diagnostics(),
+            params.only(),
+            self.settings.regions(),
+        ));
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn formatting(&self, id: &RequestId, params: &FormattingParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let tab_size = params.options().tab_size() as usize;
+        let options = self.settings.formatting().options(tab_size);
+        let result = match formatting::formatting(document, options) {
+            Ok(edits) => ResponseResult::Formatting(edits),
+            Err(e) => {
+                let error = Error::new(ErrorCode::RequestFailed, &e.to_string(), None);
+                return Response::Error(ErrorResponse::new(id, error));
+            }
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    fn range_formatting(&self, id: &RequestId, params: &RangeFormattingParams) -> Response {
+        let Some(document) = self.documents.get(params.uri()) else {
+            return document_not_open(id, params.uri());
+        };
+        let tab_size = params.options().tab_size() as usize;
+        let options = self.settings.formatting().options(tab_size);
+        let result = match formatting::range_formatting(document, params.range(), options) {
+            Ok(edits) => ResponseResult::RangeFormatting(edits),
+            Err(e) => {
+                let error = Error::new(ErrorCode::RequestFailed, &e.to_string(), None);
+                return Response::Error(ErrorResponse::new(id, error));
+            }
+        };
+        Response::Success(SuccessResponse::new(id, result))
+    }
+
+    /// Publishes the diagnostics of a document. cfn-lint is only run when
+    /// asked, on open and save, so that typing doesn't hold up other requests
+    /// while it runs. In between, its last findings are published along with
+    /// the native diagnostics, as each publish replaces the client's set
+    fn publish_diagnostics(&mut self, uri: &str, lint: bool) -> Option<Notification> {
+        let version = self.documents.get(uri)?.version();
+        tracing::debug!("Generating diagnostics for file '{uri}', version '{version}'");
+        let diagnostics = match lint {
+            true => self.lint_diagnostics(uri),
+            false => Ok(self.diagnostics(self.documents.get(uri)?)),
+        };
+        match diagnostics {
+            Ok(diagnostics) => Some(publish(self.documents.get(uri)?, diagnostics)),
+            Err(error) => {
+                tracing::error!("Failed to generate diagnostics for file '{uri}': {error}");
+                None
+            }
+        }
+    }
+
+    /// Runs cfn-lint before gathering the diagnostics of a document. The
+    /// native checks are reported even if cfn-lint fails
+    fn lint_diagnostics(&mut self, uri: &str) -> Result<Vec<diagnostic::Diagnostic>, LintError> {
+        let linted = self.lint(uri);
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(Vec::new());
+        };
+        let diagnostics = self.diagnostics(document);
+        match linted {
+            Ok(()) => Ok(diagnostics),
+            Err(error) if !diagnostics.is_empty() => {
+                tracing::error!("Failed to lint file '{uri}': {error}");
+                Ok(diagnostics)
+            }
+            Err(error) => Err(error),
         }
     }
 
+    fn lint(&mut self, uri: &str) -> Result<(), LintError> {
+        self.linted.remove(uri);
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(());
+        };
+        let linted = self.linter.lint(document)?;
+        self.linted.insert(uri.into(), linted);
+        Ok(())
+    }
+
+    /// The native diagnostics, along with cfn-lint's findings from its last
+    /// run other than those the native checks repeat
+    fn diagnostics(&self, document: &Document) -> Vec<diagnostic::Diagnostic> {
+        let syntax = lint::syntax_diagnostics(document);
+        let mut diagnostics = match syntax.is_empty() {
+            true => lint::template_diagnostics(document, &self.schemas),
+            false => syntax,
+        };
+        let linted = self
+            .linted
+            .get(document.uri())
+            .into_iter()
+            .flatten()
+            .filter(|diagnostic| !lint::superseded(document, &self.schemas, diagnostic))
+            .cloned();
+        diagnostics.extend(linted);
+        diagnostics
+    }
+
     fn exit() {
         tracing::info!("Received exit notification. Exiting...");
         std::process::exit(0);
     }
 }
 
+fn publish(document: &Document, diagnostics: Vec<diagnostic::Diagnostic>) -> Notification {
+    let params =
+        diagnostic::publish::Params::new(document.uri(), Some(document.version()), diagnostics);
+    Notification::new(NotificationMethod::PublishDiagnostics(params))
+}
+
 fn uninitialised_request(id: &RequestId) -> Response {
     let error = Error::new(
         ErrorCode::ServerNotInitialised,
@@ -181,3 +519,12 @@ fn request_post_shutdown(id: &RequestId) -> Response {
     let error = Error::new(ErrorCode::InvalidRequest, "Server has been shutdown", None);
     Response::Error(ErrorResponse::new(id, error))
 }
+
+fn document_not_open(id: &RequestId, uri: &str) -> Response {
+    let error = Error::new(
+        ErrorCode::InvalidParams,
+        &format!("Document '{uri}' is not open"),
+        None,
+    );
+    Response::Error(ErrorResponse::new(id, error))
+}
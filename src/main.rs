@@ -6,11 +6,17 @@ use model::{Message, Response};
 
 mod channel;
 mod cli;
+mod document;
 mod handler;
 mod log;
 mod method;
 mod model;
 mod reader;
+mod schema;
+mod settings;
+mod template;
+mod uri;
+mod workspace;
 mod writer;
 
 fn main() {
@@ -21,7 +27,7 @@ fn main() {
         tracing::info!("Server spawned by client process {process_id}");
     }
     let (mut input, mut output) = channel::connect(cli.command());
-    let mut handler = MessageHandler::new(cli.client_process_id());
+    let mut handler = MessageHandler::new(cli.client_process_id(), cli.schema_directories());
 
     loop {
         let message = reader::read(&mut input);
@@ -33,10 +39,10 @@ fn main() {
             }
         };
 
-        if let Some(response) = response {
-            if let Err(error) = writer::write(&mut output, &response) {
-                tracing::error!("{error}");
-            }
+        if let Some(response) = response
+            && let Err(error) = writer::write(&mut output, &response)
+        {
+            tracing::error!("{error}");
         }
     }
 }
@@ -1 +1,14 @@
+pub mod code_action;
+pub mod completion;
+pub mod definition;
 pub mod diagnostic;
+pub mod document_symbol;
+pub mod edit;
+#[cfg(test)]
+pub mod fixture;
+pub mod formatting;
+pub mod hover;
+pub mod print;
+pub mod references;
+pub mod rename;
+pub mod workspace_symbol;
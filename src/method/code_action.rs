@@ -0,0 +1,172 @@
+use crate::{
+    document::Document,
+    model::method::{
+        code_action::{CodeAction, CodeActionKind},
+        diagnostic::{Diagnostic, Range},
+        rename::WorkspaceEdit,
+    },
+    template::Span,
+};
+
+mod convert;
+mod extract;
+
+/// Quick fixes for the diagnostics in view, along with refactors of the
+/// selected part of the template and of the template as a whole
+pub fn code_action(
+    document: &Document,
+    range: &Range,
+    diagnostics: &[Diagnostic],
+    only: Option<&[String]>,
+    regions: &[String],
+) -> Vec<CodeAction> {
+    let mut actions = Vec::new();
+    if CodeActionKind::QuickFix.requested(only) {
+        actions.extend(quick_fixes(document, diagnostics));
+    }
+    if CodeActionKind::RefactorExtract.requested(only) {
+        let span = Span::new(document.offset(range.start()), document.offset(range.end()));
+        actions.extend(extract::extract(document, span, regions));
+    }
+    if CodeActionKind::RefactorRewrite.requested(only) {
+        actions.extend(convert::convert(document));
+    }
+    actions
+}
+
+/// Actions applying the fixes which the checks attached to diagnostics.
+/// Fixes made for an earlier version of the document are dropped, as the
+/// client may send back diagnostics it hasn't yet had replaced since an edit
+fn quick_fixes(document: &Document, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let fix = diagnostic
+                .fix()
+                .filter(|fix| fix.version() == document.version())?;
+            let mut edit = WorkspaceEdit::default();
+            for text_edit in fix.edits() {
+                edit.push(document.uri(), text_edit.clone());
+            }
+            Some(
+                CodeAction::builder()
+                    .title(fix.title())
+                    .kind(CodeActionKind::QuickFix)
+                    .diagnostics(vec![diagnostic.clone()])
+                    .is_preferred(true)
+                    .edit(edit)
+                    .build(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        method::{
+            diagnostic::template_diagnostics,
+            fixture::{document, registry},
+        },
+        model::method::{diagnostic::Position, initialise::PositionEncoding},
+    };
+
+    use super::*;
+
+    const TEMPLATE: &str = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Runtime: python3.7
+      Handler: index.handler
+      FunctionName: !Ref Evn
+";
+
+    fn range() -> Range {
+        Range::new(Position::new(0, 0), Position::new(0, 0))
+    }
+
+    /// Diagnostics as the client sends them back, having received them as
+    /// JSON
+    fn diagnostics(document: &Document) -> Vec<Diagnostic> {
+        let json = serde_json::to_string(&template_diagnostics(document, &registry())).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// Text of the document after applying a code action
+    fn apply(document: &Document, action: &CodeAction) -> String {
+        let mut text = document.text();
+        let mut edits = action.edit().changes()[document.uri()].clone();
+        edits.sort_by_key(|edit| document.offset(edit.range().start()));
+        for edit in edits.iter().rev() {
+            let start = document.offset(edit.range().start());
+            let end = document.offset(edit.range().end());
+            text.replace_range(start..end, edit.new_text());
+        }
+        text
+    }
+
+    #[test]
+    fn fixes_diagnostics() {
+        let document = document(TEMPLATE);
+        let only = ["quickfix".to_string()];
+        let actions = code_action(
+            &document,
+            &range(),
+            &diagnostics(&document),
+            Some(&only),
+            &[],
+        );
+        let actual: Vec<_> = actions
+            .iter()
+            .map(|action| (action.title(), apply(&document, action)))
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("Change to 'Env'", TEMPLATE.replace("!Ref Evn", "!Ref Env")),
+                (
+                    "Remove property 'Handler'",
+                    TEMPLATE.replace("      Handler: index.handler\n", "")
+                ),
+                (
+                    "Add property 'Role'",
+                    TEMPLATE.replace("!Ref Evn\n", "!Ref Evn\n      Role: \"\"\n")
+                ),
+                (
+                    "Change runtime to 'python3.14'",
+                    TEMPLATE.replace("python3.7", "python3.14")
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn skips_fixes_for_other_versions() {
+        let document = document(TEMPLATE);
+        let diagnostics = diagnostics(&document);
+        let changed = Document::new(document.uri(), "yaml", 2, TEMPLATE, PositionEncoding::Utf16);
+        let only = ["quickfix".to_string()];
+        assert!(code_action(&changed, &range(), &diagnostics, Some(&only), &[]).is_empty())
+    }
+
+    #[test]
+    fn offers_only_requested_kinds() {
+        let document = document(TEMPLATE);
+        let only = ["source".to_string()];
+        assert!(
+            code_action(
+                &document,
+                &range(),
+                &diagnostics(&document),
+                Some(&only),
+                &[]
+            )
+            .is_empty()
+        )
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    document::Document,
+    method::{
+        edit,
+        print::{self, Options},
+    },
+    model::method::{
+        code_action::{CodeAction, CodeActionKind},
+        rename::WorkspaceEdit,
+    },
+    template::{Format, Span},
+};
+
+/// Rewrites the whole template in the other format, keeping the order of
+/// keys. JSON has nowhere to keep comments, so converting to it drops them
+pub fn convert(document: &Document) -> Option<CodeAction> {
+    let template = document.template();
+    // Parts of the template skipped over by error recovery would be lost
+    if !template.errors().is_empty() {
+        return None;
+    }
+    let (title, format) = match template.format() {
+        Format::Json => ("Convert template to YAML", Format::Yaml),
+        Format::Yaml => ("Convert template to JSON", Format::Json),
+    };
+    let text = print::template(document, format, Options::builder().build())?;
+    let whole = Span::new(0, document.text().len());
+    let mut edit = WorkspaceEdit::default();
+    edit.push(document.uri(), edit::replace(document, whole, &text));
+    Some(
+        CodeAction::builder()
+            .title(title)
+            .kind(CodeActionKind::RefactorRewrite)
+            .is_preferred(false)
+            .edit(edit)
+            .build(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::method::initialise::PositionEncoding, template};
+
+    use super::*;
+
+    const YAML: &str = "\
+AWSTemplateFormatVersion: '2010-09-09'
+Parameters:
+  Env:
+    Type: String
+    AllowedValues:
+      - dev
+      - prod
+Resources:
+  Instance:
+    Type: AWS::EC2::Instance
+    Properties:
+      ImageId: !FindInMap
+        - Images
+        - !Ref AWS::Region
+        - Id
+      UserData:
+        Fn::Base64: !Sub |
+          #!/bin/bash
+          echo ${Env}
+      Tags:
+        - Key: Name
+          Value: !Join
+            - '-'
+            - - !Ref Env
+              - 'on'
+Outputs:
+  Ip:
+    Value: !GetAtt Instance.PublicIp
+";
+
+    const JSON: &str = r##"{
+  "AWSTemplateFormatVersion": "2010-09-09",
+  "Parameters": {
+    "Env": {
+      "Type": "String",
+      "AllowedValues": [
+        "dev",
+        "prod"
+      ]
+    }
+  },
+  "Resources": {
+    "Instance": {
+      "Type": "AWS::EC2::Instance",
+      "Properties": {
+        "ImageId": {
+          "Fn::FindInMap": [
+            "Images",
+            {"Ref": "AWS::Region"},
+            "Id"
+          ]
+        },
+        "UserData": {
+          "Fn::Base64": {"Fn::Sub": "#!/bin/bash\necho ${Env}\n"}
+        },
+        "Tags": [
+          {
+            "Key": "Name",
+            "Value": {
+              "Fn::Join": [
+                "-",
+                [
+                  {"Ref": "Env"},
+                  "on"
+                ]
+              ]
+            }
+          }
+        ]
+      }
+    }
+  },
+  "Outputs": {
+    "Ip": {
+      "Value": {"Fn::GetAtt": ["Instance", "PublicIp"]}
+    }
+  }
+}
+"##;
+
+    fn convert_text(text: &str) -> (String, String) {
+        let document = Document::new("file:///cf", "yaml", 1, text, PositionEncoding::Utf16);
+        let action = convert(&document).unwrap();
+        let edits = &action.edit().changes()[document.uri()];
+        (action.title().to_string(), edits[0].new_text().to_string())
+    }
+
+    #[test]
+    fn converts_yaml_to_json() {
+        assert_eq!(
+            convert_text(YAML),
+            ("Convert template to JSON".into(), JSON.into())
+        )
+    }
+
+    #[test]
+    fn converts_json_to_yaml() {
+        assert_eq!(
+            convert_text(JSON),
+            ("Convert template to YAML".into(), YAML.into())
+        )
+    }
+
+    #[test]
+    fn quotes_strings_which_would_change_meaning() {
+        let json = r#"{"Metadata": {"A": "yes", "B": "80", "C": "a: b", "D": "", "E": "it's", "F": "tab\there\u0007", "G": "x\n\n"}}"#;
+        let (_, yaml) = convert_text(json);
+        assert_eq!(
+            yaml,
+            "Metadata:\n  A: 'yes'\n  B: '80'\n  C: 'a: b'\n  D: ''\n  E: it's\n  F: \"tab\\there\\u0007\"\n  G: \"x\\n\\n\"\n"
+        );
+        let root = template::parse(&yaml).root().clone();
+        let metadata = root.get("Metadata").unwrap();
+        for (key, expected) in [
+            ("A", "yes"),
+            ("B", "80"),
+            ("C", "a: b"),
+            ("F", "tab\there\u{7}"),
+            ("G", "x\n\n"),
+        ] {
+            assert_eq!(metadata.get(key).unwrap().as_str(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn skips_templates_with_errors() {
+        let document = Document::new(
+            "file:///cf",
+            "json",
+            1,
+            "{\"Resources\": {",
+            PositionEncoding::Utf16,
+        );
+        assert!(convert(&document).is_none())
+    }
+}
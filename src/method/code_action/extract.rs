@@ -0,0 +1,351 @@
+use crate::{
+    document::Document,
+    method::edit::{self, Insert},
+    model::method::{
+        code_action::{CodeAction, CodeActionKind},
+        rename::WorkspaceEdit,
+        text_document::TextEdit,
+    },
+    template::{DeclarationKind, Format, Intrinsic, Node, Segment, Span, Template, Value},
+};
+
+/// Functions whose arguments name something rather than being values which
+/// could come from a parameter
+const NAMING_FUNCTIONS: [Intrinsic; 4] = [
+    Intrinsic::Condition,
+    Intrinsic::GetAtt,
+    Intrinsic::ImportValue,
+    Intrinsic::Ref,
+];
+
+/// A hard-coded value in a resource's properties or an output's value
+struct Literal<'a> {
+    node: &'a Node,
+    /// Name for what the value is, from the key holding it
+    name: &'a str,
+}
+
+/// Refactors moving the selected literal out into a new parameter, or into
+/// a mapping keyed by region
+pub fn extract(document: &Document, span: Span, regions: &[String]) -> Vec<CodeAction> {
+    let Some(literal) = literal(document, span) else {
+        return Vec::new();
+    };
+    [
+        parameter(document, &literal),
+        mapping(document, &literal, regions),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn parameter(document: &Document, literal: &Literal) -> Option<CodeAction> {
+    let template = document.template();
+    let name = unique_name(
+        template,
+        &[DeclarationKind::Parameter, DeclarationKind::Resource],
+        literal.name,
+    );
+    let parameter_type = match literal.node.value() {
+        Value::Number(_) => "Number",
+        _ => "String",
+    };
+    let body = Insert::Mapping(vec![
+        (
+            "Type".into(),
+            Insert::Scalar(string(document, parameter_type)),
+        ),
+        (
+            "Default".into(),
+            Insert::Scalar(document.slice(literal.node.span())),
+        ),
+    ]);
+    let declaration = edit::declare(document, "Parameters", &name, body)?;
+    let reference = reference(document, &name);
+    let replacement = edit::replace(document, literal.node.span(), &reference);
+    Some(action(
+        document,
+        &format!("Extract to parameter '{name}'"),
+        [replacement, declaration],
+    ))
+}
+
+/// The mapping has a key for each of the regions the settings list, all
+/// starting out with the extracted value. Looking the value up in a region
+/// missing from it fails the deployment, so the list is left to the user
+/// rather than guessed, and no mapping is offered for an empty one
+fn mapping(document: &Document, literal: &Literal, regions: &[String]) -> Option<CodeAction> {
+    if regions.is_empty() {
+        return None;
+    }
+    let template = document.template();
+    let name = unique_name(
+        template,
+        &[DeclarationKind::Mapping],
+        &format!("{}ByRegion", literal.name),
+    );
+    let key = literal.name;
+    let value = document.slice(literal.node.span());
+    let body = Insert::Mapping(
+        regions
+            .iter()
+            .map(|region| {
+                let values = vec![(key.into(), Insert::Scalar(value.clone()))];
+                (region.clone(), Insert::Mapping(values))
+            })
+            .collect(),
+    );
+    let declaration = edit::declare(document, "Mappings", &name, body)?;
+    let lookup = match template.format() {
+        Format::Json => format!(
+            "{{\"Fn::FindInMap\": [\"{name}\", {}, \"{key}\"]}}",
+            reference(document, "AWS::Region")
+        ),
+        Format::Yaml => format!("!FindInMap [{name}, !Ref AWS::Region, {key}]"),
+    };
+    let replacement = edit::replace(document, literal.node.span(), &lookup);
+    Some(action(
+        document,
+        &format!("Extract to mapping '{name}'"),
+        [replacement, declaration],
+    ))
+}
+
+/// The literal covering the selection, if it sits somewhere a parameter or
+/// mapping could supply it
+fn literal(document: &Document, span: Span) -> Option<Literal<'_>> {
+    let template = document.template();
+    let path = template.root().path_to(span.start());
+    let (section, declaration, within) = match path.as_slice() {
+        [
+            Segment::Value(section),
+            Segment::Value(declaration),
+            Segment::Value(within),
+            ..,
+        ] => (section, declaration, within),
+        _ => return None,
+    };
+    let name = match (section.key().name(), within.key().name()) {
+        ("Resources", "Properties") => None,
+        ("Outputs", "Value") => Some(declaration.key().name()),
+        _ => return None,
+    };
+
+    let node = match path.last()? {
+        Segment::Value(entry) => entry.value(),
+        Segment::Item(_, item) => item,
+        _ => return None,
+    };
+    let scalar = matches!(
+        node.value(),
+        Value::String(_) | Value::Number(_) | Value::Bool(_)
+    );
+    let text = document.slice(node.span());
+    if !scalar || !node.span().contains(span.end()) && node.span().end() != span.end() {
+        return None;
+    }
+    // Block scalars span several lines, which a parameter default can't
+    if text.contains('\n') {
+        return None;
+    }
+
+    if let Some(index) = path
+        .iter()
+        .rposition(|segment| matches!(segment, Segment::Function(_)))
+    {
+        let Segment::Function(function) = path[index] else {
+            return None;
+        };
+        let intrinsic = function.intrinsic();
+        // The template string of `Fn::Sub` and the condition of `Fn::If`
+        // must be written out
+        let first_argument = matches!(path[index + 1..], [] | [Segment::Item(0, _)]);
+        if NAMING_FUNCTIONS.contains(&intrinsic)
+            || first_argument && matches!(intrinsic, Intrinsic::Sub | Intrinsic::If)
+        {
+            return None;
+        }
+    }
+
+    let name = name.or_else(|| {
+        path.iter().rev().find_map(|segment| match segment {
+            Segment::Value(entry) => Some(entry.key().name()),
+            _ => None,
+        })
+    })?;
+    Some(Literal { node, name })
+}
+
+/// A name not yet declared in any of the sections, based on the name of the
+/// value
+fn unique_name(template: &Template, kinds: &[DeclarationKind], base: &str) -> String {
+    let base: String = base.chars().filter(char::is_ascii_alphanumeric).collect();
+    let taken = |name: &str| {
+        kinds
+            .iter()
+            .any(|kind| template.find(*kind, name).is_some())
+    };
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|suffix| format!("{base}{suffix}"))
+        .find(|name| !taken(name))
+        .unwrap_or(base)
+}
+
+fn reference(document: &Document, name: &str) -> String {
+    match document.template().format() {
+        Format::Json => format!("{{\"Ref\": \"{name}\"}}"),
+        Format::Yaml => format!("!Ref {name}"),
+    }
+}
+
+fn string(document: &Document, text: &str) -> String {
+    match document.template().format() {
+        Format::Json => format!("\"{text}\""),
+        Format::Yaml => text.into(),
+    }
+}
+
+fn action(document: &Document, title: &str, edits: [TextEdit; 2]) -> CodeAction {
+    let mut edit = WorkspaceEdit::default();
+    for text_edit in edits {
+        edit.push(document.uri(), text_edit);
+    }
+    CodeAction::builder()
+        .title(title)
+        .kind(CodeActionKind::RefactorExtract)
+        .is_preferred(false)
+        .edit(edit)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::initialise::PositionEncoding;
+
+    use super::*;
+
+    const YAML: &str = "\
+AWSTemplateFormatVersion: '2010-09-09'
+Resources:
+  Instance:
+    Type: AWS::EC2::Instance
+    Properties:
+      InstanceType: t3.micro
+      ImageId: ami-0abc
+      UserData: !Sub 'echo ${AWS::Region}'
+      SecurityGroupIds: [!Ref Group]
+";
+
+    const REGIONS: [&str; 2] = ["us-east-1", "eu-west-1"];
+
+    /// Title and resulting text of each action offered for a selection
+    fn extract_at(text: &str, selected: &str) -> Vec<(String, String)> {
+        extract_with(text, selected, &REGIONS.map(String::from))
+    }
+
+    fn extract_with(text: &str, selected: &str, regions: &[String]) -> Vec<(String, String)> {
+        let document = Document::new("file:///cf", "yaml", 1, text, PositionEncoding::Utf16);
+        let start = text.find(selected).unwrap();
+        extract(&document, Span::new(start, start + selected.len()), regions)
+            .iter()
+            .map(|action| {
+                let mut text = document.text();
+                let mut edits = action.edit().changes()[document.uri()].clone();
+                edits.sort_by_key(|edit| document.offset(edit.range().start()));
+                for edit in edits.iter().rev() {
+                    let start = document.offset(edit.range().start());
+                    let end = document.offset(edit.range().end());
+                    text.replace_range(start..end, edit.new_text());
+                }
+                (action.title().to_string(), text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_parameters_and_mappings() {
+        let actual = extract_at(YAML, "t3.micro");
+        let regions: String = REGIONS
+            .iter()
+            .map(|region| format!("    {region}:\n      InstanceType: t3.micro\n"))
+            .collect();
+        assert_eq!(
+            actual[0],
+            (
+                "Extract to parameter 'InstanceType'".into(),
+                YAML.replace(
+                    "Resources:\n",
+                    "Parameters:\n  InstanceType:\n    Type: String\n    Default: t3.micro\nResources:\n"
+                )
+                .replace("InstanceType: t3.micro", "InstanceType: !Ref InstanceType")
+            )
+        );
+        assert_eq!(
+            actual[1],
+            (
+                "Extract to mapping 'InstanceTypeByRegion'".into(),
+                YAML.replace(
+                    "InstanceType: t3.micro",
+                    "InstanceType: !FindInMap [InstanceTypeByRegion, !Ref AWS::Region, InstanceType]"
+                )
+                .replace(
+                    "Resources:\n",
+                    &format!("Mappings:\n  InstanceTypeByRegion:\n{regions}Resources:\n")
+                )
+            )
+        )
+    }
+
+    #[test]
+    fn offers_no_mapping_without_regions() {
+        let actual = extract_with(YAML, "t3.micro", &[]);
+        let titles: Vec<_> = actual.iter().map(|(title, _)| title.as_str()).collect();
+        assert_eq!(titles, vec!["Extract to parameter 'InstanceType'"])
+    }
+
+    #[test]
+    fn extracts_into_existing_json_sections() {
+        let text = r#"{
+  "Parameters": {
+    "InstanceType": {
+      "Type": "String"
+    }
+  },
+  "Resources": {
+    "Instance": {
+      "Type": "AWS::EC2::Instance",
+      "Properties": {
+        "InstanceType": "t3.micro"
+      }
+    }
+  }
+}"#;
+        let actual = extract_at(text, "\"t3.micro\"");
+        assert_eq!(
+            actual[0],
+            (
+                "Extract to parameter 'InstanceType2'".into(),
+                text.replace(
+                    "      \"Type\": \"String\"\n    }\n",
+                    "      \"Type\": \"String\"\n    },\n    \"InstanceType2\": {\n      \"Type\": \"String\",\n      \"Default\": \"t3.micro\"\n    }\n"
+                )
+                .replace(
+                    "\"InstanceType\": \"t3.micro\"",
+                    "\"InstanceType\": {\"Ref\": \"InstanceType2\"}"
+                )
+            )
+        )
+    }
+
+    #[test]
+    fn ignores_names_and_template_strings() {
+        assert!(extract_at(YAML, "AWS::EC2::Instance").is_empty());
+        assert!(extract_at(YAML, "echo").is_empty());
+        assert!(extract_at(YAML, "Group").is_empty());
+        assert!(extract_at(YAML, "2010-09-09").is_empty())
+    }
+}
@@ -0,0 +1,1234 @@
+use crate::{
+    document::Document,
+    model::method::{
+        completion::{InsertTextFormat, InsertTextMode, Item, ItemKind, List},
+        diagnostic::Position,
+        hover::MarkupContent,
+        text_document::TextEdit,
+    },
+    schema::{Property, ResourceSchema, SchemaRegistry, Shape},
+    template::{Entry, Format, Intrinsic, Node, PSEUDO_PARAMETERS, Segment, Span, Value},
+};
+
+use super::hover;
+
+/// Spaces added per level of nesting in inserted YAML
+const INDENT: usize = 2;
+
+/// Deepest path of fields offered for an object attribute, which guards
+/// against definitions that refer to each other in a cycle
+const MAX_ATTRIBUTE_DEPTH: usize = 4;
+
+/// Completion items for the value, key or function argument under the
+/// cursor. Items only insert snippets if the client supports them
+pub fn completion(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    position: &Position,
+    snippets: bool,
+) -> Option<List> {
+    let offset = document.offset(position);
+    if let Some(span) = resource_type_span(document, offset) {
+        return Some(List::new(resource_types(document, schemas, span, offset)));
+    }
+    if let Some(argument) = function_argument(document, offset) {
+        return Some(List::new(targets(document, schemas, &argument, offset)));
+    }
+    let key = match document.template().format() {
+        Format::Yaml => yaml_key(document, offset),
+        Format::Json => json_key(document, offset),
+    }?;
+    let items = property_keys(document, schemas, &key, offset, snippets)?;
+    Some(List::new(items))
+}
+
+/// Span of the `Type` value of the resource under the cursor, excluding any
+/// quotes. The span is empty if no type has been written yet
+fn resource_type_span(document: &Document, offset: usize) -> Option<Span> {
+    document
+        .template()
+        .resources()?
+        .items()
+        .iter()
+        .find_map(|resource| {
+            let entry = resource.body().entry("Type")?;
+            let value = entry.value();
+            match value.value() {
+                Value::String(_) if value.span().contains(offset) => {
+                    Some(unquoted(document, value.span()))
+                }
+                Value::Null if offset >= entry.key().span().end() => {
+                    // The cursor may be past the end of the empty value, as
+                    // long as it hasn't left the line
+                    let gap = document.slice(Span::new(entry.key().span().end(), offset));
+                    gap.trim_start_matches(['"', '\''])
+                        .trim_start_matches(':')
+                        .chars()
+                        .all(|c| c == ' ' || c == '\t')
+                        .then_some(Span::new(offset, offset))
+                }
+                _ => None,
+            }
+        })
+}
+
+fn unquoted(document: &Document, span: Span) -> Span {
+    let text = document.slice(span);
+    match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let end = match text.len() > 1 && text.ends_with(quote) {
+                true => span.end() - 1,
+                false => span.end(),
+            };
+            Span::new(span.start() + 1, end)
+        }
+        _ => span,
+    }
+}
+
+fn resource_types(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    span: Span,
+    offset: usize,
+) -> Vec<Item> {
+    let typed = document.slice(Span::new(
+        span.start(),
+        offset.clamp(span.start(), span.end()),
+    ));
+    // YAML needs a space between the colon and the value
+    let separator = match span.start() == span.end()
+        && document.slice(Span::new(span.start().saturating_sub(1), span.start())) == ":"
+    {
+        true => " ",
+        false => "",
+    };
+    let range = document.range(span);
+
+    let mut matches: Vec<_> = schemas
+        .resource_types()
+        .filter_map(|schema| Some((fuzzy_score(&typed, schema.type_name())?, schema)))
+        .collect();
+    matches.sort_by(|(score, schema), (other_score, other)| {
+        other_score
+            .cmp(score)
+            .then_with(|| schema.type_name().len().cmp(&other.type_name().len()))
+            .then_with(|| schema.type_name().cmp(other.type_name()))
+    });
+
+    matches
+        .iter()
+        .enumerate()
+        .map(|(rank, (_, schema))| {
+            Item::builder()
+                .label(schema.type_name())
+                .kind(ItemKind::Class)
+                .detail(detail(schema))
+                .documentation(MarkupContent::markdown(&hover::resource_type(schema)))
+                .sort_text(format!("{rank:05}"))
+                .filter_text(schema.type_name())
+                .text_edit(TextEdit::new(
+                    range.clone(),
+                    &format!("{separator}{}", schema.type_name()),
+                ))
+                .build()
+        })
+        .collect()
+}
+
+/// Paths `Fn::GetAtt` can return a value for. An object attribute gives way
+/// to the paths of its fields, which schemas don't always list as read-only
+/// themselves
+fn attribute_paths(schema: &ResourceSchema) -> Vec<(String, &Shape)> {
+    let mut paths = Vec::new();
+    for attribute in schema.attributes() {
+        field_paths(schema, attribute.name(), attribute.shape(), &mut paths);
+    }
+    paths
+}
+
+fn field_paths<'a>(
+    schema: &'a ResourceSchema,
+    path: &str,
+    shape: &'a Shape,
+    paths: &mut Vec<(String, &'a Shape)>,
+) {
+    match schema.resolve(shape) {
+        Shape::Object {
+            properties: fields, ..
+        } if !fields.is_empty() => {
+            if path.split('.').count() >= MAX_ATTRIBUTE_DEPTH {
+                return;
+            }
+            for field in fields {
+                let path = format!("{path}.{}", field.name());
+                field_paths(schema, &path, field.shape(), paths);
+            }
+        }
+        _ if paths.iter().any(|(existing, _)| existing == path) => {}
+        _ => paths.push((path.into(), shape)),
+    }
+}
+
+/// First sentence of the type's description
+fn detail(schema: &ResourceSchema) -> String {
+    schema
+        .description()
+        .and_then(|description| description.lines().next())
+        .map(|line| match line.find(". ") {
+            Some(end) => line[..=end].to_string(),
+            None => line.to_string(),
+        })
+        .unwrap_or_else(|| "Resource type".into())
+}
+
+/// Scores a candidate containing all characters of the typed text in order,
+/// ignoring case. Runs of consecutive characters and matches at the start of
+/// a word score higher
+pub fn fuzzy_score(typed: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for c in typed.chars() {
+        let index = (next..candidate.len()).find(|&i| candidate[i].eq_ignore_ascii_case(&c))?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 2;
+        }
+        let word_start = match index.checked_sub(1).map(|i| candidate[i]) {
+            None => true,
+            Some(before) => {
+                !before.is_alphanumeric()
+                    || (before.is_lowercase() && candidate[index].is_uppercase())
+            }
+        };
+        if word_start {
+            score += 3;
+        }
+        previous = Some(index);
+        next = index + 1;
+    }
+    Some(score)
+}
+
+/// What the argument of a `Ref` or `Fn::GetAtt` under the cursor refers to
+#[derive(Debug, PartialEq, Eq)]
+enum Target {
+    /// A parameter, resource or pseudo parameter
+    Ref,
+    /// The resource part of `Fn::GetAtt`
+    Resource,
+    /// An attribute of the named resource
+    Attribute(String),
+}
+
+/// The part of a function argument being written
+#[derive(Debug)]
+struct Argument {
+    target: Target,
+    /// Text replaced by the completion, excluding any quotes
+    span: Span,
+}
+
+/// Finds the `Ref` or `Fn::GetAtt` argument under the cursor, in either the
+/// `Bucket.Arn` or `[Bucket, Arn]` form of `Fn::GetAtt`
+fn function_argument(document: &Document, offset: usize) -> Option<Argument> {
+    let entry = empty_entry(document, offset);
+    let path = document.template().root().path_to(entry.unwrap_or(offset));
+    let Some(index) = path
+        .iter()
+        .rposition(|segment| matches!(segment, Segment::Function(_)))
+    else {
+        return empty_argument(document, offset);
+    };
+    let Segment::Function(function) = path[index] else {
+        return None;
+    };
+    let argument = function.argument();
+    if let Some(entry) = entry {
+        let index = argument
+            .as_sequence()?
+            .iter()
+            .position(|item| matches!(item.value(), Value::Null) && item.span().end() == entry)?;
+        return match function.intrinsic() {
+            Intrinsic::GetAtt => sequence_argument(argument, index, offset),
+            _ => None,
+        };
+    }
+    if function.name_span().contains(offset) || !argument.span().contains(offset) {
+        return empty_argument(document, offset);
+    }
+
+    let (target, span) = match (function.intrinsic(), &path[index + 1..], argument.value()) {
+        (Intrinsic::Ref, [], Value::String(_) | Value::Null) => {
+            (Target::Ref, unquoted(document, argument.span()))
+        }
+        (Intrinsic::GetAtt, [], Value::String(_)) => {
+            let span = unquoted(document, argument.span());
+            let text = document.slice(span);
+            match text.find('.') {
+                Some(dot) if span.start() + dot < offset => (
+                    Target::Attribute(text[..dot].into()),
+                    Span::new(span.start() + dot + 1, span.end()),
+                ),
+                Some(dot) => (
+                    Target::Resource,
+                    Span::new(span.start(), span.start() + dot),
+                ),
+                None => (Target::Resource, span),
+            }
+        }
+        (Intrinsic::GetAtt, [], Value::Null) => (Target::Resource, argument.span()),
+        (Intrinsic::GetAtt, [], Value::Sequence(items)) => {
+            // Between the items of the list rather than on one of them
+            let index = items
+                .iter()
+                .filter(|item| item.span().end() < offset)
+                .count();
+            return sequence_argument(argument, index, offset);
+        }
+        (Intrinsic::GetAtt, [Segment::Item(0, item)], _) => {
+            (Target::Resource, unquoted(document, item.span()))
+        }
+        (Intrinsic::GetAtt, [Segment::Item(1, item)], Value::Sequence(items)) => (
+            Target::Attribute(items[0].as_str()?.into()),
+            unquoted(document, item.span()),
+        ),
+        _ => return None,
+    };
+    Some(Argument { target, span })
+}
+
+/// The end of the `-` of a block sequence entry with nothing written after
+/// it, on the cursor's line. The parser ends the empty entry there, before
+/// the cursor
+fn empty_entry(document: &Document, offset: usize) -> Option<usize> {
+    let line_start = document.line_start(offset);
+    let before = document.slice(Span::new(line_start, offset));
+    let indicator = before.trim_end_matches([' ', '\t']);
+    let indentation = indicator.strip_suffix('-')?;
+    (indicator.len() < before.len() && indentation.chars().all(|c| c == ' '))
+        .then_some(line_start + indicator.len())
+}
+
+/// The resource or attribute written as an item of the `[Bucket, Arn]` form
+/// of `Fn::GetAtt`, where there's nothing written yet
+fn sequence_argument(argument: &Node, index: usize, offset: usize) -> Option<Argument> {
+    let items = argument.as_sequence()?;
+    let target = match index {
+        0 => Target::Resource,
+        1 => Target::Attribute(items[0].as_str()?.into()),
+        _ => return None,
+    };
+    Some(Argument {
+        target,
+        span: Span::new(offset, offset),
+    })
+}
+
+/// Recognises `!Ref ` and friends with nothing after them yet, which the
+/// parser gives an empty argument ending before the cursor
+fn empty_argument(document: &Document, offset: usize) -> Option<Argument> {
+    let line_start = document.line_start(offset);
+    let before = document.slice(Span::new(line_start, offset));
+    if !before.ends_with([' ', '\t']) {
+        return None;
+    }
+    let before = before.trim_end();
+    let target = if before.ends_with("!Ref") || before.ends_with("Ref:") {
+        Target::Ref
+    } else if before.ends_with("!GetAtt") || before.ends_with("Fn::GetAtt:") {
+        Target::Resource
+    } else {
+        return None;
+    };
+    Some(Argument {
+        target,
+        span: Span::new(offset, offset),
+    })
+}
+
+/// Completion item before ranking
+struct Candidate {
+    label: String,
+    kind: ItemKind,
+    detail: String,
+    documentation: Option<String>,
+}
+
+fn targets(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    argument: &Argument,
+    offset: usize,
+) -> Vec<Item> {
+    let template = document.template();
+    let section = template.section_at(offset);
+    // A resource can't refer to itself
+    let current = match section {
+        Some("Resources") => template.resources().and_then(|resources| {
+            resources
+                .items()
+                .iter()
+                .take_while(|resource| resource.logical_id().span().start() <= offset)
+                .last()
+                .map(|resource| resource.logical_id().name())
+        }),
+        _ => None,
+    };
+    let resources = || {
+        template
+            .resources()
+            .map(|resources| resources.items())
+            .unwrap_or_default()
+            .iter()
+            .filter(move |resource| Some(resource.logical_id().name()) != current)
+            .map(|resource| {
+                let type_name = resource
+                    .resource_type()
+                    .and_then(|node| node.as_str())
+                    .unwrap_or("Resource");
+                Candidate {
+                    label: resource.logical_id().name().into(),
+                    kind: ItemKind::Reference,
+                    detail: type_name.into(),
+                    documentation: None,
+                }
+            })
+    };
+
+    // Each group is offered after the ones before it when scores are equal
+    let groups: Vec<Vec<Candidate>> = match &argument.target {
+        Target::Ref => {
+            let parameters = template
+                .parameters()
+                .map(|parameters| parameters.items())
+                .unwrap_or_default()
+                .iter()
+                .map(|parameter| Candidate {
+                    label: parameter.logical_id().name().into(),
+                    kind: ItemKind::Variable,
+                    detail: parameter
+                        .parameter_type()
+                        .and_then(|node| node.as_str())
+                        .unwrap_or("Parameter")
+                        .into(),
+                    documentation: parameter
+                        .get("Description")
+                        .and_then(|node| node.as_str())
+                        .map(String::from),
+                })
+                .collect();
+            let pseudo_parameters = PSEUDO_PARAMETERS
+                .iter()
+                .map(|(name, description)| Candidate {
+                    label: (*name).into(),
+                    kind: ItemKind::Constant,
+                    detail: "Pseudo parameter".into(),
+                    documentation: Some((*description).into()),
+                })
+                .collect();
+            // Conditions and rules are evaluated before any resource exists
+            match section {
+                Some("Conditions" | "Rules") => vec![parameters, pseudo_parameters],
+                _ => vec![parameters, resources().collect(), pseudo_parameters],
+            }
+        }
+        Target::Resource => vec![resources().collect()],
+        Target::Attribute(logical_id) => {
+            let schema = template
+                .resources()
+                .and_then(|resources| resources.get(logical_id))
+                .and_then(|resource| resource.resource_type()?.as_str())
+                .and_then(|type_name| schemas.get(type_name));
+            let attributes = schema
+                .into_iter()
+                .flat_map(|schema| {
+                    attribute_paths(schema)
+                        .into_iter()
+                        .map(|(path, shape)| Candidate {
+                            label: path,
+                            kind: ItemKind::Property,
+                            detail: schema.describe(shape),
+                            documentation: None,
+                        })
+                })
+                .collect();
+            vec![attributes]
+        }
+    };
+
+    let typed = document.slice(Span::new(
+        argument.span.start(),
+        offset.clamp(argument.span.start(), argument.span.end()),
+    ));
+    let mut matches: Vec<_> = groups
+        .into_iter()
+        .enumerate()
+        .flat_map(|(group, candidates)| {
+            candidates
+                .into_iter()
+                .map(move |candidate| (group, candidate))
+        })
+        .filter_map(|(group, candidate)| {
+            Some((fuzzy_score(&typed, &candidate.label)?, group, candidate))
+        })
+        .collect();
+    matches.sort_by(
+        |(score, group, candidate), (other_score, other_group, other)| {
+            other_score
+                .cmp(score)
+                .then_with(|| group.cmp(other_group))
+                .then_with(|| candidate.label.cmp(&other.label))
+        },
+    );
+
+    let range = document.range(argument.span);
+    matches
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (_, _, candidate))| {
+            Item::builder()
+                .label(candidate.label.as_str())
+                .kind(candidate.kind)
+                .detail(candidate.detail)
+                .maybe_documentation(
+                    candidate
+                        .documentation
+                        .map(|text| MarkupContent::markdown(&text)),
+                )
+                .sort_text(format!("{rank:05}"))
+                .filter_text(candidate.label.as_str())
+                .text_edit(TextEdit::new(range.clone(), &candidate.label))
+                .build()
+        })
+        .collect()
+}
+
+/// A mapping key being written
+#[derive(Debug)]
+struct KeyContext {
+    /// Keys of the enclosing mappings, from the top of the template
+    path: Vec<String>,
+    /// Keys already present in the mapping
+    existing: Vec<String>,
+    /// Text replaced by the completion
+    span: Span,
+    /// Column of the key, which nested lines are indented from
+    column: usize,
+    /// Whether the key is already followed by a colon, in which case only
+    /// the name is replaced
+    has_value: bool,
+}
+
+/// Works out the key under the cursor from indentation alone, as a half
+/// written key is usually a syntax error which the parser has skipped over
+fn yaml_key(document: &Document, offset: usize) -> Option<KeyContext> {
+    let text = document.text();
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+
+    let before = &text[line_start..offset];
+    let line = YamlLine::parse(before).unwrap_or(YamlLine {
+        dash: None,
+        column: before.len(),
+        key: None,
+    });
+    let typed = &before[line.column..];
+    if !typed.chars().all(is_key_char) {
+        return None;
+    }
+    let after = &text[offset..line_end];
+    let rest = after.trim_start_matches(is_key_char);
+    let span = Span::new(offset - typed.len(), line_end - rest.len());
+
+    let above: Vec<_> = text[..line_start]
+        .lines()
+        .rev()
+        .filter_map(YamlLine::parse)
+        .collect();
+    let below = text[line_end..].lines().skip(1).filter_map(YamlLine::parse);
+
+    let mut existing = Vec::new();
+    if line.dash.is_none() {
+        for other in &above {
+            if other.column < line.column {
+                break;
+            }
+            if other.column == line.column {
+                existing.extend(other.key.map(String::from));
+                if other.dash.is_some() {
+                    break;
+                }
+            }
+        }
+    }
+    for other in below {
+        if other.column < line.column || other.dash.is_some_and(|dash| dash < line.column) {
+            break;
+        }
+        if other.column == line.column {
+            existing.extend(other.key.map(String::from));
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut limit = line.dash.unwrap_or(line.column);
+    for other in &above {
+        if limit == 0 {
+            break;
+        }
+        if other.column < limit {
+            path.push(other.key?.to_string());
+            limit = other.dash.unwrap_or(other.column);
+        } else if let Some(dash) = other.dash.filter(|dash| *dash < limit) {
+            // An earlier key of the list item the cursor is in
+            limit = dash;
+        }
+    }
+    if limit != 0 {
+        return None;
+    }
+    path.reverse();
+
+    Some(KeyContext {
+        path,
+        existing,
+        span,
+        column: line.column,
+        has_value: rest.trim_start().starts_with(':'),
+    })
+}
+
+/// The indentation and key of a line of block YAML
+#[derive(Debug)]
+struct YamlLine<'a> {
+    /// Column of the first sequence entry indicator, if any
+    dash: Option<usize>,
+    /// Column of the content after any sequence entry indicators
+    column: usize,
+    key: Option<&'a str>,
+}
+
+impl<'a> YamlLine<'a> {
+    /// Reads a line, skipping those which are blank or only a comment
+    fn parse(line: &'a str) -> Option<Self> {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let mut column = indent;
+        let mut dash = None;
+        let mut rest = &line[indent..];
+        while let Some(item) = rest.strip_prefix('-').filter(|item| item.starts_with(' ')) {
+            dash.get_or_insert(column);
+            let content = item.trim_start_matches(' ');
+            column += rest.len() - content.len();
+            rest = content;
+        }
+        let content = rest.trim_end();
+        if (content.is_empty() && dash.is_none()) || content.starts_with('#') {
+            return None;
+        }
+        let key = content
+            .split_once(':')
+            .filter(|(_, value)| value.is_empty() || value.starts_with([' ', '\t']))
+            .map(|(key, _)| key.trim_matches(['"', '\'']));
+        Some(Self { dash, column, key })
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Finds the key under the cursor, or the gap for a new key, in the parsed
+/// JSON, which keeps half written keys as entries without values
+fn json_key(document: &Document, offset: usize) -> Option<KeyContext> {
+    let segments = document.template().root().path_to(offset);
+    let mut path = Vec::new();
+    for segment in &segments {
+        match segment {
+            Segment::Value(entry) => path.push(entry.key().name().to_string()),
+            Segment::Item(..) => {}
+            Segment::Key(entry) => {
+                let mapping = match segments.iter().rev().nth(1) {
+                    Some(Segment::Value(parent)) => parent.value(),
+                    Some(Segment::Item(_, item)) => item,
+                    _ => document.template().root(),
+                };
+                let span = entry.key().span();
+                let after = document.slice(Span::new(span.end(), entry.value().span().start()));
+                return Some(KeyContext {
+                    path,
+                    existing: keys(mapping.as_mapping()?, Some(entry.key().name())),
+                    span,
+                    column: 0,
+                    has_value: after.contains(':'),
+                });
+            }
+            Segment::Function(_) => return None,
+        }
+    }
+    let mapping = match segments.last()? {
+        Segment::Value(entry) => entry.value(),
+        Segment::Item(_, item) => item,
+        _ => return None,
+    };
+    Some(KeyContext {
+        path,
+        existing: keys(mapping.as_mapping()?, None),
+        span: Span::new(offset, offset),
+        column: 0,
+        has_value: false,
+    })
+}
+
+fn keys(entries: &[Entry], except: Option<&str>) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| entry.key().name())
+        .filter(|name| Some(*name) != except)
+        .map(String::from)
+        .collect()
+}
+
+/// Properties of the resource, or nested property, whose `Properties`
+/// mapping the key is in
+fn property_keys(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    key: &KeyContext,
+    offset: usize,
+    snippets: bool,
+) -> Option<Vec<Item>> {
+    let [section, logical_id, properties, names @ ..] = key.path.as_slice() else {
+        return None;
+    };
+    if section != "Resources" || properties != "Properties" {
+        return None;
+    }
+    let template = document.template();
+    let resource = template.resources()?.get(logical_id)?;
+    let schema = schemas.get(resource.resource_type()?.as_str()?)?;
+    let candidates = match names {
+        [] => schema.properties(),
+        _ => {
+            let names: Vec<_> = names.iter().map(String::as_str).collect();
+            schema.properties_of(schema.property_at(&names)?.shape())
+        }
+    };
+
+    let quoted = template.format() == Format::Json;
+    let typed = document.slice(Span::new(key.span.start(), offset.max(key.span.start())));
+    let typed = typed.trim_start_matches('"');
+    let mut matches: Vec<_> = candidates
+        .iter()
+        .filter(|property| !key.existing.iter().any(|name| name == property.name()))
+        .filter_map(|property| Some((fuzzy_score(typed, property.name())?, property)))
+        .collect();
+    matches.sort_by(|(score, property), (other_score, other)| {
+        other_score
+            .cmp(score)
+            .then_with(|| other.required().cmp(&property.required()))
+            .then_with(|| property.name().cmp(other.name()))
+    });
+
+    let range = document.range(key.span);
+    let items = matches
+        .iter()
+        .enumerate()
+        .map(|(rank, (_, property))| {
+            let url = match names {
+                [] => schema.property_documentation_url(property.name()),
+                _ => schema.documentation_url(),
+            };
+            let mut detail = schema.describe(property.shape());
+            if property.required() {
+                detail.push_str(" (required)");
+            }
+            let name = match quoted {
+                true => format!("\"{}\"", property.name()),
+                false => property.name().to_string(),
+            };
+            let (text, format) = match (key.has_value, snippets) {
+                (true, _) => (name.clone(), InsertTextFormat::PlainText),
+                (false, true) => (
+                    snippet(schema, property, &name, key.column, quoted),
+                    InsertTextFormat::Snippet,
+                ),
+                (false, false) => (
+                    skeleton(schema, property, &name, key.column, quoted),
+                    InsertTextFormat::PlainText,
+                ),
+            };
+            Item::builder()
+                .label(property.name())
+                .kind(ItemKind::Property)
+                .detail(detail)
+                .documentation(MarkupContent::markdown(&hover::property(
+                    schema, property, url,
+                )))
+                .sort_text(format!("{rank:05}"))
+                .filter_text(name)
+                .insert_text_format(format)
+                .insert_text_mode(InsertTextMode::AsIs)
+                .text_edit(TextEdit::new(range.clone(), &text))
+                .build()
+        })
+        .collect();
+    Some(items)
+}
+
+/// Key and value with tab stops for the parts to fill in: a choice of the
+/// allowed values of strings, a list item, or the required keys of objects
+fn snippet(
+    schema: &ResourceSchema,
+    property: &Property,
+    name: &str,
+    column: usize,
+    json: bool,
+) -> String {
+    let shape = schema.resolve(property.shape());
+    if json {
+        return match shape {
+            Shape::String { allowed_values, .. } if !allowed_values.is_empty() => {
+                format!("{name}: \"${{1|{}|}}\"", choices(allowed_values))
+            }
+            Shape::String { .. } => format!("{name}: \"$1\""),
+            Shape::List(_) => format!("{name}: [$1]"),
+            Shape::Object { .. } | Shape::Map(_) => format!("{name}: {{$1}}"),
+            _ => format!("{name}: $1"),
+        };
+    }
+    let nested = format!("\n{}", " ".repeat(column + INDENT));
+    match shape {
+        Shape::String { allowed_values, .. } if !allowed_values.is_empty() => {
+            format!("{name}: ${{1|{}|}}", choices(allowed_values))
+        }
+        Shape::List(item) => {
+            let keys = required_keys(schema, item);
+            match keys.is_empty() {
+                true => format!("{name}:{nested}- $1"),
+                false => {
+                    let separator = format!("{nested}{}", " ".repeat(INDENT));
+                    format!("{name}:{nested}- {}", tab_stops(&keys).join(&separator))
+                }
+            }
+        }
+        Shape::Object { .. } => {
+            let keys = required_keys(schema, shape);
+            match keys.is_empty() {
+                true => format!("{name}:{nested}$1"),
+                false => format!("{name}:{nested}{}", tab_stops(&keys).join(&nested)),
+            }
+        }
+        Shape::Map(_) => format!("{name}:{nested}$1"),
+        _ => format!("{name}: $1"),
+    }
+}
+
+/// Key and the start of its value, for clients without snippet support
+fn skeleton(
+    schema: &ResourceSchema,
+    property: &Property,
+    name: &str,
+    column: usize,
+    json: bool,
+) -> String {
+    let nested = format!("\n{}", " ".repeat(column + INDENT));
+    match (schema.resolve(property.shape()), json) {
+        (_, true) => format!("{name}: "),
+        (Shape::List(_), false) => format!("{name}:{nested}- "),
+        (Shape::Object { .. } | Shape::Map(_), false) => format!("{name}:{nested}"),
+        (_, false) => format!("{name}: "),
+    }
+}
+
+/// Options of a choice tab stop, escaping the characters which delimit them
+fn choices(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| {
+            value
+                .replace('\\', "\\\\")
+                .replace(',', "\\,")
+                .replace('|', "\\|")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn required_keys<'a>(schema: &'a ResourceSchema, shape: &'a Shape) -> Vec<&'a str> {
+    match schema.resolve(shape) {
+        Shape::Object { properties, .. } => properties
+            .iter()
+            .filter(|property| property.required())
+            .map(Property::name)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn tab_stops(keys: &[&str]) -> Vec<String> {
+    keys.iter()
+        .enumerate()
+        .map(|(index, key)| format!("{key}: ${}", index + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::{document, registry};
+
+    use super::*;
+
+    fn complete(text: &str, line: usize, character: usize) -> Option<List> {
+        complete_with(text, line, character, true)
+    }
+
+    fn complete_with(text: &str, line: usize, character: usize, snippets: bool) -> Option<List> {
+        completion(
+            &document(text),
+            &registry(),
+            &Position::new(line, character),
+            snippets,
+        )
+    }
+
+    fn new_text(list: &List, label: &str) -> String {
+        list.items()
+            .iter()
+            .find(|item| item.label() == label)
+            .and_then(Item::text_edit)
+            .map(|edit| edit.new_text().to_string())
+            .unwrap()
+    }
+
+    fn labels(list: &List) -> Vec<&str> {
+        list.items().iter().map(Item::label).collect()
+    }
+
+    mod resource_type_tests {
+        use super::*;
+
+        #[test]
+        fn completes_partial_type() {
+            let actual =
+                complete("Resources:\n  Bucket:\n    Type: AWS::S3::Buck\n", 2, 19).unwrap();
+
+            assert_eq!(
+                labels(&actual),
+                vec!["AWS::S3::Bucket", "AWS::S3::BucketPolicy"]
+            );
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.range().start(), &Position::new(2, 10));
+            assert_eq!(edit.range().end(), &Position::new(2, 23));
+            assert_eq!(edit.new_text(), "AWS::S3::Bucket");
+            assert_eq!(actual.items()[0].detail(), Some("Creates a bucket."))
+        }
+
+        #[test]
+        fn offers_all_types_for_empty_value() {
+            let actual = complete("Resources:\n  Bucket:\n    Type: \n", 2, 10).unwrap();
+
+            assert_eq!(actual.items().len(), 4);
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.range().start(), &Position::new(2, 10));
+            assert_eq!(edit.new_text(), "AWS::S3::Bucket")
+        }
+
+        #[test]
+        fn inserts_space_after_colon() {
+            let actual = complete("Resources:\n  Bucket:\n    Type:\n", 2, 9).unwrap();
+            assert_eq!(
+                actual.items()[0].text_edit().unwrap().new_text(),
+                " AWS::S3::Bucket"
+            )
+        }
+
+        #[test]
+        fn matches_fuzzily() {
+            let actual = complete("Resources:\n  Queue:\n    Type: sqsq\n", 2, 14).unwrap();
+            assert_eq!(labels(&actual), vec!["AWS::SQS::Queue"])
+        }
+
+        #[test]
+        fn replaces_inside_json_quotes() {
+            let text = "{\"Resources\": {\"Queue\": {\"Type\": \"AWS::SQS\"}}}";
+            let actual = complete(text, 0, 42).unwrap();
+
+            assert_eq!(labels(&actual), vec!["AWS::SQS::Queue"]);
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.range().start(), &Position::new(0, 34));
+            assert_eq!(edit.range().end(), &Position::new(0, 42))
+        }
+
+        #[test]
+        fn ignores_other_values() {
+            let text = "Resources:\n  Bucket:\n    Type: AWS::S3::Bucket\n    Condition: Yes\n";
+            assert_eq!(complete(text, 3, 16), None)
+        }
+    }
+
+    mod target_tests {
+        use super::*;
+
+        const TEMPLATE: &str = "\
+Parameters:
+  Env:
+    Type: String
+    Description: Deployment stage
+Conditions:
+  IsProd: !Equals [!Ref Env, prod]
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      QueueName: !Ref Bucket
+Outputs:
+  Arn:
+    Value: !GetAtt Bucket.Arn
+";
+
+        /// Completes at the `|` in text replacing the end of a line, from the
+        /// first occurrence of `from`
+        fn complete_in(line: usize, from: &str, to: &str) -> List {
+            let mut lines: Vec<String> = TEMPLATE.lines().map(String::from).collect();
+            let (before, _) = lines[line].split_once(from).unwrap();
+            let cursor = before.len() + to.find('|').unwrap();
+            lines[line] = format!("{before}{}", to.replace('|', ""));
+            complete(&lines.join("\n"), line, cursor).unwrap()
+        }
+
+        #[test]
+        fn offers_parameters_resources_and_pseudo_parameters() {
+            let actual = complete_in(12, "!Ref Bucket", "!Ref |");
+            let labels = labels(&actual);
+
+            assert_eq!(&labels[..2], &["Env", "Bucket"]);
+            assert!(labels.contains(&"AWS::Region"));
+            assert!(!labels.contains(&"Queue"));
+            assert_eq!(actual.items()[0].detail(), Some("String"))
+        }
+
+        #[test]
+        fn filters_by_typed_text() {
+            let actual = complete_in(12, "!Ref Bucket", "!Ref Buc|");
+
+            assert_eq!(labels(&actual), vec!["Bucket"]);
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.range().start(), &Position::new(12, 22));
+            assert_eq!(edit.range().end(), &Position::new(12, 25))
+        }
+
+        #[test]
+        fn offers_references_after_wide_characters() {
+            let actual = complete("\u{1F600}: !Ref \n", 0, 9).unwrap();
+
+            assert!(labels(&actual).contains(&"AWS::Region"))
+        }
+
+        #[test]
+        fn offers_only_parameters_in_conditions() {
+            let actual = complete_in(5, "Env, prod]", "E|, prod]");
+            let labels = labels(&actual);
+
+            assert!(labels.contains(&"Env"));
+            assert!(!labels.contains(&"Bucket"));
+            assert!(!labels.contains(&"Queue"))
+        }
+
+        #[test]
+        fn offers_resources_for_get_att() {
+            let actual = complete_in(15, "Bucket.Arn", "Q|");
+            assert_eq!(labels(&actual), vec!["Queue"])
+        }
+
+        #[test]
+        fn offers_attributes_of_resource_type() {
+            let actual = complete_in(15, "Arn", "|");
+            assert_eq!(labels(&actual), vec!["Arn"]);
+            assert_eq!(actual.items()[0].detail(), Some("String"))
+        }
+
+        #[test]
+        fn offers_attributes_for_empty_block_items() {
+            let text = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+Outputs:
+  Arn:
+    Value:
+      Fn::GetAtt:
+        - Bucket
+        - \n";
+            assert_eq!(labels(&complete(text, 8, 10).unwrap()), vec!["Arn"]);
+            let text = text.replace("        - Bucket\n        - \n", "        - \n");
+            assert_eq!(labels(&complete(&text, 7, 10).unwrap()), vec!["Bucket"])
+        }
+
+        #[test]
+        fn offers_fields_of_object_attributes() {
+            let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+Outputs:
+  Arn:
+    Value: !GetAtt Function.
+";
+            assert_eq!(
+                labels(&complete(text, 5, 28).unwrap()),
+                vec![
+                    "Arn",
+                    "SnapStartResponse.ApplyOn",
+                    "SnapStartResponse.OptimizationStatus"
+                ]
+            )
+        }
+
+        #[test]
+        fn offers_attributes_in_list_form() {
+            let actual = complete_in(15, "!GetAtt Bucket.Arn", "{Fn::GetAtt: [Bucket, A|]}");
+            let text = actual.items()[0].text_edit().unwrap();
+
+            assert_eq!(labels(&actual), vec!["Arn"]);
+            assert_eq!(text.range().start(), &Position::new(15, 33))
+        }
+    }
+
+    mod property_key_tests {
+        use super::*;
+
+        const PROPERTIES: &str = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      BucketName: b
+";
+
+        fn with_line(line: &str) -> String {
+            format!("{PROPERTIES}{line}\n")
+        }
+
+        #[test]
+        fn offers_missing_properties_required_first() {
+            let actual = complete(&with_line("      "), 5, 6).unwrap();
+
+            assert_eq!(
+                labels(&actual),
+                vec!["AccessControl", "LoggingConfiguration", "Tags"]
+            );
+            assert_eq!(actual.items()[0].detail(), Some("String (required)"))
+        }
+
+        #[test]
+        fn inserts_choice_of_allowed_values() {
+            let actual = complete(&with_line("      Acc"), 5, 9).unwrap();
+
+            assert_eq!(labels(&actual), vec!["AccessControl"]);
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.range().start(), &Position::new(5, 6));
+            assert_eq!(edit.range().end(), &Position::new(5, 9));
+            assert_eq!(edit.new_text(), "AccessControl: ${1|Private,PublicRead|}");
+            assert_eq!(
+                actual.items()[0].insert_text_format(),
+                Some(&InsertTextFormat::Snippet)
+            )
+        }
+
+        #[test]
+        fn inserts_required_keys_of_list_items() {
+            let actual = complete(&with_line("      Ta"), 5, 8).unwrap();
+            assert_eq!(
+                new_text(&actual, "Tags"),
+                "Tags:\n        - Key: $1\n          Value: $2"
+            )
+        }
+
+        #[test]
+        fn inserts_required_keys_of_objects() {
+            let actual = complete(&with_line("      Logg"), 5, 10).unwrap();
+            assert_eq!(
+                new_text(&actual, "LoggingConfiguration"),
+                "LoggingConfiguration:\n        DestinationBucketName: $1"
+            )
+        }
+
+        #[test]
+        fn completes_nested_properties() {
+            let text = with_line("      LoggingConfiguration:\n        Log");
+            let actual = complete(&text, 6, 11).unwrap();
+            assert_eq!(labels(&actual), vec!["LogFilePrefix"])
+        }
+
+        #[test]
+        fn skips_keys_of_same_list_item() {
+            let text = with_line("      Tags:\n        - Key: a\n          ");
+            let actual = complete(&text, 7, 10).unwrap();
+            assert_eq!(labels(&actual), vec!["Value"])
+        }
+
+        #[test]
+        fn inserts_plain_text_without_snippet_support() {
+            let actual = complete_with(&with_line("      Ta"), 5, 8, false).unwrap();
+
+            assert_eq!(new_text(&actual, "Tags"), "Tags:\n        - ");
+            assert_eq!(
+                actual.items()[0].insert_text_format(),
+                Some(&InsertTextFormat::PlainText)
+            )
+        }
+
+        #[test]
+        fn replaces_only_name_of_existing_key() {
+            let text =
+                "Resources:\n  Bucket:\n    Type: AWS::S3::Bucket\n    Properties:\n      Acc: x\n";
+            let actual = complete(text, 4, 9).unwrap();
+
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.new_text(), "AccessControl");
+            assert_eq!(edit.range().end(), &Position::new(4, 9))
+        }
+
+        #[test]
+        fn completes_json_keys() {
+            let text = r#"{"Resources": {"Bucket": {"Type": "AWS::S3::Bucket", "Properties": {"BucketName": "b", "Acc"}}}}"#;
+            let actual = complete(text, 0, 91).unwrap();
+
+            assert_eq!(labels(&actual), vec!["AccessControl"]);
+            let edit = actual.items()[0].text_edit().unwrap();
+            assert_eq!(edit.range().start(), &Position::new(0, 87));
+            assert_eq!(
+                edit.new_text(),
+                r#""AccessControl": "${1|Private,PublicRead|}""#
+            )
+        }
+
+        #[test]
+        fn ignores_keys_outside_properties() {
+            assert_eq!(complete(&with_line("    "), 5, 4), None)
+        }
+    }
+
+    mod fuzzy_score_tests {
+        use super::*;
+
+        #[test]
+        fn prefers_word_starts() {
+            assert!(
+                fuzzy_score("sb", "AWS::S3::Bucket").unwrap()
+                    > fuzzy_score("sb", "AWS::SageMaker::Notebook").unwrap()
+            )
+        }
+
+        #[test]
+        fn requires_characters_in_order() {
+            assert_eq!(fuzzy_score("bs", "AWS::S3::Bucket"), None)
+        }
+    }
+}
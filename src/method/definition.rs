@@ -0,0 +1,101 @@
+use crate::{
+    document::Document,
+    model::method::{
+        definition,
+        diagnostic::{Location, Position},
+    },
+};
+
+/// Location of the logical id of the parameter, resource, condition or
+/// mapping referred to under the cursor
+pub fn definition(document: &Document, position: &Position) -> Option<definition::Result> {
+    let offset = document.offset(position);
+    let template = document.template();
+    let reference = template
+        .references()
+        .iter()
+        .find(|reference| reference.span().contains(offset))?;
+    let (_, declaration) = template.declaration(reference)?;
+    Some(Location::new(
+        document.uri(),
+        document.range(declaration.logical_id().span()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::initialise::PositionEncoding;
+
+    use super::*;
+
+    const TEMPLATE: &str = "\
+Parameters:
+  Env:
+    Type: String
+Mappings:
+  Names:
+    prod:
+      Bucket: data
+Conditions:
+  IsProd: !Equals [!Ref Env, prod]
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+  Bucket:
+    Type: AWS::S3::Bucket
+    Condition: IsProd
+    Properties:
+      BucketName: !If [IsProd, !FindInMap [Names, !Ref Env, Bucket], !Sub '${Queue.QueueName}-dev']
+Outputs:
+  Arn:
+    Value: !GetAtt Bucket.Arn
+";
+
+    /// Line of the declaration found from the first occurrence of `marker`
+    /// on a line
+    fn definition_line(line: usize, marker: &str) -> Option<usize> {
+        let document = Document::new("file:///cf", "yaml", 1, TEMPLATE, PositionEncoding::Utf16);
+        let character = TEMPLATE.lines().nth(line).unwrap().find(marker).unwrap() + 1;
+        definition(&document, &Position::new(line, character))
+            .map(|location| location.range().start().line())
+    }
+
+    #[test]
+    fn finds_parameter_of_ref() {
+        assert_eq!(definition_line(8, "Env"), Some(1))
+    }
+
+    #[test]
+    fn finds_resource_of_get_att() {
+        assert_eq!(definition_line(19, "Bucket"), Some(12))
+    }
+
+    #[test]
+    fn finds_resource_of_sub_variable() {
+        assert_eq!(definition_line(16, "Queue"), Some(10))
+    }
+
+    #[test]
+    fn finds_conditions() {
+        assert_eq!(definition_line(14, "IsProd"), Some(8));
+        assert_eq!(definition_line(16, "IsProd"), Some(8))
+    }
+
+    #[test]
+    fn finds_mapping() {
+        assert_eq!(definition_line(16, "Names"), Some(4))
+    }
+
+    #[test]
+    fn ignores_pseudo_parameters_and_plain_values() {
+        let document = Document::new(
+            "file:///cf",
+            "yaml",
+            1,
+            "Outputs:\n  A:\n    Value: !Ref AWS::Region\n",
+            PositionEncoding::Utf16,
+        );
+        assert!(definition(&document, &Position::new(2, 18)).is_none());
+        assert_eq!(definition_line(2, "String"), None)
+    }
+}
@@ -1,11 +1,25 @@
-use crate::model::method::diagnostic::Diagnostic;
+use crate::{
+    document::Document,
+    model::method::diagnostic::{Diagnostic, Severity},
+    schema::SchemaRegistry,
+    template::Span,
+};
 use core::str;
 use std::{
     fmt::{Debug, Display},
-    process::{Command, Output},
+    io::Write,
+    process::{Command, Output, Stdio},
 };
 
 const CFN_LINT: &str = "cfn-lint";
+const CFN_LSP: &str = "cfn-lsp";
+const SYNTAX_ERROR: &str = "syntax-error";
+
+mod cycles;
+mod deprecated;
+mod properties;
+mod unresolved;
+mod unused;
 
 pub struct LintError {
     message: String,
@@ -18,17 +32,16 @@ impl Display for LintError {
 }
 
 pub trait Lint: Debug {
-    fn lint(&self, uri: &str) -> Result<Vec<Diagnostic>, LintError>;
+    fn lint(&self, document: &Document) -> Result<Vec<Diagnostic>, LintError>;
 }
 
 #[derive(Debug, Clone)]
 pub struct CfnLinter;
 
 impl Lint for CfnLinter {
-    fn lint(&self, uri: &str) -> Result<Vec<Diagnostic>, LintError> {
-        let path = extract_file_path(uri);
-        tracing::debug!("Invoking cfn-lint for file '{path}'");
-        let result = execute_linter(&path)?;
+    fn lint(&self, document: &Document) -> Result<Vec<Diagnostic>, LintError> {
+        tracing::debug!("Invoking cfn-lint for file '{}'", document.uri());
+        let result = execute_linter(&document.text())?;
 
         if result.status.success() {
             Ok(Vec::new())
@@ -40,28 +53,113 @@ impl Lint for CfnLinter {
                 serde_json::from_str(response).map_err(|e| LintError {
                     message: format!("Linter reponse didn't match expected structure: {e}"),
                 })?;
-            Ok(diagnostics.into_iter().map(From::from).collect())
+            Ok(diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.into_diagnostic(document))
+                .collect())
         }
     }
 }
 
-fn execute_linter(uri: &str) -> Result<Output, LintError> {
-    Command::new(CFN_LINT)
-        .args(["--template", uri, "--format", "json"])
-        .output()
-        .map_err(|e| LintError {
-            message: format!("Failed to invoke '{CFN_LINT}': {e}"),
+/// Diagnostics for the syntax errors the template parser recovered from
+pub fn syntax_diagnostics(document: &Document) -> Vec<Diagnostic> {
+    document
+        .template()
+        .errors()
+        .iter()
+        .map(|error| {
+            native(
+                document,
+                error.span(),
+                Severity::Error,
+                SYNTAX_ERROR,
+                error.message(),
+            )
         })
+        .collect()
+}
+
+/// Diagnostics from the server's own checks of the template, which give
+/// instant feedback without waiting on cfn-lint
+pub fn template_diagnostics(document: &Document, schemas: &SchemaRegistry) -> Vec<Diagnostic> {
+    let mut diagnostics = unresolved::unresolved(document, schemas);
+    diagnostics.extend(unused::unused(document));
+    diagnostics.extend(properties::properties(document, schemas));
+    diagnostics.extend(deprecated::deprecated(document, schemas));
+    diagnostics.extend(cycles::cycles(document));
+    diagnostics
 }
 
-fn extract_file_path(uri: &str) -> String {
-    let path = uri.replace("file://", "");
-    let path = path.split(":").last().unwrap();
-    path.to_string()
+/// Whether a cfn-lint finding repeats what the native checks report, with
+/// more precise ranges. A rule only counts as covered where the check for it
+/// actually ran, with the schema data it needs, so cfn-lint's finding is
+/// kept wherever the native checks can't see
+pub fn superseded(document: &Document, schemas: &SchemaRegistry, diagnostic: &Diagnostic) -> bool {
+    // The native checks other than the syntax check don't run on a template
+    // with syntax errors, and the syntax check reports every error where
+    // cfn-lint stops at the first
+    if !document.template().errors().is_empty() {
+        return diagnostic.code() == "E0000";
+    }
+    let offset = document.offset(diagnostic.range().start());
+    match diagnostic.code() {
+        "E1010" | "E1020" | "E3005" => unresolved::checked(document),
+        "E2531" | "W2531" | "E2533" => deprecated::supersedes(document, offset),
+        "E3004" | "W2001" | "W7001" | "W8001" => true,
+        rule @ ("E3002" | "E3003" | "E3012" | "E3030" | "E3031" | "E3033" | "E3034") => {
+            properties::supersedes(document, schemas, rule, offset)
+        }
+        _ => false,
+    }
+}
+
+fn native(
+    document: &Document,
+    span: Span,
+    severity: Severity,
+    code: &str,
+    message: &str,
+) -> Diagnostic {
+    Diagnostic::builder()
+        .range(document.range(span))
+        .severity(severity)
+        .code(code.into())
+        .source(CFN_LSP.into())
+        .message(message.into())
+        .tags(Vec::new())
+        .related_information(Vec::new())
+        .build()
+}
+
+// cfn-lint reads the template from stdin when no template argument is given,
+// which lets us lint unsaved buffer content
+fn execute_linter(text: &str) -> Result<Output, LintError> {
+    let mut child = Command::new(CFN_LINT)
+        .args(["--format", "json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| LintError {
+            message: format!("Failed to invoke '{CFN_LINT}': {e}"),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).map_err(|e| LintError {
+            message: format!("Failed to write template to '{CFN_LINT}': {e}"),
+        })?;
+    }
+
+    child.wait_with_output().map_err(|e| LintError {
+        message: format!("Failed to read output of '{CFN_LINT}': {e}"),
+    })
 }
 
 mod model {
-    use crate::model::method::diagnostic;
+    use crate::{
+        document::Document,
+        model::method::{diagnostic, initialise::PositionEncoding},
+    };
     use serde::Deserialize;
 
     #[derive(Debug, Deserialize)]
@@ -98,12 +196,9 @@ mod model {
         end: Position,
     }
 
-    impl From<Location> for diagnostic::Range {
-        fn from(value: Location) -> Self {
-            Self::new(
-                diagnostic::Position::from(value.start),
-                diagnostic::Position::from(value.end),
-            )
+    impl Location {
+        fn range(&self, document: &Document) -> diagnostic::Range {
+            diagnostic::Range::new(self.start.position(document), self.end.position(document))
         }
     }
 
@@ -114,9 +209,14 @@ mod model {
         column_number: usize,
     }
 
-    impl From<Position> for diagnostic::Position {
-        fn from(value: Position) -> Self {
-            diagnostic::Position::new(value.line_number - 1, value.column_number - 1)
+    impl Position {
+        // cfn-lint reports 1-based lines and columns counted in code points
+        fn position(&self, document: &Document) -> diagnostic::Position {
+            let position = diagnostic::Position::new(
+                self.line_number.saturating_sub(1),
+                self.column_number.saturating_sub(1),
+            );
+            document.transcode(&position, PositionEncoding::Utf32)
         }
     }
 
@@ -127,18 +227,65 @@ mod model {
         source: String,
     }
 
-    impl From<LintDiagnostic> for diagnostic::Diagnostic {
-        fn from(value: LintDiagnostic) -> Self {
-            Self::builder()
-                .range(diagnostic::Range::from(value.location))
-                .severity(diagnostic::Severity::from(value.level))
-                .code(value.rule.id)
-                .code_description(diagnostic::CodeDescription::new(&value.rule.source))
+    impl LintDiagnostic {
+        pub fn into_diagnostic(self, document: &Document) -> diagnostic::Diagnostic {
+            diagnostic::Diagnostic::builder()
+                .range(self.location.range(document))
+                .severity(diagnostic::Severity::from(self.level))
+                .code(self.rule.id)
+                .code_description(diagnostic::CodeDescription::new(&self.rule.source))
                 .source(super::CFN_LINT.into())
-                .message(value.message)
+                .message(self.message)
                 .tags(Vec::new())
                 .related_information(Vec::new())
                 .build()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        method::fixture::{document, registry},
+        model::method::diagnostic::{Position, Range},
+    };
+
+    use super::*;
+
+    fn linted(code: &str, line: usize) -> Diagnostic {
+        Diagnostic::builder()
+            .range(Range::new(Position::new(line, 0), Position::new(line, 0)))
+            .severity(Severity::Error)
+            .code(code.into())
+            .source(CFN_LINT.into())
+            .message("message".into())
+            .tags(Vec::new())
+            .related_information(Vec::new())
+            .build()
+    }
+
+    #[test]
+    fn supersedes_reference_rules_without_transform() {
+        let text = "Outputs:\n  Arn:\n    Value: !GetAtt Nope.Arn\n";
+        let transformed = format!("Transform: AWS::Serverless-2016-10-31\n{text}");
+
+        assert!(superseded(
+            &document(text),
+            &registry(),
+            &linted("E1010", 2)
+        ));
+        assert!(!superseded(
+            &document(&transformed),
+            &registry(),
+            &linted("E1010", 3)
+        ))
+    }
+
+    #[test]
+    fn keeps_findings_of_templates_with_syntax_errors() {
+        let text = "Parameters:\n  Env:\n    Type: [String\n";
+        let document = document(text);
+        assert!(!superseded(&document, &registry(), &linted("W2001", 1)));
+        assert!(superseded(&document, &registry(), &linted("E0000", 2)))
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+
+use crate::{
+    document::Document,
+    model::method::diagnostic::{Diagnostic, Location, RelatedInformation, Severity},
+    template::{DeclarationKind, Reference, ReferenceKind},
+};
+
+use super::CFN_LSP;
+
+const CIRCULAR_DEPENDENCY: &str = "circular-dependency";
+
+/// A reference from one resource to another, by index among the resources
+struct Hop<'a> {
+    from: usize,
+    to: usize,
+    reference: &'a Reference,
+}
+
+/// Resources which depend on themselves through `Ref`, `Fn::GetAtt`,
+/// `Fn::Sub` or `DependsOn`. Each one in a cycle is reported along with the
+/// shortest path back to it, with a location for every hop
+pub fn cycles(document: &Document) -> Vec<Diagnostic> {
+    let template = document.template();
+    let resources: Vec<_> = template.declarations(DeclarationKind::Resource).collect();
+    let hops: Vec<Vec<Hop>> = resources
+        .iter()
+        .enumerate()
+        .map(|(from, resource)| {
+            let mut hops: Vec<Hop> = Vec::new();
+            let references = template.references().iter().filter(|reference| {
+                matches!(
+                    reference.kind(),
+                    ReferenceKind::Ref | ReferenceKind::Resource
+                ) && resource.span().contains(reference.span().start())
+            });
+            for reference in references {
+                let to = resources
+                    .iter()
+                    .position(|resource| resource.logical_id().name() == reference.name());
+                if let Some(to) = to
+                    && !hops.iter().any(|hop| hop.to == to)
+                {
+                    hops.push(Hop {
+                        from,
+                        to,
+                        reference,
+                    });
+                }
+            }
+            hops
+        })
+        .collect();
+
+    (0..resources.len())
+        .filter_map(|start| {
+            let cycle = shortest_cycle(&hops, start)?;
+            let names: Vec<_> = std::iter::once(resources[start].logical_id().name())
+                .chain(cycle.iter().map(|hop| hop.reference.name()))
+                .collect();
+            let related = cycle
+                .iter()
+                .map(|hop| {
+                    RelatedInformation::new(
+                        Location::new(document.uri(), document.range(hop.reference.span())),
+                        &format!(
+                            "'{}' depends on '{}'",
+                            resources[hop.from].logical_id().name(),
+                            hop.reference.name()
+                        ),
+                    )
+                })
+                .collect();
+            Some(
+                Diagnostic::builder()
+                    .range(document.range(resources[start].logical_id().name_span()))
+                    .severity(Severity::Error)
+                    .code(CIRCULAR_DEPENDENCY.into())
+                    .source(CFN_LSP.into())
+                    .message(format!("Circular dependency: {}", names.join(" -> ")))
+                    .tags(Vec::new())
+                    .related_information(related)
+                    .build(),
+            )
+        })
+        .collect()
+}
+
+/// Breadth-first search for the fewest hops leading from a resource back to
+/// itself
+fn shortest_cycle<'a>(hops: &'a [Vec<Hop<'a>>], start: usize) -> Option<Vec<&'a Hop<'a>>> {
+    let mut previous: Vec<Option<&Hop>> = vec![None; hops.len()];
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for hop in &hops[node] {
+            if hop.to == start {
+                let mut cycle = vec![hop];
+                let mut node = hop.from;
+                while node != start {
+                    let hop = previous[node]?;
+                    cycle.push(hop);
+                    node = hop.from;
+                }
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if hop.to != start && previous[hop.to].is_none() {
+                previous[hop.to] = Some(hop);
+                queue.push_back(hop.to);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::document;
+
+    use super::*;
+
+    fn check(text: &str) -> Vec<Diagnostic> {
+        let document = document(text);
+        cycles(&document)
+    }
+
+    #[test]
+    fn reports_every_resource_in_cycle() {
+        let text = "\
+Resources:
+  Role:
+    Type: AWS::IAM::Role
+    Properties:
+      Description: !Sub '${Function.Arn}'
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: !GetAtt Role.Arn
+  Bucket:
+    Type: AWS::S3::Bucket
+    DependsOn: [Function]
+";
+        let actual: Vec<_> = check(text)
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message().to_string(),
+                    diagnostic.range().start().line(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("Circular dependency: Role -> Function -> Role".into(), 1),
+                (
+                    "Circular dependency: Function -> Role -> Function".into(),
+                    5
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn relates_each_hop() {
+        let text = "\
+Resources:
+  A:
+    Type: AWS::SNS::Topic
+    DependsOn: B
+  B:
+    Type: AWS::SNS::Topic
+    DependsOn: C
+  C:
+    Type: AWS::SNS::Topic
+    Properties:
+      TopicName: !Ref A
+";
+        let actual = check(text);
+        let hops: Vec<_> = actual[0]
+            .related_information()
+            .iter()
+            .map(|related| {
+                (
+                    related.message().to_string(),
+                    related.location().range().start().line(),
+                )
+            })
+            .collect();
+        assert_eq!(actual.len(), 3);
+        assert_eq!(
+            hops,
+            vec![
+                ("'A' depends on 'B'".into(), 3),
+                ("'B' depends on 'C'".into(), 6),
+                ("'C' depends on 'A'".into(), 10),
+            ]
+        )
+    }
+
+    #[test]
+    fn reports_resource_referring_to_itself() {
+        let text = "\
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      QueueName: !GetAtt Queue.QueueName
+";
+        assert_eq!(
+            check(text)[0].message(),
+            "Circular dependency: Queue -> Queue"
+        )
+    }
+
+    #[test]
+    fn ignores_acyclic_dependencies() {
+        let text = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      QueueName: !Ref Env
+  Bucket:
+    Type: AWS::S3::Bucket
+    DependsOn: Queue
+";
+        assert!(check(text).is_empty())
+    }
+}
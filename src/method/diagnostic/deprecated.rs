@@ -0,0 +1,308 @@
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    document::Document,
+    method::edit,
+    model::method::{
+        code_action::Fix,
+        diagnostic::{Diagnostic, Severity, Tag},
+    },
+    schema::SchemaRegistry,
+    template::{DeclarationKind, Node, Segment, Span},
+};
+
+use super::CFN_LSP;
+
+const DEPRECATED: &str = "deprecated";
+
+/// Resource types which can no longer be created, with their replacement
+/// where there is one
+const DEPRECATED_TYPES: [(&str, Option<&str>); 10] = [
+    ("AWS::SDB::Domain", Some("AWS::DynamoDB::Table")),
+    ("AWS::CodeStar::GitHubRepository", None),
+    ("AWS::IoTThingsGraph::FlowTemplate", None),
+    ("AWS::OpsWorks::App", None),
+    ("AWS::OpsWorks::ElasticLoadBalancerAttachment", None),
+    ("AWS::OpsWorks::Instance", None),
+    ("AWS::OpsWorks::Layer", None),
+    ("AWS::OpsWorks::Stack", None),
+    ("AWS::OpsWorks::UserProfile", None),
+    ("AWS::OpsWorks::Volume", None),
+];
+
+/// Properties which name Lambda runtimes, either singly or as a list
+const RUNTIME_PROPERTIES: [(&str, &str); 4] = [
+    ("AWS::Lambda::Function", "Runtime"),
+    ("AWS::Lambda::LayerVersion", "CompatibleRuntimes"),
+    ("AWS::Serverless::Function", "Runtime"),
+    ("AWS::Serverless::LayerVersion", "CompatibleRuntimes"),
+];
+
+/// Lifecycle of each Lambda runtime, copied from cfn-lint by
+/// `scripts/bundle-runtimes.py`
+static RUNTIME_DATA: &str = include_str!("../../../data/lambda-runtimes.json");
+
+static RUNTIMES: LazyLock<HashMap<String, Runtime>> = LazyLock::new(|| {
+    serde_json::from_str(RUNTIME_DATA).unwrap_or_else(|e| {
+        tracing::error!("Failed to load Lambda runtimes: {e}");
+        HashMap::new()
+    })
+});
+
+#[derive(Debug, Deserialize)]
+struct Runtime {
+    /// Date the runtime was deprecated, or will be, as `YYYY-MM-DD`
+    deprecated: String,
+    successor: Option<String>,
+}
+
+/// Deprecated resource types, properties and Lambda runtimes, tagged so that
+/// editors strike them through
+pub fn deprecated(document: &Document, schemas: &SchemaRegistry) -> Vec<Diagnostic> {
+    deprecated_on(document, schemas, &today())
+}
+
+/// Whether a cfn-lint runtime finding at an offset is one the check makes,
+/// i.e. it is on a runtime the lifecycle data knows
+pub fn supersedes(document: &Document, offset: usize) -> bool {
+    let path = document.template().root().path_to(offset);
+    let runtime = match path.last() {
+        Some(Segment::Key(entry) | Segment::Value(entry)) => entry.value().as_str(),
+        _ => None,
+    };
+    runtime.is_some_and(|runtime| RUNTIMES.contains_key(runtime))
+}
+
+fn deprecated_on(document: &Document, schemas: &SchemaRegistry, today: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for resource in document.template().declarations(DeclarationKind::Resource) {
+        let Some(type_node) = resource.get("Type") else {
+            continue;
+        };
+        let Some(type_name) = type_node.as_str() else {
+            continue;
+        };
+        if let Some((_, replacement)) = DEPRECATED_TYPES.iter().find(|(name, _)| *name == type_name)
+        {
+            let message = match replacement {
+                Some(replacement) => {
+                    format!("'{type_name}' is deprecated, use '{replacement}' instead")
+                }
+                None => format!("'{type_name}' is deprecated"),
+            };
+            diagnostics.push(diagnostic(document, type_node.span(), message));
+        }
+
+        let entries = resource
+            .get("Properties")
+            .and_then(Node::as_mapping)
+            .unwrap_or_default();
+        if let Some(schema) = schemas.get(type_name) {
+            for entry in entries {
+                let name = entry.key().name();
+                if schema
+                    .property(name)
+                    .is_some_and(|property| property.deprecated())
+                {
+                    let message = format!("Property '{name}' of '{type_name}' is deprecated");
+                    diagnostics.push(diagnostic(document, entry.key().span(), message));
+                }
+            }
+        }
+
+        let runtimes = RUNTIME_PROPERTIES
+            .iter()
+            .filter(|(resource_type, _)| *resource_type == type_name)
+            .filter_map(|(_, property)| resource.get("Properties")?.get(property))
+            .flat_map(|node| match node.as_sequence() {
+                Some(items) => items.iter().collect(),
+                None => vec![node],
+            });
+        for node in runtimes {
+            let Some(runtime) = node.as_str() else {
+                continue;
+            };
+            let Some(lifecycle) = RUNTIMES
+                .get(runtime)
+                .filter(|lifecycle| lifecycle.deprecated.as_str() <= today)
+            else {
+                continue;
+            };
+            let diagnostic = match &lifecycle.successor {
+                Some(successor) => {
+                    let message =
+                        format!("Runtime '{runtime}' is deprecated, use '{successor}' instead");
+                    let edit = edit::replace_scalar(document, node, successor);
+                    let title = format!("Change runtime to '{successor}'");
+                    let fix = Fix::new(&title, document.version(), vec![edit]);
+                    diagnostic(document, node.span(), message).with_fix(&fix)
+                }
+                None => {
+                    let message = format!("Runtime '{runtime}' is deprecated");
+                    diagnostic(document, node.span(), message)
+                }
+            };
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Today's date in UTC, as `YYYY-MM-DD`
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    date(seconds / 86_400)
+}
+
+/// Date a number of days after 1970-01-01, see
+/// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn date(days: u64) -> String {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn diagnostic(document: &Document, span: Span, message: String) -> Diagnostic {
+    Diagnostic::builder()
+        .range(document.range(span))
+        .severity(Severity::Warning)
+        .code(DEPRECATED.into())
+        .source(CFN_LSP.into())
+        .message(message)
+        .tags(vec![Tag::Deprecated])
+        .related_information(Vec::new())
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::{covered, document, registry};
+
+    use super::*;
+
+    /// Message and the text each diagnostic covers
+    fn check(text: &str) -> Vec<(String, String)> {
+        let document = document(text);
+        covered(
+            &document,
+            &deprecated_on(&document, &registry(), "2026-01-01"),
+        )
+        .into_iter()
+        .map(|(_, message, text)| (message, text))
+        .collect()
+    }
+
+    #[test]
+    fn reports_deprecated_types_properties_and_runtimes() {
+        let text = "\
+Resources:
+  Domain:
+    Type: AWS::SDB::Domain
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Runtime: python3.7
+      Code: {}
+  Layer:
+    Type: AWS::Lambda::LayerVersion
+    Properties:
+      CompatibleRuntimes: [python3.12, python3.9, nodejs16.x]
+";
+        assert_eq!(
+            check(text),
+            vec![
+                (
+                    "'AWS::SDB::Domain' is deprecated, use 'AWS::DynamoDB::Table' instead".into(),
+                    "AWS::SDB::Domain".into()
+                ),
+                (
+                    "Property 'Code' of 'AWS::Lambda::Function' is deprecated".into(),
+                    "Code".into()
+                ),
+                (
+                    "Runtime 'python3.7' is deprecated, use 'python3.14' instead".into(),
+                    "python3.7".into()
+                ),
+                (
+                    "Runtime 'python3.9' is deprecated, use 'python3.14' instead".into(),
+                    "python3.9".into()
+                ),
+                (
+                    "Runtime 'nodejs16.x' is deprecated, use 'nodejs24.x' instead".into(),
+                    "nodejs16.x".into()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn tags_as_deprecated() {
+        let document = document(
+            "Resources:\n  Function:\n    Type: AWS::Lambda::Function\n    Properties:\n      Runtime: go1.x\n",
+        );
+        let actual = deprecated(&document, &registry());
+        assert_eq!(actual[0].tags(), &[Tag::Deprecated]);
+        assert_eq!(actual[0].severity(), &Severity::Warning)
+    }
+
+    #[test]
+    fn reports_runtimes_without_successor() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Runtime: java25
+";
+        let document = document(text);
+        let actual = deprecated_on(&document, &registry(), "2030-01-01");
+        assert_eq!(actual[0].message(), "Runtime 'java25' is deprecated");
+        assert!(actual[0].fix().is_none())
+    }
+
+    #[test]
+    fn supersedes_findings_on_known_runtimes() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Runtime: python3.7
+  Other:
+    Type: AWS::Lambda::Function
+    Properties:
+      Runtime: cobol85
+";
+        let document = document(text);
+        let offset = |from| text.match_indices("Runtime").nth(from).unwrap().0;
+        assert!(supersedes(&document, offset(0)));
+        assert!(!supersedes(&document, offset(1)))
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_000), "2024-10-04")
+    }
+}
@@ -0,0 +1,687 @@
+use regex_lite::Regex;
+
+use crate::{
+    document::Document,
+    method::edit::{self, Insert},
+    model::method::{
+        code_action::Fix,
+        diagnostic::{Diagnostic, Severity},
+    },
+    schema::{Bounds, Property, ResourceSchema, SchemaRegistry, Shape},
+    template::{DeclarationKind, Entry, Function, Intrinsic, Node, Segment, Span, Value},
+};
+
+use super::native;
+
+const UNKNOWN_PROPERTY: &str = "unknown-property";
+const MISSING_PROPERTY: &str = "missing-property";
+const INVALID_TYPE: &str = "invalid-type";
+const INVALID_VALUE: &str = "invalid-value";
+
+/// What an intrinsic function resolves to, as far as can be told before
+/// the stack is deployed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolved {
+    Scalar,
+    List,
+    Unknown,
+}
+
+/// Where a missing property is added
+#[derive(Debug, Clone, Copy)]
+enum Target<'a> {
+    Mapping(&'a Node),
+    /// An entry with no value, such as an empty `Properties`
+    Entry(&'a Entry),
+    /// A resource with no `Properties` at all
+    Resource(&'a Node),
+}
+
+/// Checks the `Properties` of each resource of a known type against its
+/// schema: unknown and missing properties, values of the wrong type, and
+/// values outside the allowed values, pattern or bounds
+pub fn properties(document: &Document, schemas: &SchemaRegistry) -> Vec<Diagnostic> {
+    let template = document.template();
+    let mut diagnostics = Vec::new();
+    for resource in template.declarations(DeclarationKind::Resource) {
+        let Some(schema) = resource
+            .get("Type")
+            .and_then(Node::as_str)
+            .and_then(|type_name| schemas.get(type_name))
+        else {
+            continue;
+        };
+        let mut checker = Checker {
+            document,
+            schemas,
+            schema,
+            diagnostics: Vec::new(),
+        };
+        match resource.body().entry("Properties") {
+            Some(entry) => match entry.value().value() {
+                Value::Mapping(_) => checker.object(
+                    entry.value(),
+                    schema.properties(),
+                    schema.closed(),
+                    entry.key().span(),
+                ),
+                Value::Null => checker.missing(
+                    &[],
+                    schema.properties(),
+                    entry.key().span(),
+                    Target::Entry(entry),
+                ),
+                _ => checker.value(
+                    entry.value(),
+                    &Shape::Object {
+                        properties: schema.properties().to_vec(),
+                        closed: schema.closed(),
+                    },
+                    entry.key().span(),
+                ),
+            },
+            None => checker.missing(
+                &[],
+                schema.properties(),
+                resource.logical_id().name_span(),
+                Target::Resource(resource.body()),
+            ),
+        }
+        diagnostics.extend(checker.diagnostics);
+    }
+    diagnostics
+}
+
+/// Whether the check covers a cfn-lint rule at an offset: the offset lies
+/// within a resource whose schema is known and, for rules about allowed
+/// values, patterns and bounds, the schema gives that constraint for the
+/// property there
+pub fn supersedes(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    rule: &str,
+    offset: usize,
+) -> bool {
+    let path = document.template().root().path_to(offset);
+    let [Segment::Value(section), Segment::Value(resource), rest @ ..] = path.as_slice() else {
+        return false;
+    };
+    if section.key().name() != "Resources" {
+        return false;
+    }
+    let Some(schema) = resource
+        .value()
+        .get("Type")
+        .and_then(Node::as_str)
+        .and_then(|type_name| schemas.get(type_name))
+    else {
+        return false;
+    };
+    if !matches!(rule, "E3030" | "E3031" | "E3033" | "E3034") {
+        return true;
+    }
+    let [Segment::Value(entry), properties @ ..] = rest else {
+        return false;
+    };
+    let mut names = Vec::new();
+    for segment in properties {
+        match segment {
+            Segment::Key(entry) | Segment::Value(entry) => names.push(entry.key().name()),
+            Segment::Item(..) => {}
+            Segment::Function(_) => return false,
+        }
+    }
+    entry.key().name() == "Properties"
+        && schema
+            .property_at(&names)
+            .is_some_and(|property| constrains(schema, property.shape(), rule))
+}
+
+/// Whether a shape, or any shape it may take, has the constraint a cfn-lint
+/// rule checks
+fn constrains(schema: &ResourceSchema, shape: &Shape, rule: &str) -> bool {
+    let bounded = |bounds: &Bounds| bounds.minimum().is_some() || bounds.maximum().is_some();
+    match (schema.resolve(shape), rule) {
+        (Shape::List(item), _) => constrains(schema, item, rule),
+        (Shape::OneOf(shapes), _) => shapes.iter().any(|shape| constrains(schema, shape, rule)),
+        (Shape::String { allowed_values, .. }, "E3030") => !allowed_values.is_empty(),
+        (Shape::String { pattern, .. }, "E3031") => pattern
+            .as_deref()
+            .is_some_and(|pattern| Regex::new(pattern).is_ok()),
+        (Shape::String { length, .. }, "E3033") => bounded(length),
+        (Shape::Integer(bounds) | Shape::Number(bounds), "E3034") => bounded(bounds),
+        _ => false,
+    }
+}
+
+struct Checker<'a> {
+    document: &'a Document,
+    schemas: &'a SchemaRegistry,
+    schema: &'a ResourceSchema,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    /// Checks a value against a shape. Missing properties of an object are
+    /// reported on the anchor, usually the key holding the object
+    fn value(&mut self, node: &Node, shape: &Shape, anchor: Span) {
+        let resolved = self.schema.resolve(shape);
+        match (node.value(), resolved) {
+            (Value::Null, _) | (_, Shape::Json) => {}
+            (Value::Function(function), _) => self.function(node, function, shape, anchor),
+            (_, Shape::OneOf(shapes)) => {
+                let matches = shapes.iter().any(|shape| {
+                    let mut checker = Checker {
+                        diagnostics: Vec::new(),
+                        ..*self
+                    };
+                    checker.value(node, shape, anchor);
+                    checker.diagnostics.is_empty()
+                });
+                if !matches {
+                    self.mismatch(node, shape);
+                }
+            }
+            (Value::Sequence(items), Shape::List(item_shape)) => {
+                for item in items {
+                    let anchor = match item.as_mapping() {
+                        Some([first, ..]) => first.key().span(),
+                        _ => item.span(),
+                    };
+                    self.value(item, item_shape, anchor);
+                }
+            }
+            (Value::Mapping(_), Shape::Object { properties, closed }) => {
+                self.object(node, properties, *closed, anchor)
+            }
+            (Value::Mapping(entries), Shape::Map(value_shape)) => {
+                for entry in entries {
+                    self.value(entry.value(), value_shape, entry.key().span());
+                }
+            }
+            (
+                _,
+                Shape::String {
+                    allowed_values,
+                    pattern,
+                    length,
+                },
+            ) => match node.scalar_string() {
+                Some(text) => self.string(node, &text, allowed_values, pattern.as_deref(), length),
+                None => self.mismatch(node, shape),
+            },
+            (_, Shape::Integer(bounds)) => {
+                match node
+                    .scalar_string()
+                    .and_then(|text| text.parse::<i64>().ok())
+                {
+                    Some(number) => self.bounds(node, number as f64, bounds),
+                    None => self.mismatch(node, shape),
+                }
+            }
+            (_, Shape::Number(bounds)) => {
+                match node
+                    .scalar_string()
+                    .and_then(|text| text.parse::<f64>().ok())
+                {
+                    Some(number) => self.bounds(node, number, bounds),
+                    None => self.mismatch(node, shape),
+                }
+            }
+            (_, Shape::Boolean) => {
+                let valid = node
+                    .scalar_string()
+                    .is_some_and(|text| matches!(text.to_lowercase().as_str(), "true" | "false"));
+                if !valid {
+                    self.mismatch(node, shape);
+                }
+            }
+            _ => self.mismatch(node, shape),
+        }
+    }
+
+    fn object(&mut self, node: &Node, properties: &[Property], closed: bool, anchor: Span) {
+        let entries = node.as_mapping().unwrap_or_default();
+        for (index, entry) in entries.iter().enumerate() {
+            let name = entry.key().name();
+            match properties.iter().find(|property| property.name() == name) {
+                Some(property) => self.value(entry.value(), property.shape(), entry.key().span()),
+                None if !closed => {}
+                None => {
+                    let title = format!("Remove property '{name}'");
+                    let fix = edit::remove_entry(self.document, entries, index)
+                        .map(|edit| Fix::new(&title, self.document.version(), vec![edit]));
+                    self.report_fix(
+                        entry.key().span(),
+                        UNKNOWN_PROPERTY,
+                        &format!("Unknown property '{name}'"),
+                        fix,
+                    )
+                }
+            }
+        }
+        let present: Vec<_> = entries.iter().map(|entry| entry.key().name()).collect();
+        self.missing(&present, properties, anchor, Target::Mapping(node));
+    }
+
+    fn missing(&mut self, present: &[&str], properties: &[Property], anchor: Span, target: Target) {
+        for property in properties {
+            let name = property.name();
+            if !property.required() || present.contains(&name) {
+                continue;
+            }
+            let value = self.placeholder(property.shape());
+            let value = Insert::Scalar(value.into());
+            let edit = match target {
+                Target::Mapping(mapping) => {
+                    edit::append_entry(self.document, mapping, name, &value)
+                }
+                Target::Entry(entry) => Some(edit::fill_entry(self.document, entry, name, &value)),
+                Target::Resource(body) => {
+                    let properties = Insert::Mapping(vec![(name.into(), value)]);
+                    edit::append_entry(self.document, body, "Properties", &properties)
+                }
+            };
+            let title = format!("Add property '{name}'");
+            let fix = edit.map(|edit| Fix::new(&title, self.document.version(), vec![edit]));
+            self.report_fix(
+                anchor,
+                MISSING_PROPERTY,
+                &format!("Missing required property '{name}'"),
+                fix,
+            );
+        }
+    }
+
+    /// An empty value of a shape, for the user to fill in
+    fn placeholder(&self, shape: &Shape) -> &'static str {
+        match self.schema.resolve(shape) {
+            Shape::String { .. } => "\"\"",
+            Shape::Integer(_) | Shape::Number(_) => "0",
+            Shape::Boolean => "false",
+            Shape::List(_) => "[]",
+            Shape::OneOf(shapes) => shapes.first().map_or("{}", |shape| self.placeholder(shape)),
+            _ => "{}",
+        }
+    }
+
+    fn string(
+        &mut self,
+        node: &Node,
+        text: &str,
+        allowed_values: &[String],
+        pattern: Option<&str>,
+        length: &Bounds,
+    ) {
+        if !allowed_values.is_empty() && !allowed_values.iter().any(|value| value == text) {
+            let message = format!(
+                "'{text}' is not one of the allowed values: {}",
+                allowed_values.join(" | ")
+            );
+            return self.report(node.span(), INVALID_VALUE, &message);
+        }
+        // Schemas are written for JavaScript, so patterns which the regex
+        // engine doesn't support are ignored
+        if let Some(pattern) = pattern
+            && Regex::new(pattern).is_ok_and(|regex| !regex.is_match(text))
+        {
+            let message = format!("'{text}' does not match the pattern '{pattern}'");
+            return self.report(node.span(), INVALID_VALUE, &message);
+        }
+        let characters = text.chars().count() as f64;
+        if let Some(minimum) = length.minimum().filter(|minimum| characters < *minimum) {
+            let message = format!("'{text}' is shorter than the minimum length of {minimum}");
+            self.report(node.span(), INVALID_VALUE, &message);
+        } else if let Some(maximum) = length.maximum().filter(|maximum| characters > *maximum) {
+            let message = format!("'{text}' is longer than the maximum length of {maximum}");
+            self.report(node.span(), INVALID_VALUE, &message);
+        }
+    }
+
+    fn bounds(&mut self, node: &Node, number: f64, bounds: &Bounds) {
+        if let Some(minimum) = bounds.minimum().filter(|minimum| number < *minimum) {
+            let message = format!("{number} is less than the minimum of {minimum}");
+            self.report(node.span(), INVALID_VALUE, &message);
+        } else if let Some(maximum) = bounds.maximum().filter(|maximum| number > *maximum) {
+            let message = format!("{number} is greater than the maximum of {maximum}");
+            self.report(node.span(), INVALID_VALUE, &message);
+        }
+    }
+
+    /// Only the kind of value a function returns can be checked, except for
+    /// `Fn::If`, whose branches are checked as if written in its place
+    fn function(&mut self, node: &Node, function: &Function, shape: &Shape, anchor: Span) {
+        if function.intrinsic() == Intrinsic::If {
+            let branches = function.argument().as_sequence().unwrap_or_default();
+            for branch in branches.iter().skip(1) {
+                self.value(branch, shape, anchor);
+            }
+            return;
+        }
+        let resolved = self.resolve(function);
+        if !self.accepts(shape, resolved) {
+            let returns = match resolved {
+                Resolved::List => "a list",
+                _ => "a single value",
+            };
+            let message = format!(
+                "Expected {}, but {} returns {returns}",
+                self.schema.describe(shape),
+                function.intrinsic().key()
+            );
+            self.report(node.span(), INVALID_TYPE, &message);
+        }
+    }
+
+    fn resolve(&self, function: &Function) -> Resolved {
+        let argument = function.argument();
+        match function.intrinsic() {
+            Intrinsic::Ref => {
+                let Some(name) = argument.as_str() else {
+                    return Resolved::Unknown;
+                };
+                let template = self.document.template();
+                if name == "AWS::NotificationARNs" {
+                    return Resolved::List;
+                }
+                if name == "AWS::NoValue" {
+                    return Resolved::Unknown;
+                }
+                match template.find(DeclarationKind::Parameter, name) {
+                    Some((_, parameter)) => {
+                        let parameter_type = parameter
+                            .get("Type")
+                            .and_then(Node::as_str)
+                            .unwrap_or_default();
+                        if parameter_type.starts_with("List<")
+                            || parameter_type.contains("<List<")
+                            || parameter_type == "CommaDelimitedList"
+                        {
+                            Resolved::List
+                        } else {
+                            Resolved::Scalar
+                        }
+                    }
+                    None => Resolved::Scalar,
+                }
+            }
+            Intrinsic::GetAtt => self.attribute(argument).unwrap_or(Resolved::Unknown),
+            Intrinsic::Base64
+            | Intrinsic::ImportValue
+            | Intrinsic::Join
+            | Intrinsic::Length
+            | Intrinsic::Sub
+            | Intrinsic::ToJsonString => Resolved::Scalar,
+            Intrinsic::Cidr | Intrinsic::GetAZs | Intrinsic::Split => Resolved::List,
+            _ => Resolved::Unknown,
+        }
+    }
+
+    /// Kind of the attribute named by the argument of `Fn::GetAtt`, if the
+    /// resource's schema describes it
+    fn attribute(&self, argument: &Node) -> Option<Resolved> {
+        let (resource, attribute) = match argument.as_sequence() {
+            Some([resource, attribute, ..]) => (resource.as_str()?, attribute.as_str()?),
+            _ => argument.as_str()?.split_once('.')?,
+        };
+        let (_, resource) = self
+            .document
+            .template()
+            .find(DeclarationKind::Resource, resource)?;
+        let schema = self.schemas.get(resource.get("Type")?.as_str()?)?;
+        let attribute = schema.attribute(attribute)?;
+        match schema.resolve(attribute.shape()) {
+            Shape::List(_) => Some(Resolved::List),
+            Shape::String { .. } | Shape::Integer(_) | Shape::Number(_) | Shape::Boolean => {
+                Some(Resolved::Scalar)
+            }
+            _ => None,
+        }
+    }
+
+    fn accepts(&self, shape: &Shape, resolved: Resolved) -> bool {
+        match (self.schema.resolve(shape), resolved) {
+            (_, Resolved::Unknown) | (Shape::Json, _) => true,
+            (Shape::OneOf(shapes), _) => shapes.iter().any(|shape| self.accepts(shape, resolved)),
+            (Shape::List(_), resolved) => resolved == Resolved::List,
+            (Shape::Object { .. } | Shape::Map(_), _) => false,
+            (_, resolved) => resolved == Resolved::Scalar,
+        }
+    }
+
+    fn mismatch(&mut self, node: &Node, shape: &Shape) {
+        let found = match node.value() {
+            Value::Sequence(_) => "a list",
+            Value::Mapping(_) => "an object",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            _ => "a string",
+        };
+        let message = format!("Expected {}, found {found}", self.schema.describe(shape));
+        self.report(node.span(), INVALID_TYPE, &message);
+    }
+
+    fn report_fix(&mut self, span: Span, code: &str, message: &str, fix: Option<Fix>) {
+        let diagnostic = native(self.document, span, Severity::Error, code, message);
+        self.diagnostics.push(match fix {
+            Some(fix) => diagnostic.with_fix(&fix),
+            None => diagnostic,
+        });
+    }
+
+    fn report(&mut self, span: Span, code: &str, message: &str) {
+        self.diagnostics
+            .push(native(self.document, span, Severity::Error, code, message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::{covered, document, registry};
+
+    use super::*;
+
+    fn check(text: &str) -> Vec<(String, String, String)> {
+        let document = document(text);
+        covered(&document, &properties(&document, &registry()))
+    }
+
+    #[test]
+    fn accepts_valid_properties() {
+        let text = "\
+Parameters:
+  Subnets:
+    Type: List<AWS::EC2::Subnet::Id>
+Conditions:
+  Large: !Equals [!Ref AWS::Region, us-east-1]
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: !GetAtt Other.Arn
+      FunctionName: !Sub '${AWS::StackName}-handler'
+      MemorySize: !If [Large, 1024, '512']
+      PackageType: Zip
+      Architectures: !Ref Subnets
+      Environment:
+        Variables:
+          Stage: prod
+          Debug: true
+      Tags:
+        - Key: team
+  Other:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: arn
+      Unchecked: !Ref AWS::NoValue
+    Metadata:
+      Unknown: true
+";
+        assert_eq!(
+            check(text),
+            vec![(
+                UNKNOWN_PROPERTY.into(),
+                "Unknown property 'Unchecked'".into(),
+                "Unchecked".into()
+            )]
+        )
+    }
+
+    #[test]
+    fn accepts_unknown_keys_in_open_objects() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: arn
+      Environment:
+        Variables: {Stage: prod}
+        KmsKey: alias/env
+";
+        assert_eq!(check(text), vec![])
+    }
+
+    #[test]
+    fn reports_unknown_and_missing_properties() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Arn: arn
+      Tags:
+        - Value: platform
+  Empty:
+    Type: AWS::Lambda::Function
+";
+        assert_eq!(
+            check(text),
+            vec![
+                (
+                    UNKNOWN_PROPERTY.into(),
+                    "Unknown property 'Arn'".into(),
+                    "Arn".into()
+                ),
+                (
+                    MISSING_PROPERTY.into(),
+                    "Missing required property 'Key'".into(),
+                    "Value".into()
+                ),
+                (
+                    MISSING_PROPERTY.into(),
+                    "Missing required property 'Role'".into(),
+                    "Properties".into()
+                ),
+                (
+                    MISSING_PROPERTY.into(),
+                    "Missing required property 'Role'".into(),
+                    "Empty".into()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn reports_values_of_the_wrong_type() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: [arn]
+      MemorySize: large
+      Architectures: !GetAZs ''
+      Tags: !Join ['', [a, b]]
+      Environment: !Split [',', 'a,b']
+";
+        assert_eq!(
+            check(text),
+            vec![
+                (
+                    INVALID_TYPE.into(),
+                    "Expected String, found a list".into(),
+                    "[arn]".into()
+                ),
+                (
+                    INVALID_TYPE.into(),
+                    "Expected Integer, found a string".into(),
+                    "large".into()
+                ),
+                (
+                    INVALID_TYPE.into(),
+                    "Expected List of Tag, but Fn::Join returns a single value".into(),
+                    "!Join ['', [a, b]]".into()
+                ),
+                (
+                    INVALID_TYPE.into(),
+                    "Expected Environment, but Fn::Split returns a list".into(),
+                    "!Split [',', 'a,b']".into()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn reports_values_outside_constraints() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: arn
+      FunctionName: my handler
+      MemorySize: 64
+      PackageType: zip
+";
+        assert_eq!(
+            check(text),
+            vec![
+                (
+                    INVALID_VALUE.into(),
+                    "'my handler' does not match the pattern '^[a-zA-Z0-9-_]+$'".into(),
+                    "my handler".into()
+                ),
+                (
+                    INVALID_VALUE.into(),
+                    "64 is less than the minimum of 128".into(),
+                    "64".into()
+                ),
+                (
+                    INVALID_VALUE.into(),
+                    "'zip' is not one of the allowed values: Image | Zip".into(),
+                    "zip".into()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn supersedes_rules_only_where_schema_has_constraints() {
+        let text = "\
+Resources:
+  Function:
+    Type: AWS::Lambda::Function
+    Properties:
+      Role: arn
+      FunctionName: handler
+      MemorySize: 64
+  Thing:
+    Type: AWS::Unknown::Thing
+    Properties:
+      Count: 64
+";
+        let document = document(text);
+        let supersedes = |rule, key| {
+            let offset = text.find(key).unwrap();
+            supersedes(&document, &registry(), rule, offset)
+        };
+
+        assert!(supersedes("E3034", "MemorySize"));
+        assert!(supersedes("E3031", "FunctionName"));
+        assert!(supersedes("E3002", "Role"));
+        assert!(!supersedes("E3034", "Role"));
+        assert!(!supersedes("E3030", "MemorySize"));
+        assert!(!supersedes("E3002", "Count"))
+    }
+}
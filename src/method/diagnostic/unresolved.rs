@@ -0,0 +1,297 @@
+use crate::{
+    document::Document,
+    method::edit,
+    model::method::{
+        code_action::Fix,
+        diagnostic::{Diagnostic, Severity},
+    },
+    schema::SchemaRegistry,
+    template::{DeclarationKind, PSEUDO_PARAMETERS, Reference, ReferenceKind},
+};
+
+use super::native;
+
+const UNRESOLVED_REFERENCE: &str = "unresolved-reference";
+const UNKNOWN_ATTRIBUTE: &str = "unknown-attribute";
+
+/// Types whose attributes aren't described by their schema. Custom resources
+/// return whatever their provider sends, and nested stacks return their
+/// outputs as `Outputs.Name`
+const OPEN_ATTRIBUTE_TYPES: [&str; 2] = [
+    "AWS::CloudFormation::CustomResource",
+    "AWS::CloudFormation::Stack",
+];
+
+/// `Ref`s, `Fn::GetAtt`s, `Fn::Sub` variables and `DependsOn` entries naming
+/// a logical id or attribute which doesn't exist. Transforms such as SAM
+/// declare resources of their own, so templates using one aren't checked
+pub fn unresolved(document: &Document, schemas: &SchemaRegistry) -> Vec<Diagnostic> {
+    if !checked(document) {
+        return Vec::new();
+    }
+    document
+        .template()
+        .references()
+        .iter()
+        .filter_map(|reference| match reference.kind() {
+            ReferenceKind::Ref => unresolved_ref(document, reference),
+            ReferenceKind::Resource => unresolved_resource(document, schemas, reference),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the template's references are checked, i.e. it has no transform
+pub fn checked(document: &Document) -> bool {
+    document.template().transform().is_none()
+}
+
+fn unresolved_ref(document: &Document, reference: &Reference) -> Option<Diagnostic> {
+    let name = reference.name();
+    let pseudo = PSEUDO_PARAMETERS.iter().any(|(pseudo, _)| *pseudo == name);
+    let template = document.template();
+    if pseudo || template.declaration(reference).is_some() {
+        return None;
+    }
+    let candidates = template
+        .declarations(DeclarationKind::Parameter)
+        .chain(template.declarations(DeclarationKind::Resource))
+        .map(|declaration| declaration.logical_id().name())
+        .chain(PSEUDO_PARAMETERS.iter().map(|(pseudo, _)| *pseudo));
+    let diagnostic = native(
+        document,
+        reference.span(),
+        Severity::Error,
+        UNRESOLVED_REFERENCE,
+        &format!("'{name}' is not a parameter, resource or pseudo parameter"),
+    );
+    Some(with_nearest(document, diagnostic, reference, candidates))
+}
+
+fn unresolved_resource(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    reference: &Reference,
+) -> Option<Diagnostic> {
+    let template = document.template();
+    let name = reference.name();
+    let Some((_, resource)) = template.find(DeclarationKind::Resource, name) else {
+        let message = match template.find(DeclarationKind::Parameter, name) {
+            Some(_) => format!("'{name}' is a parameter rather than a resource"),
+            None => format!("'{name}' is not a resource"),
+        };
+        let diagnostic = native(
+            document,
+            reference.span(),
+            Severity::Error,
+            UNRESOLVED_REFERENCE,
+            &message,
+        );
+        let candidates = template
+            .declarations(DeclarationKind::Resource)
+            .map(|declaration| declaration.logical_id().name());
+        return Some(with_nearest(document, diagnostic, reference, candidates));
+    };
+
+    let (attribute, span) = reference.attribute()?;
+    let type_name = resource.get("Type")?.as_str()?;
+    if type_name.starts_with("Custom::") || OPEN_ATTRIBUTE_TYPES.contains(&type_name) {
+        return None;
+    }
+    let schema = schemas.get(type_name)?;
+    // A schema without read-only properties says nothing about attributes.
+    // Some writable properties are attributes too, which schemas don't say,
+    // and schemas may lag behind new attributes, so this is only a warning
+    let path: Vec<_> = attribute.split('.').collect();
+    if schema.attributes().is_empty()
+        || schema.attribute(attribute).is_some()
+        || schema.property_at(&path).is_some()
+    {
+        return None;
+    }
+    Some(native(
+        document,
+        span,
+        Severity::Warning,
+        UNKNOWN_ATTRIBUTE,
+        &format!("'{type_name}' has no attribute '{attribute}'"),
+    ))
+}
+
+/// Offers to change a misspelt name to the closest of the names it could
+/// have meant, if any is close enough to be a likely typo
+fn with_nearest<'a>(
+    document: &Document,
+    diagnostic: Diagnostic,
+    reference: &Reference,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Diagnostic {
+    let name = reference.name();
+    let threshold = (name.chars().count() / 3).max(1);
+    let nearest = candidates
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance);
+    match nearest {
+        Some((_, candidate)) => {
+            let edit = edit::replace(document, reference.span(), candidate);
+            let title = format!("Change to '{candidate}'");
+            diagnostic.with_fix(&Fix::new(&title, document.version(), vec![edit]))
+        }
+        None => diagnostic,
+    }
+}
+
+/// Edits needed to turn one name into another, ignoring case and counting
+/// swapped neighbouring letters as a single edit
+fn distance(from: &str, to: &str) -> usize {
+    let from: Vec<_> = from.to_lowercase().chars().collect();
+    let to: Vec<_> = to.to_lowercase().chars().collect();
+    let mut table = vec![vec![0; to.len() + 1]; from.len() + 1];
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=from.len() {
+        for j in 1..=to.len() {
+            let substitution = table[i - 1][j - 1] + usize::from(from[i - 1] != to[j - 1]);
+            let mut best = substitution
+                .min(table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1);
+            if i > 1 && j > 1 && from[i - 1] == to[j - 2] && from[i - 2] == to[j - 1] {
+                best = best.min(table[i - 2][j - 2] + 1);
+            }
+            table[i][j] = best;
+        }
+    }
+    table[from.len()][to.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::{covered, document, registry};
+
+    use super::*;
+
+    fn check(text: &str) -> Vec<(String, String, String)> {
+        let document = document(text);
+        covered(&document, &unresolved(&document, &registry()))
+    }
+
+    #[test]
+    fn accepts_declared_names_and_pseudo_parameters() {
+        let text = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      BucketName: !Sub '${Env}-${AWS::Region}-${Bucket.Arn}'
+Outputs:
+  Arn:
+    Value: !GetAtt [Bucket, Arn]
+";
+        assert!(check(text).is_empty())
+    }
+
+    #[test]
+    fn reports_unresolved_names() {
+        let text = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    DependsOn: Env
+    Properties:
+      BucketName: !Sub '${Stage}-name'
+Outputs:
+  Arn:
+    Value: !Ref Bukcet
+";
+        assert_eq!(
+            check(text),
+            vec![
+                (
+                    UNRESOLVED_REFERENCE.into(),
+                    "'Env' is a parameter rather than a resource".into(),
+                    "Env".into()
+                ),
+                (
+                    UNRESOLVED_REFERENCE.into(),
+                    "'Stage' is not a parameter, resource or pseudo parameter".into(),
+                    "Stage".into()
+                ),
+                (
+                    UNRESOLVED_REFERENCE.into(),
+                    "'Bukcet' is not a parameter, resource or pseudo parameter".into(),
+                    "Bukcet".into()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn reports_unknown_attributes() {
+        let text = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+  Custom:
+    Type: Custom::Thing
+Outputs:
+  A:
+    Value: !GetAtt Bucket.Name
+  B:
+    Value: !GetAtt Custom.Anything
+  C:
+    Value: !GetAtt Bucket.BucketName
+  D:
+    Value: !GetAtt Bucket.LoggingConfiguration.LogFilePrefix
+";
+        assert_eq!(
+            check(text),
+            vec![(
+                UNKNOWN_ATTRIBUTE.into(),
+                "'AWS::S3::Bucket' has no attribute 'Name'".into(),
+                "Name".into()
+            )]
+        )
+    }
+
+    #[test]
+    fn skips_templates_with_transforms() {
+        let text = "\
+Transform: AWS::Serverless-2016-10-31
+Outputs:
+  Role:
+    Value: !GetAtt FunctionRole.Arn
+";
+        assert!(check(text).is_empty())
+    }
+
+    #[test]
+    fn suggests_close_names_only() {
+        let text = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+Outputs:
+  Name:
+    Value: !Ref Bukcet
+  Other:
+    Value: !Ref Queue
+";
+        let document = document(text);
+        let fixes: Vec<_> = unresolved(&document, &registry())
+            .iter()
+            .map(|diagnostic| diagnostic.fix().map(|fix| fix.title().to_string()))
+            .collect();
+        assert_eq!(fixes, vec![Some("Change to 'Bucket'".into()), None])
+    }
+}
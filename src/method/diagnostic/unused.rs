@@ -0,0 +1,103 @@
+use crate::{
+    document::Document,
+    model::method::diagnostic::{Diagnostic, Severity, Tag},
+    template::DeclarationKind,
+};
+
+use super::CFN_LSP;
+
+const UNUSED: &str = "unused";
+
+/// Parameters, conditions and mappings which nothing refers to, tagged so
+/// that editors fade them out
+pub fn unused(document: &Document) -> Vec<Diagnostic> {
+    let template = document.template();
+    let kinds = [
+        (DeclarationKind::Parameter, "Parameter"),
+        (DeclarationKind::Condition, "Condition"),
+        (DeclarationKind::Mapping, "Mapping"),
+    ];
+    kinds
+        .into_iter()
+        .flat_map(|(kind, label)| {
+            template
+                .declarations(kind)
+                .filter(move |declaration| {
+                    template.references_to(kind, declaration).next().is_none()
+                })
+                .map(move |declaration| {
+                    let logical_id = declaration.logical_id();
+                    Diagnostic::builder()
+                        .range(document.range(logical_id.name_span()))
+                        .severity(Severity::Warning)
+                        .code(UNUSED.into())
+                        .source(CFN_LSP.into())
+                        .message(format!("{label} '{}' is never used", logical_id.name()))
+                        .tags(vec![Tag::Unnecessary])
+                        .related_information(Vec::new())
+                        .build()
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::document;
+
+    use super::*;
+
+    const TEMPLATE: &str = "\
+Parameters:
+  Env:
+    Type: String
+  Unused:
+    Type: String
+Mappings:
+  Names:
+    prod:
+      Bucket: data
+  Stale:
+    prod:
+      Bucket: old
+Conditions:
+  IsProd: !Equals [!Ref Env, prod]
+  Never: !Equals [a, b]
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Condition: IsProd
+    Properties:
+      BucketName: !FindInMap [Names, !Ref Env, Bucket]
+";
+
+    #[test]
+    fn reports_unreferenced_declarations() {
+        let document = document(TEMPLATE);
+        let actual: Vec<_> = unused(&document)
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message().to_string(),
+                    diagnostic.range().start().line(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("Parameter 'Unused' is never used".into(), 3),
+                ("Condition 'Never' is never used".into(), 14),
+                ("Mapping 'Stale' is never used".into(), 9),
+            ]
+        )
+    }
+
+    #[test]
+    fn tags_as_unnecessary() {
+        let document = document("Parameters:\n  Env:\n    Type: String\n");
+        let actual = unused(&document);
+        assert_eq!(actual[0].tags(), &[Tag::Unnecessary]);
+        assert_eq!(actual[0].severity(), &Severity::Warning)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    document::Document,
+    model::method::document_symbol::{self, DocumentSymbol, SymbolKind},
+    template::{Entry, Node},
+};
+
+/// Outline of a template: its top-level sections, with the logical ids
+/// declared within each
+pub fn document_symbol(document: &Document) -> document_symbol::Result {
+    document
+        .template()
+        .root()
+        .as_mapping()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|section| {
+            let declared = declared_kind(section.key().name());
+            let children = match (declared, section.value().as_mapping()) {
+                (Some(kind), Some(entries)) => Some(
+                    entries
+                        .iter()
+                        .filter_map(|entry| {
+                            symbol(document, entry, kind, detail(section, entry.value()), None)
+                        })
+                        .collect(),
+                ),
+                _ => None,
+            };
+            symbol(document, section, SymbolKind::Namespace, None, children)
+        })
+        .collect()
+}
+
+fn symbol(
+    document: &Document,
+    entry: &Entry,
+    kind: SymbolKind,
+    detail: Option<&str>,
+    children: Option<Vec<DocumentSymbol>>,
+) -> Option<DocumentSymbol> {
+    // Clients reject symbols without a name, which a half-typed key may have
+    let name = entry.key().name();
+    if name.is_empty() {
+        return None;
+    }
+    Some(
+        DocumentSymbol::builder()
+            .name(name)
+            .maybe_detail(detail)
+            .kind(kind)
+            .range(document.range(entry.span()))
+            .selection_range(document.range(entry.key().name_span()))
+            .maybe_children(children)
+            .build(),
+    )
+}
+
+/// Symbol kind of the entries within a section which declares logical ids
+pub fn declared_kind(section: &str) -> Option<SymbolKind> {
+    match section {
+        "Parameters" => Some(SymbolKind::Variable),
+        "Mappings" => Some(SymbolKind::Object),
+        "Conditions" => Some(SymbolKind::Boolean),
+        "Rules" => Some(SymbolKind::Event),
+        "Resources" => Some(SymbolKind::Class),
+        "Outputs" => Some(SymbolKind::Property),
+        _ => None,
+    }
+}
+
+/// The type of a parameter or resource, or the export name of an output
+fn detail<'a>(section: &Entry, body: &'a Node) -> Option<&'a str> {
+    match section.key().name() {
+        "Parameters" | "Resources" => body.get("Type")?.as_str(),
+        "Outputs" => body.get("Export")?.get("Name")?.as_str(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::{diagnostic::Position, initialise::PositionEncoding};
+
+    use super::*;
+
+    const TEMPLATE: &str = "\
+AWSTemplateFormatVersion: '2010-09-09'
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+Outputs:
+  Arn:
+    Value: !GetAtt Bucket.Arn
+    Export:
+      Name: bucket-arn
+";
+
+    fn symbols(text: &str, language_id: &str) -> document_symbol::Result {
+        let document = Document::new("file:///cf", language_id, 1, text, PositionEncoding::Utf16);
+        document_symbol(&document)
+    }
+
+    #[test]
+    fn lists_sections_and_declarations() {
+        let actual = symbols(TEMPLATE, "yaml");
+        let names: Vec<_> = actual.iter().map(DocumentSymbol::name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "AWSTemplateFormatVersion",
+                "Parameters",
+                "Resources",
+                "Outputs"
+            ]
+        );
+        assert!(actual[0].children().is_empty());
+
+        let bucket = &actual[2].children()[0];
+        assert_eq!(bucket.name(), "Bucket");
+        assert_eq!(bucket.kind(), &SymbolKind::Class);
+        assert_eq!(bucket.detail(), Some("AWS::S3::Bucket"))
+    }
+
+    #[test]
+    fn describes_parameters_and_outputs() {
+        let actual = symbols(TEMPLATE, "yaml");
+        assert_eq!(actual[1].children()[0].detail(), Some("String"));
+        assert_eq!(actual[3].children()[0].detail(), Some("bucket-arn"))
+    }
+
+    #[test]
+    fn spans_whole_declaration() {
+        let actual = symbols(TEMPLATE, "yaml");
+        let output = &actual[3].children()[0];
+        assert_eq!(output.range().start(), &Position::new(8, 2));
+        assert_eq!(output.range().end(), &Position::new(11, 22));
+        assert_eq!(output.selection_range().end(), &Position::new(8, 5))
+    }
+
+    #[test]
+    fn selects_json_names_without_quotes() {
+        let actual = symbols(
+            r#"{"Resources": {"Queue": {"Type": "AWS::SQS::Queue"}}}"#,
+            "json",
+        );
+        let queue = &actual[0].children()[0];
+        assert_eq!(queue.selection_range().start(), &Position::new(0, 16));
+        assert_eq!(queue.selection_range().end(), &Position::new(0, 21))
+    }
+}
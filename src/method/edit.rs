@@ -0,0 +1,360 @@
+use crate::{
+    document::Document,
+    model::method::text_document::TextEdit,
+    template::{Entry, Format, Node, Span, Value},
+};
+
+/// Spaces added for each level of nesting
+const INDENT: usize = 2;
+
+/// Top-level sections in the order the CloudFormation documentation gives
+/// them
+pub const SECTIONS: [&str; 10] = [
+    "AWSTemplateFormatVersion",
+    "Description",
+    "Metadata",
+    "Parameters",
+    "Rules",
+    "Mappings",
+    "Conditions",
+    "Transform",
+    "Resources",
+    "Outputs",
+];
+
+/// The value of a new entry, either text to insert as it is or a mapping
+/// to lay out to match its surroundings
+#[derive(Debug, Clone)]
+pub enum Insert {
+    Scalar(String),
+    Mapping(Vec<(String, Insert)>),
+}
+
+/// How the entries of a mapping are laid out
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    /// A YAML block mapping, with each key on its own line at a column
+    Block(usize),
+    /// A braced mapping with each key on its own line at a column, as JSON
+    /// is usually written
+    Lines(usize),
+    /// A braced mapping on a single line
+    Inline,
+}
+
+pub fn replace(document: &Document, span: Span, text: &str) -> TextEdit {
+    TextEdit::new(document.range(span), text)
+}
+
+/// Replaces a scalar, keeping any quotes around it
+pub fn replace_scalar(document: &Document, node: &Node, text: &str) -> TextEdit {
+    let text = match document.slice(node.span()).chars().next() {
+        Some(quote @ ('"' | '\'')) => format!("{quote}{text}{quote}"),
+        _ => text.to_string(),
+    };
+    replace(document, node.span(), &text)
+}
+
+/// Adds an entry to the end of a mapping, laid out like the entries already
+/// there
+pub fn append_entry(
+    document: &Document,
+    mapping: &Node,
+    key: &str,
+    value: &Insert,
+) -> Option<TextEdit> {
+    let index = mapping.as_mapping()?.len();
+    insert_entry(document, mapping, index, key, value)
+}
+
+/// Adds an entry to a mapping ahead of the entry at an index, laid out like
+/// the entries already there
+pub fn insert_entry(
+    document: &Document,
+    mapping: &Node,
+    index: usize,
+    key: &str,
+    value: &Insert,
+) -> Option<TextEdit> {
+    let entries = mapping.as_mapping()?;
+    let Some(last) = entries.last() else {
+        // Only braced mappings can be empty
+        let text = format!("{{{}}}", render(document, key, value, Layout::Inline));
+        return Some(replace(document, mapping.span(), &text));
+    };
+    let layout = layout(document, mapping, entries);
+    let separator = match layout {
+        Layout::Block(column) => format!("\n{}", " ".repeat(column)),
+        Layout::Lines(column) => format!(",\n{}", " ".repeat(column)),
+        Layout::Inline => ", ".into(),
+    };
+    let text = render(document, key, value, layout);
+    let edit = match entries.get(index) {
+        Some(entry) => {
+            let start = entry.span().start();
+            replace(
+                document,
+                Span::new(start, start),
+                &format!("{text}{separator}"),
+            )
+        }
+        None => {
+            let end = last.span().end();
+            replace(document, Span::new(end, end), &format!("{separator}{text}"))
+        }
+    };
+    Some(edit)
+}
+
+/// Gives an entry without a value a mapping holding a new entry
+pub fn fill_entry(document: &Document, entry: &Entry, key: &str, value: &Insert) -> TextEdit {
+    match document.template().format() {
+        Format::Json => {
+            let text = format!("{{{}}}", render(document, key, value, Layout::Inline));
+            replace(document, entry.value().span(), &text)
+        }
+        Format::Yaml => {
+            let column = column(document, entry.key().span().start()) + INDENT;
+            let text = format!(
+                "\n{}{}",
+                " ".repeat(column),
+                render(document, key, value, Layout::Block(column))
+            );
+            let end = entry.key().span().end() + 1;
+            replace(document, Span::new(end, end), &text)
+        }
+    }
+}
+
+/// Adds a declaration to a section of the template, adding the section in
+/// its usual place if the template doesn't have it yet
+pub fn declare(document: &Document, section: &str, name: &str, body: Insert) -> Option<TextEdit> {
+    let root = document.template().root();
+    if let Some(entry) = root.entry(section) {
+        return match entry.value().value() {
+            Value::Null => Some(fill_entry(document, entry, name, &body)),
+            _ => append_entry(document, entry.value(), name, &body),
+        };
+    }
+    let position = |key: &str| SECTIONS.iter().position(|section| *section == key);
+    let order = position(section)?;
+    let index = root
+        .as_mapping()?
+        .iter()
+        .position(|entry| position(entry.key().name()).is_some_and(|other| other > order))
+        .unwrap_or(root.as_mapping()?.len());
+    let section_body = Insert::Mapping(vec![(name.into(), body)]);
+    insert_entry(document, root, index, section, &section_body)
+}
+
+/// Removes an entry from a mapping, along with whatever separates it from
+/// its neighbours
+pub fn remove_entry(document: &Document, entries: &[Entry], index: usize) -> Option<TextEdit> {
+    let entry = entries.get(index)?;
+    let previous = index.checked_sub(1).and_then(|index| entries.get(index));
+    let span = match (previous, entries.get(index + 1)) {
+        (_, Some(next)) => Span::new(entry.span().start(), next.span().start()),
+        (Some(previous), None) => Span::new(previous.span().end(), entry.span().end()),
+        (None, None) => {
+            // The only entry takes its line with it, unless something such as
+            // a list item's dash shares the line
+            let start = entry.span().start();
+            let line_start = document.line_start(start);
+            match document
+                .slice(Span::new(line_start, start))
+                .trim()
+                .is_empty()
+            {
+                true => Span::new(line_start.saturating_sub(1), entry.span().end()),
+                false => entry.span(),
+            }
+        }
+    };
+    Some(replace(document, span, ""))
+}
+
+fn layout(document: &Document, mapping: &Node, entries: &[Entry]) -> Layout {
+    let start = mapping.span().start();
+    let first = entries.first().map_or(start, |entry| entry.span().start());
+    if document.slice(Span::new(start, start + 1)) != "{" {
+        Layout::Block(column(document, first))
+    } else if document.line_start(first) != document.line_start(start) {
+        Layout::Lines(column(document, first))
+    } else {
+        Layout::Inline
+    }
+}
+
+/// Text of an entry, with any nested mapping laid out to match
+fn render(document: &Document, key: &str, value: &Insert, layout: Layout) -> String {
+    let key = match document.template().format() {
+        Format::Json => format!("\"{key}\""),
+        Format::Yaml => key.to_string(),
+    };
+    let entries = match value {
+        Insert::Scalar(text) => return format!("{key}: {text}"),
+        Insert::Mapping(entries) => entries,
+    };
+    match layout {
+        Layout::Block(column) => {
+            let column = column + INDENT;
+            let nested: String = entries
+                .iter()
+                .map(|(name, value)| {
+                    let entry = render(document, name, value, Layout::Block(column));
+                    format!("\n{}{entry}", " ".repeat(column))
+                })
+                .collect();
+            format!("{key}:{nested}")
+        }
+        Layout::Lines(column) => {
+            let inner = column + INDENT;
+            let nested: Vec<_> = entries
+                .iter()
+                .map(|(name, value)| render(document, name, value, Layout::Lines(inner)))
+                .collect();
+            let indent = " ".repeat(inner);
+            format!(
+                "{key}: {{\n{indent}{}\n{}}}",
+                nested.join(&format!(",\n{indent}")),
+                " ".repeat(column)
+            )
+        }
+        Layout::Inline => {
+            let nested: Vec<_> = entries
+                .iter()
+                .map(|(name, value)| render(document, name, value, Layout::Inline))
+                .collect();
+            format!("{key}: {{{}}}", nested.join(", "))
+        }
+    }
+}
+
+fn column(document: &Document, offset: usize) -> usize {
+    offset - document.line_start(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::initialise::PositionEncoding;
+
+    use super::*;
+
+    /// Text of a document after applying an edit
+    fn apply(document: &Document, edit: &TextEdit) -> String {
+        let text = document.text();
+        let start = document.offset(edit.range().start());
+        let end = document.offset(edit.range().end());
+        format!("{}{}{}", &text[..start], edit.new_text(), &text[end..])
+    }
+
+    fn document(text: &str) -> Document {
+        Document::new("file:///cf", "yaml", 1, text, PositionEncoding::Utf16)
+    }
+
+    /// `Properties` holding a single `Name`
+    fn properties(value: &str) -> Insert {
+        Insert::Mapping(vec![("Name".into(), Insert::Scalar(value.into()))])
+    }
+
+    fn resource(document: &Document) -> &Node {
+        document
+            .template()
+            .root()
+            .get("Resources")
+            .unwrap()
+            .get("Queue")
+            .unwrap()
+    }
+
+    #[test]
+    fn appends_entries_in_block_yaml() {
+        let document = document("Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n");
+        let edit = append_entry(
+            &document,
+            resource(&document),
+            "Properties",
+            &properties("''"),
+        );
+        assert_eq!(
+            apply(&document, &edit.unwrap()),
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties:\n      Name: ''\n"
+        )
+    }
+
+    #[test]
+    fn appends_entries_in_json() {
+        let document = document(
+            "{\n  \"Resources\": {\n    \"Queue\": {\n      \"Type\": \"AWS::SQS::Queue\"\n    }\n  }\n}",
+        );
+        let edit = append_entry(
+            &document,
+            resource(&document),
+            "Properties",
+            &properties("\"\""),
+        );
+        assert_eq!(
+            apply(&document, &edit.unwrap()),
+            "{\n  \"Resources\": {\n    \"Queue\": {\n      \"Type\": \"AWS::SQS::Queue\",\n      \"Properties\": {\n        \"Name\": \"\"\n      }\n    }\n  }\n}"
+        )
+    }
+
+    #[test]
+    fn appends_entries_to_flow_mappings() {
+        let document = document("Resources:\n  Queue: {Type: AWS::SQS::Queue}\n");
+        let edit = append_entry(
+            &document,
+            resource(&document),
+            "Properties",
+            &properties("''"),
+        );
+        assert_eq!(
+            apply(&document, &edit.unwrap()),
+            "Resources:\n  Queue: {Type: AWS::SQS::Queue, Properties: {Name: ''}}\n"
+        )
+    }
+
+    #[test]
+    fn fills_entries_without_values() {
+        let document =
+            document("Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties:\n");
+        let entry = resource(&document).entry("Properties").unwrap();
+        let edit = fill_entry(&document, entry, "Name", &Insert::Scalar("''".into()));
+        assert_eq!(
+            apply(&document, &edit),
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties:\n      Name: ''\n"
+        )
+    }
+
+    #[test]
+    fn removes_entries_with_their_separators() {
+        let document = document(
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties: {A: 1, B: 2}\n",
+        );
+        let properties = resource(&document).get("Properties").unwrap();
+        let entries = properties.as_mapping().unwrap();
+        let first = remove_entry(&document, entries, 0).unwrap();
+        let last = remove_entry(&document, entries, 1).unwrap();
+        assert_eq!(
+            apply(&document, &first),
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties: {B: 2}\n"
+        );
+        assert_eq!(
+            apply(&document, &last),
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties: {A: 1}\n"
+        )
+    }
+
+    #[test]
+    fn removes_only_entry_with_its_line() {
+        let document = document(
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties:\n      A: 1\n",
+        );
+        let properties = resource(&document).get("Properties").unwrap();
+        let edit = remove_entry(&document, properties.as_mapping().unwrap(), 0).unwrap();
+        assert_eq!(
+            apply(&document, &edit),
+            "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n    Properties:\n"
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    document::Document,
+    model::method::{diagnostic::Diagnostic, initialise::PositionEncoding},
+    schema::{ResourceSchema, SchemaRegistry},
+    template::Span,
+};
+
+const BUCKET: &str = r##"{
+    "typeName": "AWS::S3::Bucket",
+    "description": "Creates a bucket. Really.",
+    "properties": {
+        "Arn": {"type": "string"},
+        "BucketName": {"type": "string", "description": "A name for the bucket"},
+        "AccessControl": {"type": "string", "enum": ["Private", "PublicRead"]},
+        "LoggingConfiguration": {
+            "type": "object",
+            "properties": {
+                "DestinationBucketName": {"type": "string"},
+                "LogFilePrefix": {"type": "string"}
+            },
+            "required": ["DestinationBucketName"],
+            "additionalProperties": false
+        },
+        "Tags": {"type": "array", "items": {"$ref": "#/definitions/Tag"}}
+    },
+    "definitions": {
+        "Tag": {
+            "type": "object",
+            "properties": {"Key": {"type": "string"}, "Value": {"type": "string"}},
+            "required": ["Key", "Value"],
+            "additionalProperties": false
+        }
+    },
+    "required": ["AccessControl"],
+    "additionalProperties": false,
+    "readOnlyProperties": ["/properties/Arn"],
+    "createOnlyProperties": ["/properties/BucketName"]
+}"##;
+
+const FUNCTION: &str = r##"{
+    "typeName": "AWS::Lambda::Function",
+    "description": "Creates a function. Really.",
+    "properties": {
+        "Arn": {"type": "string"},
+        "Role": {"type": "string"},
+        "FunctionName": {"type": "string", "pattern": "^[a-zA-Z0-9-_]+$", "maxLength": 64},
+        "MemorySize": {"type": "integer", "minimum": 128, "maximum": 10240},
+        "PackageType": {"type": "string", "enum": ["Image", "Zip"]},
+        "Runtime": {"type": "string"},
+        "Code": {"type": "object"},
+        "Architectures": {"type": "array", "items": {"type": "string"}},
+        "Environment": {"$ref": "#/definitions/Environment"},
+        "SnapStartResponse": {"$ref": "#/definitions/SnapStartResponse"},
+        "Tags": {"type": "array", "items": {"$ref": "#/definitions/Tag"}}
+    },
+    "definitions": {
+        "SnapStartResponse": {
+            "type": "object",
+            "properties": {
+                "ApplyOn": {"type": "string"},
+                "OptimizationStatus": {"type": "string"}
+            }
+        },
+        "Environment": {
+            "type": "object",
+            "properties": {
+                "Variables": {
+                    "type": "object",
+                    "patternProperties": {"[a-zA-Z]+": {"type": "string"}}
+                }
+            }
+        },
+        "Tag": {
+            "type": "object",
+            "properties": {"Key": {"type": "string"}, "Value": {"type": "string"}},
+            "required": ["Key"],
+            "additionalProperties": false
+        }
+    },
+    "required": ["Role"],
+    "additionalProperties": false,
+    "readOnlyProperties": [
+        "/properties/Arn",
+        "/properties/SnapStartResponse",
+        "/properties/SnapStartResponse/ApplyOn"
+    ],
+    "deprecatedProperties": ["/properties/Code"]
+}"##;
+
+/// Resource types with no properties, to complete and search among
+const OTHER_TYPES: [&str; 2] = ["AWS::S3::BucketPolicy", "AWS::SQS::Queue"];
+
+/// Registry of a few small, hand-written schemas, so that tests don't depend
+/// on the contents of the bundled ones
+pub fn registry() -> SchemaRegistry {
+    let mut registry = SchemaRegistry::default();
+    for schema in [BUCKET, FUNCTION] {
+        registry.insert(ResourceSchema::from_json(schema).unwrap());
+    }
+    for type_name in OTHER_TYPES {
+        registry.insert(
+            ResourceSchema::from_json(&format!(
+                r#"{{"typeName": "{type_name}", "description": "Creates a thing. Really."}}"#
+            ))
+            .unwrap(),
+        );
+    }
+    registry
+}
+
+pub fn document(text: &str) -> Document {
+    Document::new("file:///cf", "yaml", 1, text, PositionEncoding::Utf16)
+}
+
+/// Code, message and the text each diagnostic covers
+pub fn covered(document: &Document, diagnostics: &[Diagnostic]) -> Vec<(String, String, String)> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let range = diagnostic.range();
+            let span = Span::new(document.offset(range.start()), document.offset(range.end()));
+            (
+                diagnostic.code().to_string(),
+                diagnostic.message().to_string(),
+                document.slice(span),
+            )
+        })
+        .collect()
+}
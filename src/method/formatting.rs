@@ -0,0 +1,313 @@
+use std::fmt::Display;
+
+use crate::{
+    document::Document,
+    method::{
+        edit,
+        print::{self, Options},
+    },
+    model::method::{diagnostic::Range, text_document::TextEdit},
+    template::{Entry, Format, Segment, Span},
+};
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct FormattingError {
+    message: String,
+}
+
+impl FormattingError {
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for FormattingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Lays out the whole template, keeping it in the format it's written in
+pub fn formatting(document: &Document, options: Options) -> Result<Vec<TextEdit>, FormattingError> {
+    let whole = Span::new(0, document.text().len());
+    check(document, whole)?;
+    let template = document.template();
+    match print::template(document, template.format(), options) {
+        Some(text) => Ok(edits(document, whole, &text)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Lays out the innermost entry holding the range, or the whole template if
+/// the range spans several sections
+pub fn range_formatting(
+    document: &Document,
+    range: &Range,
+    options: Options,
+) -> Result<Vec<TextEdit>, FormattingError> {
+    let span = Span::new(document.offset(range.start()), document.offset(range.end()));
+    match covering(document, span) {
+        Some(entry) => {
+            check(document, entry.span())?;
+            let text = print::entry(document, entry, options);
+            Ok(edits(document, entry.span(), &text))
+        }
+        None => formatting(document, options),
+    }
+}
+
+/// Fails for text the printer can't lay out without changing it: parts of
+/// the template skipped over by error recovery would be lost, and the nodes
+/// of YAML anchors are printed out in full in place of each alias
+fn check(document: &Document, span: Span) -> Result<(), FormattingError> {
+    let template = document.template();
+    if !template.errors().is_empty() {
+        return Err(FormattingError::new(
+            "Can't format a template with syntax errors",
+        ));
+    }
+    if template
+        .anchors()
+        .iter()
+        .any(|anchor| span.contains(anchor.start()))
+    {
+        return Err(FormattingError::new(
+            "Can't format YAML anchors and aliases",
+        ));
+    }
+    Ok(())
+}
+
+fn edits(document: &Document, span: Span, text: &str) -> Vec<TextEdit> {
+    match document.slice(span) == text {
+        true => Vec::new(),
+        false => vec![edit::replace(document, span, text)],
+    }
+}
+
+/// The innermost entry containing a span which starts a line of its own,
+/// outside of any YAML flow collection, so that it can be laid out afresh
+fn covering(document: &Document, span: Span) -> Option<&Entry> {
+    let template = document.template();
+    let text = document.text();
+    let mut covering = None;
+    for segment in template.root().path_to(span.start()) {
+        let (entry, node) = match segment {
+            Segment::Key(entry) | Segment::Value(entry) => (Some(entry), entry.value()),
+            Segment::Item(_, item) => (None, item),
+            Segment::Function(function) => (None, function.argument()),
+        };
+        if let Some(entry) = entry {
+            let start = entry.span().start();
+            let indent = &text[document.line_start(start)..start];
+            if entry.span().start() <= span.start()
+                && span.end() <= entry.span().end()
+                && indent.chars().all(|c| c == ' ' || c == '-')
+            {
+                covering = Some(entry);
+            }
+        }
+        let flow = text[node.span().start()..].starts_with(['[', '{']);
+        if template.format() == Format::Yaml && flow {
+            break;
+        }
+    }
+    covering
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::{diagnostic::Position, initialise::PositionEncoding};
+
+    use super::*;
+
+    const YAML: &str = "\
+# Queue and its alarm
+Resources:
+    Queue:   # the queue
+        Type: AWS::SQS::Queue
+        Properties:
+            QueueName: !Join
+            - '-'
+            - [!Ref Env, queue]
+            Tags:
+            -   Key: Team
+                Value: platform
+
+            # Alarm to follow
+    Alarm:
+        Type: AWS::CloudWatch::Alarm
+        Properties:
+            AlarmDescription: |
+                Messages are
+                  piling up
+            Dimensions:
+            - Name: QueueName
+              Value:
+                Fn::GetAtt: [Queue, QueueName]
+Parameters:
+  Env: {Type: String,   Default: dev}
+";
+
+    fn document(text: &str) -> Document {
+        Document::new("file:///cf", "yaml", 1, text, PositionEncoding::Utf16)
+    }
+
+    /// Text of a document after applying edits
+    fn apply(document: &Document, edits: &[TextEdit]) -> String {
+        let mut text = document.text();
+        for edit in edits.iter().rev() {
+            let start = document.offset(edit.range().start());
+            let end = document.offset(edit.range().end());
+            text.replace_range(start..end, edit.new_text());
+        }
+        text
+    }
+
+    #[test]
+    fn lays_out_yaml_keeping_comments() {
+        let document = document(YAML);
+        let options = Options::builder().sort_sections(true).build();
+        assert_eq!(
+            apply(&document, &formatting(&document, options).unwrap()),
+            "\
+Parameters:
+  Env: {Type: String, Default: dev}
+# Queue and its alarm
+Resources:
+  Queue:  # the queue
+    Type: AWS::SQS::Queue
+    Properties:
+      QueueName: !Join
+        - '-'
+        - [!Ref Env, queue]
+      Tags:
+        - Key: Team
+          Value: platform
+
+        # Alarm to follow
+  Alarm:
+    Type: AWS::CloudWatch::Alarm
+    Properties:
+      AlarmDescription: |
+        Messages are
+          piling up
+      Dimensions:
+        - Name: QueueName
+          Value:
+            Fn::GetAtt: [Queue, QueueName]
+"
+        )
+    }
+
+    #[test]
+    fn rewrites_functions_in_short_form() {
+        let document = document(
+            "Outputs:\n  Name:\n    Value:\n      Fn::GetAtt: [Queue, QueueName]\n  Url:\n    Value: {Ref: Queue}\n",
+        );
+        let options = Options::builder().indent(4).short_form(true).build();
+        assert_eq!(
+            apply(&document, &formatting(&document, options).unwrap()),
+            "Outputs:\n    Name:\n        Value: !GetAtt Queue.QueueName\n    Url:\n        Value: !Ref Queue\n"
+        )
+    }
+
+    #[test]
+    fn lays_out_json() {
+        let document = document(
+            "{\"Resources\": {\"Queue\": {\"Type\": \"AWS::SQS::Queue\", \"Properties\": {\"QueueName\": {\"Fn::Sub\": \"${Env}-queue\"}}}},\n\"Parameters\": {\"Env\": {\"Type\": \"String\"}}}",
+        );
+        let options = Options::builder().sort_sections(true).build();
+        assert_eq!(
+            apply(&document, &formatting(&document, options).unwrap()),
+            r#"{
+  "Parameters": {
+    "Env": {
+      "Type": "String"
+    }
+  },
+  "Resources": {
+    "Queue": {
+      "Type": "AWS::SQS::Queue",
+      "Properties": {
+        "QueueName": {"Fn::Sub": "${Env}-queue"}
+      }
+    }
+  }
+}
+"#
+        )
+    }
+
+    #[test]
+    fn formats_innermost_entry_holding_range() {
+        let document = document(YAML);
+        let start = YAML.find("AlarmDescription").unwrap();
+        let position = document.position(start);
+        let range = Range::new(position.clone(), position);
+        let edits = range_formatting(&document, &range, Options::builder().build()).unwrap();
+        assert_eq!(
+            apply(&document, &edits),
+            YAML.replace(
+                "            AlarmDescription: |\n                Messages are\n                  piling up",
+                "            AlarmDescription: |\n              Messages are\n                piling up"
+            )
+        );
+
+        let start = Position::new(0, 0);
+        let end = document.position(YAML.len());
+        let range = Range::new(start, end);
+        let whole = range_formatting(&document, &range, Options::builder().build());
+        assert_eq!(whole, formatting(&document, Options::builder().build()))
+    }
+
+    #[test]
+    fn refuses_templates_with_errors() {
+        let document = document("Resources:\n  Queue: [\n");
+        assert_eq!(
+            formatting(&document, Options::builder().build()),
+            Err(FormattingError::new(
+                "Can't format a template with syntax errors"
+            ))
+        )
+    }
+
+    #[test]
+    fn refuses_anchors_and_aliases() {
+        let text = "\
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      Tags: &tags
+        - {Key: Team,   Value: platform}
+  Topic:
+    Type: AWS::SNS::Topic
+    Properties:
+      Tags: *tags
+Outputs:
+  Name:
+    Value:   !Ref Queue
+";
+        let document = document(text);
+        let options = || Options::builder().build();
+        let error = FormattingError::new("Can't format YAML anchors and aliases");
+        assert_eq!(formatting(&document, options()), Err(error));
+
+        let position = document.position(text.find("Value:   !Ref").unwrap());
+        let range = Range::new(position.clone(), position);
+        assert_eq!(
+            apply(
+                &document,
+                &range_formatting(&document, &range, options()).unwrap()
+            ),
+            text.replace("Value:   !Ref", "Value: !Ref")
+        );
+        let position = document.position(text.find("Tags: *tags").unwrap());
+        let range = Range::new(position.clone(), position);
+        assert!(range_formatting(&document, &range, options()).is_err())
+    }
+}
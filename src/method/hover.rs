@@ -0,0 +1,251 @@
+use crate::{
+    document::Document,
+    model::method::{diagnostic::Position, hover},
+    schema::{Property, ResourceSchema, SchemaRegistry, Shape},
+    template::{Intrinsic, Segment, Span},
+};
+
+/// Documentation for the resource type, property or intrinsic function under
+/// the cursor
+pub fn hover(
+    document: &Document,
+    schemas: &SchemaRegistry,
+    position: &Position,
+) -> Option<hover::Result> {
+    let offset = document.offset(position);
+    let path = document.template().root().path_to(offset);
+
+    let function = path.iter().rev().find_map(|segment| match segment {
+        Segment::Function(function) if function.name_span().contains(offset) => Some(function),
+        _ => None,
+    });
+    if let Some(function) = function {
+        let markdown = intrinsic(function.intrinsic());
+        return Some(hover::Result::new(
+            &markdown,
+            document.range(function.name_span()),
+        ));
+    }
+
+    let [Segment::Value(section), Segment::Value(resource), rest @ ..] = path.as_slice() else {
+        return None;
+    };
+    if section.key().name() != "Resources" {
+        return None;
+    }
+    let schema = schemas.get(resource.value().get("Type")?.as_str()?)?;
+    match rest {
+        [Segment::Value(entry)] if entry.key().name() == "Type" => Some(hover::Result::new(
+            &resource_type(schema),
+            document.range(entry.value().span()),
+        )),
+        [Segment::Value(entry), properties @ ..] if entry.key().name() == "Properties" => {
+            let (names, span) = property_path(properties)?;
+            let property = schema.property_at(&names)?;
+            let url = match names.as_slice() {
+                [name] => schema.property_documentation_url(name),
+                _ => schema.documentation_url(),
+            };
+            Some(hover::Result::new(
+                &self::property(schema, property, url),
+                document.range(span),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Names of the keys leading to a property key under the cursor, skipping
+/// over list items
+fn property_path<'a>(path: &[Segment<'a>]) -> Option<(Vec<&'a str>, Span)> {
+    let mut names = Vec::new();
+    for segment in path {
+        match segment {
+            Segment::Value(entry) => names.push(entry.key().name()),
+            Segment::Item(..) => {}
+            Segment::Key(entry) => {
+                names.push(entry.key().name());
+                return Some((names, entry.key().span()));
+            }
+            Segment::Function(_) => return None,
+        }
+    }
+    None
+}
+
+pub fn resource_type(schema: &ResourceSchema) -> String {
+    let mut sections = vec![format!("**{}**", schema.type_name())];
+    sections.extend(schema.description().map(String::from));
+    sections.extend(
+        schema
+            .documentation_url()
+            .map(|url| format!("[Documentation]({url})")),
+    );
+    sections.join("\n\n")
+}
+
+pub fn property(schema: &ResourceSchema, property: &Property, url: Option<String>) -> String {
+    let mut sections = vec![format!(
+        "**{}**: `{}`",
+        property.name(),
+        schema.describe(property.shape())
+    )];
+    sections.extend(property.description().map(String::from));
+
+    let mut flags = vec![format!("Required: {}", yes_no(property.required()))];
+    // Schemas only say which properties force replacement, not whether
+    // updating the others interrupts the resource
+    if property.create_only() {
+        flags.push("Update requires: Replacement".into());
+    }
+    let allowed = allowed_values(schema, property.shape());
+    if !allowed.is_empty() {
+        let allowed: Vec<_> = allowed.iter().map(|value| format!("`{value}`")).collect();
+        flags.push(format!("Allowed values: {}", allowed.join(" | ")));
+    }
+    if property.deprecated() {
+        flags.push("Deprecated".into());
+    }
+    sections.push(
+        flags
+            .iter()
+            .map(|flag| format!("- {flag}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    sections.extend(url.map(|url| format!("[Documentation]({url})")));
+    sections.join("\n\n")
+}
+
+fn allowed_values<'a>(schema: &'a ResourceSchema, shape: &'a Shape) -> &'a [String] {
+    match schema.resolve(shape) {
+        Shape::String { allowed_values, .. } => allowed_values,
+        Shape::List(item) => allowed_values(schema, item),
+        _ => &[],
+    }
+}
+
+fn intrinsic(intrinsic: Intrinsic) -> String {
+    format!(
+        "**{}**\n\n{}\n\n```yaml\n{}\n```\n\n[Documentation]({})",
+        intrinsic.key(),
+        intrinsic.description(),
+        intrinsic.syntax(),
+        intrinsic.documentation_url()
+    )
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "Yes" } else { "No" }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::method::fixture::{document, registry};
+
+    use super::*;
+
+    const TEMPLATE: &str = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      BucketName: !Ref Name
+      Tags:
+        - Key: team
+          Value: platform
+";
+
+    fn hover_at(line: usize, character: usize) -> Option<String> {
+        hover(
+            &document(TEMPLATE),
+            &registry(),
+            &Position::new(line, character),
+        )
+        .map(|result| result.contents().value().to_string())
+    }
+
+    #[test]
+    fn describes_resource_type() {
+        let actual = hover_at(2, 14).unwrap();
+        assert!(actual.starts_with("**AWS::S3::Bucket**\n\nCreates a bucket"));
+        assert!(actual.contains("aws-resource-s3-bucket.html"))
+    }
+
+    #[test]
+    fn describes_property() {
+        let actual = hover_at(4, 8).unwrap();
+        assert!(actual.starts_with("**BucketName**: `String`\n\nA name for the bucket"));
+        assert!(actual.contains("- Required: No\n- Update requires: Replacement"));
+        assert!(actual.contains("#cfn-s3-bucket-bucketname"))
+    }
+
+    #[test]
+    fn describes_nested_property() {
+        let actual = hover_at(6, 11).unwrap();
+        assert!(actual.starts_with("**Key**: `String`"));
+        assert!(actual.contains("- Required: Yes"));
+        assert!(!actual.contains("Update requires"))
+    }
+
+    #[test]
+    fn describes_intrinsic_function() {
+        let actual = hover_at(4, 20).unwrap();
+        assert!(actual.starts_with("**Ref**\n\nReturns the value of the specified parameter"))
+    }
+
+    #[test]
+    fn describes_bundled_schemas() {
+        let text = "\
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      FifoQueue: true
+";
+        let document = document(text);
+        let schemas = SchemaRegistry::bundled();
+        let hover_at = |line, character| {
+            hover(&document, &schemas, &Position::new(line, character))
+                .map(|result| result.contents().value().to_string())
+                .unwrap()
+        };
+
+        let resource_type = hover_at(2, 14);
+        assert!(resource_type.starts_with("**AWS::SQS::Queue**"));
+        assert!(resource_type.contains("aws-resource-sqs-queue.html"));
+        let property = hover_at(4, 8);
+        assert!(property.starts_with("**FifoQueue**: `Boolean`"));
+        assert!(property.contains("- Required: No\n- Update requires: Replacement"));
+        assert!(property.contains("#cfn-sqs-queue-fifoqueue"))
+    }
+
+    #[test]
+    #[ignore = "the bundle was built from cfn-lint's copies, which have no descriptions"]
+    fn describes_bundled_schemas_with_their_descriptions() {
+        let text = "\
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      FifoQueue: true
+";
+        let document = document(text);
+        let schemas = SchemaRegistry::bundled();
+        let description_at = |line, character| {
+            let contents = hover(&document, &schemas, &Position::new(line, character))
+                .map(|result| result.contents().value().to_string())
+                .unwrap();
+            let (_, rest) = contents.split_once("\n\n").unwrap();
+            rest.split("\n\n").next().unwrap_or_default().to_string()
+        };
+
+        assert!(description_at(2, 14).contains("standard or FIFO queue"));
+        assert!(description_at(4, 8).starts_with("If set to true, creates a FIFO queue"))
+    }
+
+    #[test]
+    fn ignores_values() {
+        assert_eq!(hover_at(7, 18), None)
+    }
+}
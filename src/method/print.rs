@@ -0,0 +1,653 @@
+use bon::Builder;
+
+use crate::{
+    document::Document,
+    method::edit::SECTIONS,
+    template::{Comment, Entry, Format, Function, Intrinsic, Key, Node, Span, Value},
+};
+
+/// Width of the `- ` ahead of a block sequence item
+const ITEM: usize = 2;
+
+/// Characters which have a meaning of their own at the start of a YAML
+/// scalar
+const INDICATORS: &str = "-?:,[]{}#&*!|>'\"%@`";
+
+/// Plain scalars which YAML would read as booleans or null rather than
+/// strings
+const RESERVED: [&str; 10] = [
+    "y", "n", "yes", "no", "true", "false", "on", "off", "null", "~",
+];
+
+/// How a template is laid out when printed
+#[derive(Debug, Clone, Copy, Builder)]
+pub struct Options {
+    /// Spaces added for each level of nesting
+    #[builder(default = 2)]
+    indent: usize,
+    /// Whether top-level sections are put in the order the CloudFormation
+    /// documentation gives them
+    #[builder(default)]
+    sort_sections: bool,
+    /// Whether long form functions in YAML are rewritten as short form tags
+    #[builder(default)]
+    short_form: bool,
+}
+
+/// Prints a whole template in a format, keeping the order of keys along with
+/// any comments and blank lines between entries. Functions from JSON become
+/// short form tags in YAML, and those from YAML long form keys in JSON
+pub fn template(document: &Document, format: Format, options: Options) -> Option<String> {
+    let entries = document.template().root().as_mapping()?;
+    let text = document.text();
+    let mut printer = Printer::new(document, &text, format, options, Span::new(0, text.len()));
+    let entries = printer.sorted(entries);
+    let printed = match format {
+        Format::Yaml => {
+            printer.yaml_entries(&entries, 0, 0, false);
+            let body = printer.out.trim_start_matches('\n');
+            match printer.verbatim && text.trim_start().starts_with("---") {
+                true => format!("---\n{body}"),
+                false => body.into(),
+            }
+        }
+        Format::Json => printer.json_mapping(&entries, 0),
+    };
+    Some(format!("{printed}\n"))
+}
+
+/// Prints an entry of a template in its own format, as it would stand at
+/// the column its key is at now. Comments after the entry are left out
+pub fn entry(document: &Document, entry: &Entry, options: Options) -> String {
+    let text = document.text();
+    let format = document.template().format();
+    let mut printer = Printer::new(document, &text, format, options, entry.span());
+    let start = entry.span().start();
+    let column = start - document.line_start(start);
+    match format {
+        Format::Yaml => {
+            printer.yaml_entries(&[entry], column, start, true);
+            printer.out
+        }
+        Format::Json => format!(
+            "{}: {}",
+            printer.key(entry.key()),
+            printer.json(entry.value(), column)
+        ),
+    }
+}
+
+struct Printer<'a> {
+    document: &'a Document,
+    text: &'a str,
+    format: Format,
+    options: Options,
+    /// Scalars, keys and flow collections are copied as written when the
+    /// template is already in the format being printed
+    verbatim: bool,
+    /// Comments in the part of the template being printed which have yet to
+    /// find a place
+    comments: Vec<&'a Comment>,
+    /// End of the last comment printed, from which blank lines are looked
+    /// for instead of the end of the entry before it
+    printed: usize,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn new(
+        document: &'a Document,
+        text: &'a str,
+        format: Format,
+        options: Options,
+        within: Span,
+    ) -> Self {
+        let template = document.template();
+        let verbatim = template.format() == format;
+        let comments = match verbatim {
+            true => template
+                .comments()
+                .iter()
+                .filter(|comment| (within.start()..within.end()).contains(&comment.span().start()))
+                .collect(),
+            false => Vec::new(),
+        };
+        Self {
+            document,
+            text,
+            format,
+            options,
+            verbatim,
+            comments,
+            printed: 0,
+            out: String::new(),
+        }
+    }
+
+    fn sorted<'e>(&self, entries: &'e [Entry]) -> Vec<&'e Entry> {
+        let mut entries: Vec<_> = entries.iter().collect();
+        if self.options.sort_sections {
+            entries.sort_by_key(|entry| {
+                SECTIONS
+                    .iter()
+                    .position(|section| *section == entry.key().name())
+                    .unwrap_or(SECTIONS.len())
+            });
+        }
+        entries
+    }
+
+    fn slice(&self, span: Span) -> &'a str {
+        self.text.get(span.start()..span.end()).unwrap_or("")
+    }
+
+    fn column(&self, offset: usize) -> usize {
+        offset - self.document.line_start(offset)
+    }
+
+    fn key(&self, key: &Key) -> String {
+        match (self.verbatim, self.format) {
+            (true, _) => self.slice(key.span()).into(),
+            (false, Format::Json) => json_string(key.name()),
+            // Keys can't be block scalars
+            (false, Format::Yaml) if key.name().contains('\n') => json_string(key.name()),
+            (false, Format::Yaml) => yaml_string(key.name(), 0),
+        }
+    }
+
+    /// A tag can't apply to a node which already has one, so a function
+    /// whose argument is itself a function is kept in long form. Otherwise
+    /// functions keep the form they're written in unless asked for short
+    /// form
+    fn long_form(&self, function: &Function) -> bool {
+        matches!(function.argument().value(), Value::Function(_))
+            || self.verbatim && !function.short_form() && !self.options.short_form
+    }
+
+    /// Whether a node was written as a flow collection on a single line,
+    /// which is kept on one line
+    fn flow(&self, node: &Node) -> bool {
+        let text = self.slice(node.span());
+        self.verbatim
+            && self.format == Format::Yaml
+            && text.starts_with(['[', '{'])
+            && !text.contains('\n')
+    }
+
+    fn yaml_entries(&mut self, entries: &[&Entry], column: usize, window: usize, inline: bool) {
+        for (index, entry) in entries.iter().enumerate() {
+            let start = entry.span().start();
+            if index > 0 || !inline {
+                // Comments belong to the entry they come before in the
+                // source, wherever sorting puts it
+                let window = entries
+                    .iter()
+                    .map(|entry| entry.span().end())
+                    .filter(|end| *end <= start)
+                    .max()
+                    .unwrap_or(window);
+                self.line(window, start, column);
+            }
+            let key = self.key(entry.key());
+            self.out.push_str(&key);
+            self.out.push(':');
+            let indent = self.options.indent;
+            self.yaml_value(entry.value(), column + indent, entry.key().span().end());
+        }
+        if let (Some(first), Some(last)) = (
+            entries.iter().min_by_key(|entry| entry.span().start()),
+            entries.iter().max_by_key(|entry| entry.span().end()),
+        ) {
+            let source_column = self.column(first.span().start());
+            self.dangling(last.span().end(), source_column, column);
+        }
+    }
+
+    fn yaml_items(&mut self, items: &[Node], column: usize, window: usize, inline: bool) {
+        let mut previous = window;
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 || !inline {
+                self.line(previous, item.span().start(), column);
+            }
+            self.out.push('-');
+            self.yaml_item(item, column + ITEM, previous);
+            previous = item.span().end();
+        }
+        if let Some(first) = items.first() {
+            let start = first.span().start();
+            let dash = self.text[..start].rfind('-').unwrap_or(start);
+            self.dangling(previous, self.column(dash), column);
+        }
+    }
+
+    /// Prints a node following a key, with block collections starting on the
+    /// next line at a column
+    fn yaml_value(&mut self, node: &Node, column: usize, window: usize) {
+        if self.flow(node) {
+            let inline = self.inline(node);
+            self.out.push(' ');
+            self.out.push_str(&inline);
+            self.trailing(node.span().end(), usize::MAX);
+            return;
+        }
+        match node.value() {
+            Value::Mapping(entries) if !entries.is_empty() => {
+                self.trailing(window, node.span().start());
+                let entries: Vec<_> = entries.iter().collect();
+                self.yaml_entries(&entries, column, window, false);
+            }
+            Value::Sequence(items) if !items.is_empty() => {
+                self.trailing(window, node.span().start());
+                self.yaml_items(items, column, window, false);
+            }
+            Value::Function(function) if self.long_form(function) => {
+                self.trailing(window, node.span().start());
+                self.line(window, function.name_span().start(), column);
+                self.yaml_long_form(function, column);
+            }
+            Value::Function(function) => self.yaml_short_form(function, column),
+            _ => self.yaml_scalar(node, column),
+        }
+    }
+
+    /// Prints a node following a list item's dash, with block mappings and
+    /// sequences starting on the same line
+    fn yaml_item(&mut self, node: &Node, column: usize, window: usize) {
+        if !self.flow(node) {
+            match node.value() {
+                Value::Mapping(entries) if !entries.is_empty() => {
+                    self.out.push(' ');
+                    let entries: Vec<_> = entries.iter().collect();
+                    return self.yaml_entries(&entries, column, window, true);
+                }
+                Value::Sequence(items) if !items.is_empty() => {
+                    self.out.push(' ');
+                    return self.yaml_items(items, column, window, true);
+                }
+                Value::Function(function) if self.long_form(function) => {
+                    self.out.push(' ');
+                    return self.yaml_long_form(function, column);
+                }
+                _ => {}
+            }
+        }
+        self.yaml_value(node, column, window)
+    }
+
+    fn yaml_long_form(&mut self, function: &Function, column: usize) {
+        self.out.push_str(function.intrinsic().key());
+        self.out.push(':');
+        let column = column + self.options.indent;
+        self.yaml_value(function.argument(), column, function.name_span().end());
+    }
+
+    fn yaml_short_form(&mut self, function: &Function, column: usize) {
+        self.out.push(' ');
+        self.out.push_str(function.intrinsic().tag());
+        // `!GetAtt Bucket.Arn` is how the attribute is usually written
+        if !function.short_form()
+            && let Some(dotted) = dotted(function)
+        {
+            self.out.push(' ');
+            self.out.push_str(&yaml_string(&dotted, column));
+            return self.trailing(function.argument().span().end(), usize::MAX);
+        }
+        self.yaml_value(function.argument(), column, function.name_span().end())
+    }
+
+    fn yaml_scalar(&mut self, node: &Node, column: usize) {
+        let text = match self.verbatim {
+            true => self.slice(node.span()),
+            false => "",
+        };
+        let scalar = match text.starts_with(['|', '>']) {
+            true => self.block_scalar(node, text, column),
+            false if self.verbatim && !text.contains('\n') => text.into(),
+            false => match node.value() {
+                Value::Bool(value) => value.to_string(),
+                Value::Number(value) => value.clone(),
+                Value::String(value) => yaml_string(value, column),
+                Value::Sequence(_) => "[]".into(),
+                Value::Mapping(_) => "{}".into(),
+                Value::Null | Value::Function(_) => "null".into(),
+            },
+        };
+        if !scalar.is_empty() {
+            self.out.push(' ');
+            self.out.push_str(&scalar);
+        }
+        self.trailing(node.span().end(), usize::MAX);
+    }
+
+    /// A block scalar moved to a column, keeping the indentation of its
+    /// lines relative to one another
+    fn block_scalar(&mut self, node: &Node, text: &str, column: usize) -> String {
+        let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+        let indicators = header.split_whitespace().next().unwrap_or(header);
+        // An explicit indentation can't move, and blank lines kept at the
+        // end lie outside the scalar's span, so these are written afresh
+        if indicators.contains(|c: char| c.is_ascii_digit() || c == '+') {
+            return match node.value() {
+                Value::String(value) => yaml_string(value, column),
+                _ => text.into(),
+            };
+        }
+        let header_end = node.span().start() + header.len();
+        let mut scalar = indicators.to_string();
+        if let Some(comment) = self.take_trailing(node.span().start(), header_end) {
+            scalar.push_str("  ");
+            scalar.push_str(comment);
+        }
+        let lines: Vec<_> = body
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .min()
+            .unwrap_or(0);
+        for line in lines {
+            scalar.push('\n');
+            if let Some(line) = line.get(indent..)
+                && !line.is_empty()
+            {
+                scalar.push_str(&" ".repeat(column));
+                scalar.push_str(line);
+            }
+        }
+        scalar
+    }
+
+    /// A node on a single line, as a YAML flow collection
+    fn inline(&self, node: &Node) -> String {
+        match node.value() {
+            Value::Mapping(entries) => {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|entry| {
+                        format!("{}: {}", self.key(entry.key()), self.inline(entry.value()))
+                    })
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Value::Sequence(items) => {
+                let items: Vec<_> = items.iter().map(|item| self.inline(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Function(function) if self.long_form(function) => format!(
+                "{{{}: {}}}",
+                function.intrinsic().key(),
+                self.inline(function.argument())
+            ),
+            Value::Function(function) => {
+                let argument = match dotted(function) {
+                    Some(dotted) if !function.short_form() => yaml_string(&dotted, 0),
+                    _ => self.inline(function.argument()),
+                };
+                format!("{} {argument}", function.intrinsic().tag())
+            }
+            _ => self.slice(node.span()).into(),
+        }
+    }
+
+    /// Starts a new line for the content at an offset, bringing along the
+    /// comments on lines of their own since a window opened
+    fn line(&mut self, window: usize, start: usize, column: usize) {
+        let mut cursor = match self.printed {
+            printed if (window..=start).contains(&printed) => printed,
+            _ => window,
+        };
+        while let Some(index) = self.comments.iter().position(|comment| {
+            (window..start).contains(&comment.span().start()) && self.own_line(comment)
+        }) {
+            let comment = self.comments.remove(index);
+            self.newline(cursor, comment.span().start(), column);
+            self.out.push_str(self.slice(comment.span()));
+            cursor = comment.span().end();
+            self.printed = cursor;
+        }
+        self.newline(cursor, start, column);
+    }
+
+    /// Starts a new line at a column, keeping one blank line where the
+    /// source has any between two offsets
+    fn newline(&mut self, from: usize, to: usize, column: usize) {
+        let gap: Vec<_> = self.text.get(from..to).unwrap_or("").split('\n').collect();
+        let blank = gap.len() > 2
+            && gap[1..gap.len() - 1]
+                .iter()
+                .any(|line| line.trim().is_empty());
+        if blank && !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(column));
+    }
+
+    /// Carries over a comment at the end of the line an offset is on, if it
+    /// comes before another offset
+    fn trailing(&mut self, from: usize, to: usize) {
+        if let Some(comment) = self.take_trailing(from, to) {
+            self.out.push_str("  ");
+            self.out.push_str(comment);
+        }
+    }
+
+    fn take_trailing(&mut self, from: usize, to: usize) -> Option<&'a str> {
+        let line = self.document.line_start(from);
+        let index = self.comments.iter().position(|comment| {
+            let start = comment.span().start();
+            (from..to).contains(&start) && self.document.line_start(start) == line
+        })?;
+        let comment = self.comments.remove(index);
+        Some(self.slice(comment.span()))
+    }
+
+    /// Carries over the comments which follow the last entry or item of a
+    /// block collection, indented at least as far as its entries
+    fn dangling(&mut self, after: usize, source_column: usize, column: usize) {
+        let mut cursor = after;
+        while let Some(index) = self
+            .comments
+            .iter()
+            .position(|comment| comment.span().start() >= cursor)
+        {
+            let comment = self.comments[index];
+            let start = comment.span().start();
+            if !self.text[cursor..start].trim().is_empty()
+                || !self.own_line(comment)
+                || self.column(start) < source_column
+            {
+                return;
+            }
+            self.comments.remove(index);
+            self.newline(cursor, start, column);
+            self.out.push_str(self.slice(comment.span()));
+            cursor = comment.span().end();
+            self.printed = cursor;
+        }
+    }
+
+    fn own_line(&self, comment: &Comment) -> bool {
+        let start = comment.span().start();
+        self.text[self.document.line_start(start)..start]
+            .trim()
+            .is_empty()
+    }
+
+    /// Text of a node as JSON laid out over lines, starting at a column.
+    /// Function calls with simple arguments are kept to one line
+    fn json(&self, node: &Node, column: usize) -> String {
+        let text = self.slice(node.span());
+        match node.value() {
+            Value::Null | Value::Bool(_) | Value::String(_) | Value::Number(_) if self.verbatim => {
+                text.into()
+            }
+            Value::Null => "null".into(),
+            Value::Bool(value) => value.to_string(),
+            // YAML numbers such as `0x1F` or `1_000` have no JSON form
+            Value::Number(value) => match serde_json::from_str::<serde_json::Number>(value) {
+                Ok(_) => value.clone(),
+                Err(_) => json_string(value),
+            },
+            Value::String(value) => json_string(value),
+            Value::Sequence(items) if items.is_empty() => "[]".into(),
+            Value::Sequence(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.json(item, column + self.options.indent));
+                self.json_block('[', ']', items, column)
+            }
+            Value::Mapping(entries) => {
+                let entries: Vec<_> = entries.iter().collect();
+                self.json_mapping(&entries, column)
+            }
+            Value::Function(function) => {
+                let key = json_string(function.intrinsic().key());
+                let argument = long_form_argument(function);
+                let simple = match argument.as_sequence() {
+                    Some(items) => items.iter().all(scalar),
+                    None => scalar(&argument),
+                };
+                if simple {
+                    let argument = match argument.as_sequence() {
+                        Some(items) => {
+                            let items: Vec<_> =
+                                items.iter().map(|item| self.json(item, 0)).collect();
+                            format!("[{}]", items.join(", "))
+                        }
+                        None => self.json(&argument, 0),
+                    };
+                    return format!("{{{key}: {argument}}}");
+                }
+                let argument = self.json(&argument, column + self.options.indent);
+                self.json_block('{', '}', [format!("{key}: {argument}")].into_iter(), column)
+            }
+        }
+    }
+
+    fn json_mapping(&self, entries: &[&Entry], column: usize) -> String {
+        if entries.is_empty() {
+            return "{}".into();
+        }
+        let entries = entries.iter().map(|entry| {
+            format!(
+                "{}: {}",
+                self.key(entry.key()),
+                self.json(entry.value(), column + self.options.indent)
+            )
+        });
+        self.json_block('{', '}', entries, column)
+    }
+
+    fn json_block(
+        &self,
+        open: char,
+        close: char,
+        lines: impl Iterator<Item = String>,
+        column: usize,
+    ) -> String {
+        let inner = " ".repeat(column + self.options.indent);
+        let lines: Vec<_> = lines.map(|line| format!("{inner}{line}")).collect();
+        format!(
+            "{open}\n{}\n{}{close}",
+            lines.join(",\n"),
+            " ".repeat(column)
+        )
+    }
+}
+
+/// The resource and attribute of a `Fn::GetAtt` as a single dotted string
+fn dotted(function: &Function) -> Option<String> {
+    if function.intrinsic() != Intrinsic::GetAtt {
+        return None;
+    }
+    let [resource, attribute] = function.argument().as_sequence()? else {
+        return None;
+    };
+    let (resource, attribute) = (resource.as_str()?, attribute.as_str()?);
+    (!resource.contains('.')).then(|| format!("{resource}.{attribute}"))
+}
+
+/// The argument as written in long form, where `Fn::GetAtt` only takes a
+/// list
+fn long_form_argument(function: &Function) -> Node {
+    let argument = function.argument();
+    if function.intrinsic() == Intrinsic::GetAtt
+        && let Some(dotted) = argument.as_str()
+        && let Some((resource, attribute)) = dotted.split_once('.')
+    {
+        let span = argument.span();
+        let items = [resource, attribute]
+            .map(|part| Node::new(Value::String(part.into()), span))
+            .to_vec();
+        return Node::new(Value::Sequence(items), span);
+    }
+    argument.clone()
+}
+
+fn scalar(node: &Node) -> bool {
+    !matches!(
+        node.value(),
+        Value::Sequence(_) | Value::Mapping(_) | Value::Function(_)
+    )
+}
+
+/// A string written plainly where YAML would read it back as the same
+/// string, as a literal block if it spans lines and quoted otherwise
+fn yaml_string(value: &str, column: usize) -> String {
+    if plain(value) {
+        return value.into();
+    }
+    let printable = |c: char| !c.is_control() || c == '\t';
+    let lines = value.trim_end_matches('\n');
+    if lines.contains('\n')
+        && !lines.starts_with([' ', '\t', '\n'])
+        && lines.chars().all(|c| c == '\n' || printable(c))
+    {
+        let chomping = match value.len() - lines.len() {
+            0 => "-",
+            1 => "",
+            _ => "+",
+        };
+        let indent = " ".repeat(column);
+        // Text kept by `+` ends with blank lines, the last of which comes
+        // from whatever follows the scalar
+        let body = value.strip_suffix('\n').unwrap_or(value);
+        let lines: Vec<_> = body
+            .split('\n')
+            .map(|line| match line.is_empty() {
+                true => String::new(),
+                false => format!("{indent}{line}"),
+            })
+            .collect();
+        return format!("|{chomping}\n{}", lines.join("\n"));
+    }
+    if value.chars().all(printable) {
+        return format!("'{}'", value.replace('\'', "''"));
+    }
+    // JSON's escapes are a subset of those in double quoted YAML
+    json_string(value)
+}
+
+fn plain(value: &str) -> bool {
+    let Some(first) = value.chars().next() else {
+        return false;
+    };
+    // Anything which might read as a number or date is quoted too
+    let numeric = first.is_ascii_digit() || matches!(first, '.' | '+');
+    !INDICATORS.contains(first)
+        && !numeric
+        && !first.is_whitespace()
+        && !value.ends_with([' ', ':'])
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.chars().any(char::is_control)
+        && !RESERVED.contains(&value.to_ascii_lowercase().as_str())
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
@@ -0,0 +1,212 @@
+use crate::{
+    document::Document,
+    model::method::{
+        diagnostic::{Location, Position},
+        references,
+    },
+    template::{Declaration, DeclarationKind, ReferenceKind},
+};
+
+/// Locations of every use of the parameter, resource, condition, mapping or
+/// export declared or referred to under the cursor. Exports are looked up
+/// across all open documents
+pub fn references<'a>(
+    document: &'a Document,
+    documents: &[&'a Document],
+    position: &Position,
+    include_declaration: bool,
+) -> Option<references::Result> {
+    let offset = document.offset(position);
+    let (owner, kind, declaration) = target(document, documents, offset)?;
+
+    let mut locations = Vec::new();
+    if include_declaration {
+        locations.push(Location::new(
+            owner.uri(),
+            owner.range(declaration.logical_id().span()),
+        ));
+    }
+    let mut searched: Vec<&Document> = match kind {
+        DeclarationKind::Output => documents.to_vec(),
+        _ => vec![owner],
+    };
+    searched.sort_by_key(|document| document.uri());
+    for searched in searched {
+        locations.extend(
+            searched
+                .template()
+                .references_to(kind, declaration)
+                .map(|reference| Location::new(searched.uri(), searched.range(reference.span()))),
+        );
+    }
+    Some(locations)
+}
+
+/// The declaration under the cursor, or the one referred to by the reference
+/// under it, along with the document declaring it
+fn target<'a>(
+    document: &'a Document,
+    documents: &[&'a Document],
+    offset: usize,
+) -> Option<(&'a Document, DeclarationKind, &'a Declaration)> {
+    let template = document.template();
+    if let Some((kind, declaration)) = template.declaration_at(offset) {
+        return Some((document, kind, declaration));
+    }
+    let reference = template
+        .references()
+        .iter()
+        .find(|reference| reference.span().contains(offset))?;
+    if let Some((kind, declaration)) = template.declaration(reference) {
+        return Some((document, kind, declaration));
+    }
+    match reference.kind() {
+        ReferenceKind::Import => documents.iter().find_map(|&other| {
+            let (kind, declaration) = other.template().declaration(reference)?;
+            Some((other, kind, declaration))
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::initialise::PositionEncoding;
+
+    use super::*;
+
+    const TEMPLATE: &str = "\
+Parameters:
+  Env:
+    Type: String
+Conditions:
+  IsProd: !Equals [!Ref Env, prod]
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+  Bucket:
+    Type: AWS::S3::Bucket
+    Condition: IsProd
+    DependsOn: Queue
+    Properties:
+      BucketName: !If [IsProd, !Sub '${Queue.QueueName}-${Env}', !GetAtt Queue.Arn]
+Outputs:
+  Arn:
+    Value: !Ref Queue
+    Export:
+      Name: queue-arn
+";
+
+    fn document(uri: &str, text: &str) -> Document {
+        Document::new(uri, "yaml", 1, text, PositionEncoding::Utf16)
+    }
+
+    /// `(line, character)` of each location found from the first occurrence
+    /// of `marker` on a line
+    fn references_at(
+        documents: &[&Document],
+        line: usize,
+        marker: &str,
+        include_declaration: bool,
+    ) -> Option<Vec<(String, usize, usize)>> {
+        let document = documents[0];
+        let character = document
+            .text()
+            .lines()
+            .nth(line)
+            .unwrap()
+            .find(marker)
+            .unwrap()
+            + 1;
+        references(
+            document,
+            documents,
+            &Position::new(line, character),
+            include_declaration,
+        )
+        .map(|locations| {
+            locations
+                .iter()
+                .map(|location| {
+                    let start = location.range().start();
+                    (location.uri().to_string(), start.line(), start.character())
+                })
+                .collect()
+        })
+    }
+
+    fn lines(locations: Option<Vec<(String, usize, usize)>>) -> Vec<usize> {
+        locations
+            .unwrap()
+            .into_iter()
+            .map(|(_, line, _)| line)
+            .collect()
+    }
+
+    #[test]
+    fn finds_references_to_parameter() {
+        let document = document("file:///cf", TEMPLATE);
+        let actual = references_at(&[&document], 1, "Env", false);
+        assert_eq!(
+            actual,
+            Some(vec![
+                ("file:///cf".into(), 4, 24),
+                ("file:///cf".into(), 13, 58),
+            ])
+        )
+    }
+
+    #[test]
+    fn finds_every_use_of_resource() {
+        let document = document("file:///cf", TEMPLATE);
+        assert_eq!(
+            lines(references_at(&[&document], 6, "Queue", false)),
+            vec![11, 13, 13, 16]
+        )
+    }
+
+    #[test]
+    fn finds_condition_attributes_and_if() {
+        let document = document("file:///cf", TEMPLATE);
+        assert_eq!(
+            lines(references_at(&[&document], 4, "IsProd", false)),
+            vec![10, 13]
+        )
+    }
+
+    #[test]
+    fn starts_from_reference_under_cursor() {
+        let document = document("file:///cf", TEMPLATE);
+        assert_eq!(
+            lines(references_at(&[&document], 16, "Queue", true)),
+            vec![6, 11, 13, 13, 16]
+        )
+    }
+
+    #[test]
+    fn finds_imports_of_export_in_open_documents() {
+        let exporting = document("file:///a", TEMPLATE);
+        let importing = document(
+            "file:///b",
+            "Outputs:\n  A:\n    Value: !ImportValue queue-arn\n",
+        );
+        let expected = Some(vec![
+            ("file:///a".into(), 15, 2),
+            ("file:///b".into(), 2, 24),
+        ]);
+        assert_eq!(
+            references_at(&[&exporting, &importing], 15, "Arn", true),
+            expected
+        );
+        assert_eq!(
+            references_at(&[&importing, &exporting], 2, "queue", true),
+            expected
+        )
+    }
+
+    #[test]
+    fn ignores_plain_values() {
+        let document = document("file:///cf", TEMPLATE);
+        assert_eq!(references_at(&[&document], 2, "String", true), None)
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt::Display;
+
+use crate::{
+    document::Document,
+    model::method::{
+        diagnostic::Position,
+        prepare_rename,
+        rename::{self, WorkspaceEdit},
+        text_document::TextEdit,
+    },
+    template::{Declaration, DeclarationKind, ReferenceKind, Span, Template},
+};
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct RenameError {
+    message: String,
+}
+
+impl RenameError {
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Range and current text of the logical id, or reference to one, under the
+/// cursor
+pub fn prepare_rename(document: &Document, position: &Position) -> Option<prepare_rename::Result> {
+    let template = document.template();
+    let (_, declaration, span) = target(template, document.offset(position))?;
+    Some(prepare_rename::Result::new(
+        document.range(span),
+        declaration.logical_id().name(),
+    ))
+}
+
+/// Renames the logical id under the cursor, or the one referred to under it,
+/// along with every reference to it in the template
+pub fn rename(
+    document: &Document,
+    position: &Position,
+    new_name: &str,
+) -> Result<Option<rename::Result>, RenameError> {
+    let template = document.template();
+    let Some((kind, declaration, _)) = target(template, document.offset(position)) else {
+        return Ok(None);
+    };
+    if new_name.is_empty() || !new_name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(RenameError::new(&format!(
+            "'{new_name}' is not a valid logical id, which must be alphanumeric"
+        )));
+    }
+    let name = declaration.logical_id().name();
+    if new_name != name && collides(template, kind, new_name) {
+        return Err(RenameError::new(&format!(
+            "'{new_name}' is already declared in {}",
+            kind.section()
+        )));
+    }
+
+    // Imports name an output's export, which stays the same when its
+    // logical id changes
+    let spans = std::iter::once(declaration.logical_id().name_span()).chain(
+        template
+            .references_to(kind, declaration)
+            .filter(|reference| reference.kind() != ReferenceKind::Import)
+            .map(|reference| reference.span()),
+    );
+    let mut edit = WorkspaceEdit::default();
+    for span in spans {
+        edit.push(
+            document.uri(),
+            TextEdit::new(document.range(span), new_name),
+        );
+    }
+    Ok(Some(edit))
+}
+
+/// The declaration under the cursor, or the one referred to by the reference
+/// under it, along with the span of the name at the cursor
+fn target(template: &Template, offset: usize) -> Option<(DeclarationKind, &Declaration, Span)> {
+    if let Some((kind, declaration)) = template.declaration_at(offset) {
+        return Some((kind, declaration, declaration.logical_id().name_span()));
+    }
+    let reference = template
+        .references()
+        .iter()
+        .find(|reference| reference.span().contains(offset))?;
+    let (kind, declaration) = template.declaration(reference)?;
+    // Outputs are only referred to by export name, which isn't a logical id
+    (kind != DeclarationKind::Output).then_some((kind, declaration, reference.span()))
+}
+
+/// Parameters and resources share a namespace, as `Ref` may refer to either
+fn collides(template: &Template, kind: DeclarationKind, name: &str) -> bool {
+    let kinds: &[DeclarationKind] = match kind {
+        DeclarationKind::Parameter | DeclarationKind::Resource => {
+            &[DeclarationKind::Parameter, DeclarationKind::Resource]
+        }
+        _ => &[kind],
+    };
+    kinds
+        .iter()
+        .any(|kind| template.find(*kind, name).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::initialise::PositionEncoding;
+
+    use super::*;
+
+    const YAML: &str = "\
+Parameters:
+  Env:
+    Type: String
+Conditions:
+  IsProd: !Equals [!Ref Env, prod]
+Resources:
+  Queue:
+    Type: AWS::SQS::Queue
+  Bucket:
+    Type: AWS::S3::Bucket
+    Condition: IsProd
+    DependsOn: [Queue]
+    Properties:
+      BucketName: !Sub '${Queue.QueueName}-${Env}'
+Outputs:
+  Queue:
+    Value: !GetAtt Queue.Arn
+";
+
+    fn document(text: &str) -> Document {
+        Document::new("file:///cf", "yaml", 1, text, PositionEncoding::Utf16)
+    }
+
+    /// Applies the edits of a rename from the first occurrence of `marker`
+    /// on a line
+    fn rename_at(
+        document: &Document,
+        line: usize,
+        marker: &str,
+        new_name: &str,
+    ) -> Result<Option<String>, RenameError> {
+        let text = document.text();
+        let character = text.lines().nth(line).unwrap().find(marker).unwrap() + 1;
+        let edit = rename(document, &Position::new(line, character), new_name)?;
+        Ok(edit.map(|edit| {
+            let mut edits = edit.changes()["file:///cf"].clone();
+            edits.sort_by_key(|edit| document.offset(edit.range().start()));
+            let mut renamed = text.clone();
+            for edit in edits.iter().rev() {
+                let start = document.offset(edit.range().start());
+                let end = document.offset(edit.range().end());
+                renamed.replace_range(start..end, edit.new_text());
+            }
+            renamed
+        }))
+    }
+
+    #[test]
+    fn renames_resource_and_references() {
+        let actual = rename_at(&document(YAML), 6, "Queue", "Jobs")
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual.matches("Jobs").count(), 4);
+        assert!(actual.contains("DependsOn: [Jobs]"));
+        assert!(actual.contains("!Sub '${Jobs.QueueName}-${Env}'"));
+        assert!(actual.contains("!GetAtt Jobs.Arn"));
+        assert!(actual.contains("Outputs:\n  Queue:"))
+    }
+
+    #[test]
+    fn keeps_imports_when_renaming_outputs() {
+        let text = "\
+Resources:
+  Topic:
+    Type: AWS::SNS::Topic
+    Properties:
+      TopicName: !ImportValue Queue
+Outputs:
+  Queue:
+    Value: !GetAtt Topic.TopicArn
+    Export:
+      Name: Queue
+";
+        let actual = rename_at(&document(text), 6, "Queue", "Topic")
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual, text.replace("  Queue:\n", "  Topic:\n"))
+    }
+
+    #[test]
+    fn renames_from_reference() {
+        let actual = rename_at(&document(YAML), 10, "IsProd", "Production")
+            .unwrap()
+            .unwrap();
+        assert!(actual.contains("  Production: !Equals"));
+        assert!(actual.contains("Condition: Production"))
+    }
+
+    #[test]
+    fn renames_quoted_json_keys() {
+        let text = r#"{"Parameters": {"Env": {"Type": "String"}}, "Outputs": {"A": {"Value": {"Ref": "Env"}}}}"#;
+        let document = Document::new("file:///cf", "json", 1, text, PositionEncoding::Utf16);
+        let actual = rename_at(&document, 0, "Env", "Stage").unwrap().unwrap();
+        assert_eq!(
+            actual,
+            r#"{"Parameters": {"Stage": {"Type": "String"}}, "Outputs": {"A": {"Value": {"Ref": "Stage"}}}}"#
+        )
+    }
+
+    #[test]
+    fn refuses_invalid_names() {
+        let actual = rename_at(&document(YAML), 1, "Env", "my-env");
+        assert!(actual.unwrap_err().to_string().contains("alphanumeric"));
+        assert!(rename_at(&document(YAML), 1, "Env", "").is_err())
+    }
+
+    #[test]
+    fn refuses_collisions() {
+        let actual = rename_at(&document(YAML), 1, "Env", "Bucket");
+        assert!(actual.unwrap_err().to_string().contains("already declared"));
+        assert!(rename_at(&document(YAML), 15, "Queue", "Queue").is_ok())
+    }
+
+    #[test]
+    fn prepares_rename_of_reference() {
+        let document = document(YAML);
+        let actual = prepare_rename(&document, &Position::new(13, 27)).unwrap();
+        assert_eq!(actual.placeholder(), "Queue");
+        assert_eq!(actual.range().start(), &Position::new(13, 26))
+    }
+
+    #[test]
+    fn ignores_pseudo_parameters_and_values() {
+        let document = document("Outputs:\n  A:\n    Value: !Ref AWS::Region\n");
+        assert!(prepare_rename(&document, &Position::new(2, 18)).is_none());
+        assert_eq!(rename_at(&document, 2, "Value", "B"), Ok(None))
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    document::Document,
+    method::{completion::fuzzy_score, document_symbol::declared_kind},
+    model::method::{
+        diagnostic::Location,
+        document_symbol::SymbolKind,
+        workspace_symbol::{self, SymbolInformation},
+    },
+    template::{Intrinsic, Node, Value},
+};
+
+/// Clients only show the first few results, and a query is sent for every
+/// keystroke
+const MAX_SYMBOLS: usize = 200;
+
+/// Logical ids, export names and resource types across templates which
+/// fuzzily match a query, best and shortest matches first
+pub fn workspace_symbol<'a>(
+    symbols: impl Iterator<Item = &'a SymbolInformation>,
+    query: &str,
+) -> workspace_symbol::Result {
+    let mut matches: Vec<_> = symbols
+        .filter_map(|symbol| Some((fuzzy_score(query, symbol.name())?, symbol)))
+        .collect();
+    matches.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.name().len().cmp(&b.name().len()))
+            .then_with(|| a.name().cmp(b.name()))
+            .then_with(|| a.location().uri().cmp(b.location().uri()))
+    });
+    matches
+        .into_iter()
+        .take(MAX_SYMBOLS)
+        .map(|(_, symbol)| symbol.clone())
+        .collect()
+}
+
+/// The symbols of a template which workspace searches look through
+pub fn symbols(document: &Document) -> Vec<SymbolInformation> {
+    let location = |node: &Node| Location::new(document.uri(), document.range(node.span()));
+    let mut symbols = Vec::new();
+    for section in document.template().root().as_mapping().unwrap_or_default() {
+        let section_name = section.key().name();
+        let Some(kind) = declared_kind(section_name) else {
+            continue;
+        };
+        for entry in section.value().as_mapping().unwrap_or_default() {
+            let logical_id = entry.key();
+            if logical_id.name().is_empty() {
+                continue;
+            }
+            symbols.push(SymbolInformation::new(
+                logical_id.name(),
+                kind,
+                Location::new(document.uri(), document.range(logical_id.name_span())),
+                section_name,
+            ));
+
+            let body = entry.value();
+            let (name, kind) = match section_name {
+                "Resources" => (body.get("Type"), SymbolKind::Struct),
+                "Outputs" => (
+                    body.get("Export").and_then(|export| export.get("Name")),
+                    SymbolKind::Constant,
+                ),
+                _ => continue,
+            };
+            if let Some((node, name)) = name.and_then(|node| Some((node, text(node)?))) {
+                symbols.push(SymbolInformation::new(
+                    name,
+                    kind,
+                    location(node),
+                    logical_id.name(),
+                ));
+            }
+        }
+    }
+    symbols
+}
+
+/// A plain string, or the template of `Fn::Sub`, which export names often
+/// use to include the stack name
+fn text(node: &Node) -> Option<&str> {
+    match node.value() {
+        Value::Function(function) if function.intrinsic() == Intrinsic::Sub => {
+            let argument = function.argument();
+            argument
+                .as_str()
+                .or_else(|| argument.as_sequence()?.first()?.as_str())
+        }
+        _ => node.as_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::method::initialise::PositionEncoding;
+
+    use super::*;
+
+    const NETWORK: &str = "\
+Resources:
+  Vpc:
+    Type: AWS::EC2::VPC
+Outputs:
+  VpcId:
+    Value: !Ref Vpc
+    Export:
+      Name: !Sub '${AWS::StackName}-VpcId'
+";
+
+    const APP: &str = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Service:
+    Type: AWS::ECS::Service
+";
+
+    fn search(query: &str) -> Vec<(String, String, String)> {
+        let network = Document::new(
+            "file:///network.yaml",
+            "yaml",
+            1,
+            NETWORK,
+            PositionEncoding::Utf16,
+        );
+        let app = Document::new("file:///app.yaml", "yaml", 1, APP, PositionEncoding::Utf16);
+        let symbols = [symbols(&network), symbols(&app)];
+        workspace_symbol(symbols.iter().flatten(), query)
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name().to_string(),
+                    symbol.container_name().to_string(),
+                    symbol.location().uri().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_export_names() {
+        let actual = search("vpcid");
+        assert_eq!(actual[0].0, "VpcId");
+        assert!(actual.contains(&(
+            "${AWS::StackName}-VpcId".into(),
+            "VpcId".into(),
+            "file:///network.yaml".into()
+        )))
+    }
+
+    #[test]
+    fn finds_resource_types() {
+        assert_eq!(
+            search("ecs::service"),
+            vec![(
+                "AWS::ECS::Service".into(),
+                "Service".into(),
+                "file:///app.yaml".into()
+            )]
+        )
+    }
+
+    #[test]
+    fn finds_logical_ids_in_every_section() {
+        let actual = search("");
+        assert_eq!(actual.len(), 7);
+        assert!(actual.contains(&("Env".into(), "Parameters".into(), "file:///app.yaml".into())))
+    }
+}
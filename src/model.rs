@@ -1,8 +1,19 @@
 use std::fmt::Display;
 
 // reference: https://www.jsonrpc.org/specification
+use method::code_action;
+use method::completion;
+use method::definition;
 use method::diagnostic;
+use method::document_symbol;
+use method::formatting;
+use method::hover;
 use method::initialise;
+use method::prepare_rename;
+use method::range_formatting;
+use method::references;
+use method::rename;
+use method::workspace_symbol;
 use method::NotificationMethod;
 use method::RequestMethod;
 use serde::{Deserialize, Serialize};
@@ -160,6 +171,17 @@ impl Notification {
 pub enum ResponseResult {
     Initialise(initialise::Result),
     PullDiagnostics(diagnostic::pull::Result),
+    Hover(hover::Result),
+    Completion(completion::List),
+    Definition(definition::Result),
+    References(references::Result),
+    PrepareRename(prepare_rename::Result),
+    Rename(rename::Result),
+    DocumentSymbol(document_symbol::Result),
+    WorkspaceSymbol(workspace_symbol::Result),
+    CodeAction(code_action::Result),
+    Formatting(formatting::Result),
+    RangeFormatting(range_formatting::Result),
     Null,
 }
 
@@ -236,6 +258,7 @@ pub enum ErrorCode {
     Internal,
     ServerNotInitialised,
     ServerAlreadyInitialised,
+    RequestFailed,
 }
 
 impl ErrorCode {
@@ -248,6 +271,7 @@ impl ErrorCode {
             ErrorCode::Internal => -32603,
             ErrorCode::ServerNotInitialised => -32002,
             ErrorCode::ServerAlreadyInitialised => -32003,
+            ErrorCode::RequestFailed => -32803,
         }
     }
 }
@@ -262,6 +286,7 @@ impl Display for ErrorCode {
             ErrorCode::Internal => write!(f, "Internal failure"),
             ErrorCode::ServerNotInitialised => write!(f, "Server not initialised"),
             ErrorCode::ServerAlreadyInitialised => write!(f, "Server already initialised"),
+            ErrorCode::RequestFailed => write!(f, "Request failed"),
         }
     }
 }
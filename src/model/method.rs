@@ -1,11 +1,25 @@
 use serde::{Deserialize, Serialize};
 
+pub mod code_action;
+pub mod completion;
+pub mod definition;
 pub mod diagnostic;
 pub mod did_change;
+pub mod did_change_configuration;
+pub mod did_close;
 pub mod did_open;
 pub mod did_save;
+pub mod document_symbol;
+pub mod formatting;
+pub mod hover;
 pub mod initialise;
 pub mod initialised;
+pub mod prepare_rename;
+pub mod range_formatting;
+pub mod references;
+pub mod rename;
+pub mod text_document;
+pub mod workspace_symbol;
 
 #[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
@@ -19,6 +33,39 @@ pub enum RequestMethod {
 
     #[serde(rename = "textDocument/diagnostic")]
     PullDiagnostics(diagnostic::pull::Params),
+
+    #[serde(rename = "textDocument/hover")]
+    Hover(hover::Params),
+
+    #[serde(rename = "textDocument/completion")]
+    Completion(completion::Params),
+
+    #[serde(rename = "textDocument/definition")]
+    Definition(definition::Params),
+
+    #[serde(rename = "textDocument/references")]
+    References(references::Params),
+
+    #[serde(rename = "textDocument/prepareRename")]
+    PrepareRename(prepare_rename::Params),
+
+    #[serde(rename = "textDocument/rename")]
+    Rename(rename::Params),
+
+    #[serde(rename = "textDocument/documentSymbol")]
+    DocumentSymbol(document_symbol::Params),
+
+    #[serde(rename = "workspace/symbol")]
+    WorkspaceSymbol(workspace_symbol::Params),
+
+    #[serde(rename = "textDocument/codeAction")]
+    CodeAction(code_action::Params),
+
+    #[serde(rename = "textDocument/formatting")]
+    Formatting(formatting::Params),
+
+    #[serde(rename = "textDocument/rangeFormatting")]
+    RangeFormatting(range_formatting::Params),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -31,11 +78,14 @@ pub enum NotificationMethod {
     #[serde(rename = "initialized")]
     Initialised(initialised::Params),
 
+    #[serde(rename = "workspace/didChangeConfiguration")]
+    DidChangeConfiguration(did_change_configuration::Params),
+
     #[serde(rename = "textDocument/didChange")]
     DidChange(did_change::Params),
 
     #[serde(rename = "textDocument/didClose")]
-    DidClose(serde_json::Value),
+    DidClose(did_close::Params),
 
     #[serde(rename = "textDocument/didOpen")]
     DidOpen(did_open::Params),
@@ -0,0 +1,198 @@
+use bon::Builder;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{
+    diagnostic::{Diagnostic, Range},
+    rename::WorkspaceEdit,
+    text_document::{TextDocumentIdentifier, TextEdit},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    #[serde(rename = "textDocument")]
+    text_document: TextDocumentIdentifier,
+    range: Range,
+    context: Context,
+}
+
+impl Params {
+    pub fn uri(&self) -> &str {
+        self.text_document.uri()
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.context.diagnostics
+    }
+
+    pub fn only(&self) -> Option<&[String]> {
+        self.context.only.as_deref()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+struct Context {
+    #[serde(deserialize_with = "own_diagnostics")]
+    diagnostics: Vec<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    only: Option<Vec<String>>,
+}
+
+/// Clients may pass along diagnostics from other sources, which don't match
+/// the shape of this server's diagnostics and have no fixes to offer
+fn own_diagnostics<'de, D>(deserializer: D) -> std::result::Result<Vec<Diagnostic>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values: Vec<serde_json::Value> = Deserialize::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
+}
+
+#[derive(Debug, Deserialize, Serialize, Builder)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct CodeAction {
+    #[builder(into)]
+    title: String,
+    kind: CodeActionKind,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    #[builder(default)]
+    diagnostics: Vec<Diagnostic>,
+    is_preferred: bool,
+    edit: WorkspaceEdit,
+}
+
+impl CodeAction {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn kind(&self) -> &CodeActionKind {
+        &self.kind
+    }
+
+    pub fn edit(&self) -> &WorkspaceEdit {
+        &self.edit
+    }
+}
+
+pub type Result = Vec<CodeAction>;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum CodeActionKind {
+    #[serde(rename = "quickfix")]
+    QuickFix,
+    #[serde(rename = "refactor.extract")]
+    RefactorExtract,
+    #[serde(rename = "refactor.rewrite")]
+    RefactorRewrite,
+}
+
+impl CodeActionKind {
+    pub const ALL: [CodeActionKind; 3] =
+        [Self::QuickFix, Self::RefactorExtract, Self::RefactorRewrite];
+
+    pub fn value(&self) -> &'static str {
+        match self {
+            Self::QuickFix => "quickfix",
+            Self::RefactorExtract => "refactor.extract",
+            Self::RefactorRewrite => "refactor.rewrite",
+        }
+    }
+
+    /// Whether the kind was asked for, either by name or as a sub-kind of a
+    /// requested kind such as `refactor`
+    pub fn requested(&self, only: Option<&[String]>) -> bool {
+        let value = self.value();
+        only.is_none_or(|only| {
+            only.iter().any(|kind| {
+                value == kind || value.starts_with(kind) && value[kind.len()..].starts_with('.')
+            })
+        })
+    }
+}
+
+/// The edits which resolve a diagnostic, carried in its data so that code
+/// actions can offer them without checking the template again. The edits
+/// only apply to the version of the document they were made for
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Fix {
+    title: String,
+    version: usize,
+    edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    pub fn new(title: &str, version: usize, edits: Vec<TextEdit>) -> Self {
+        Self {
+            title: title.into(),
+            version,
+            edits,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    pub fn edits(&self) -> &[TextEdit] {
+        &self.edits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_diagnostics_from_other_sources() {
+        let json = r#"{
+            "textDocument": {"uri": "file:///cf.yaml"},
+            "range": {"start": {"line": 1, "character": 0}, "end": {"line": 1, "character": 0}},
+            "context": {
+                "diagnostics": [
+                    {
+                        "range": {"start": {"line": 1, "character": 2}, "end": {"line": 1, "character": 5}},
+                        "severity": 2,
+                        "code": "unused",
+                        "source": "cfn-lsp",
+                        "message": "Parameter 'Env' is never used",
+                        "tags": [1]
+                    },
+                    {
+                        "range": {"start": {"line": 1, "character": 2}, "end": {"line": 1, "character": 5}},
+                        "code": 42,
+                        "message": "From another server"
+                    }
+                ]
+            }
+        }"#;
+        let params: Params = serde_json::from_str(json).unwrap();
+        assert_eq!(params.diagnostics().len(), 1);
+        assert_eq!(params.only(), None)
+    }
+
+    #[test]
+    fn matches_requested_kinds() {
+        let kind = CodeActionKind::QuickFix;
+        assert!(kind.requested(None));
+        assert!(kind.requested(Some(&["quickfix".into()])));
+        assert!(!kind.requested(Some(&["quick".into()])));
+        assert!(!kind.requested(Some(&["refactor".into()])));
+        assert!(CodeActionKind::RefactorExtract.requested(Some(&["refactor".into()])))
+    }
+}
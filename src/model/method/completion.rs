@@ -0,0 +1,157 @@
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+
+use super::{hover::MarkupContent, text_document};
+
+pub type Params = text_document::PositionParams;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct List {
+    is_incomplete: bool,
+    items: Vec<Item>,
+}
+
+impl List {
+    pub fn new(items: Vec<Item>) -> Self {
+        Self {
+            is_incomplete: false,
+            items,
+        }
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Builder)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    #[builder(into)]
+    label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<ItemKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    documentation: Option<MarkupContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    sort_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    filter_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    insert_text_format: Option<InsertTextFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    insert_text_mode: Option<InsertTextMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text_edit: Option<text_document::TextEdit>,
+}
+
+impl Item {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn insert_text_format(&self) -> Option<&InsertTextFormat> {
+        self.insert_text_format.as_ref()
+    }
+
+    pub fn text_edit(&self) -> Option<&text_document::TextEdit> {
+        self.text_edit.as_ref()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum ItemKind {
+    Property,
+    Value,
+    Class,
+    Reference,
+    Variable,
+    Constant,
+}
+
+impl ItemKind {
+    fn value(&self) -> u8 {
+        match self {
+            Self::Property => 10,
+            Self::Value => 12,
+            Self::Class => 7,
+            Self::Reference => 18,
+            Self::Variable => 6,
+            Self::Constant => 21,
+        }
+    }
+}
+
+impl Serialize for ItemKind {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.value())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum InsertTextFormat {
+    PlainText,
+    Snippet,
+}
+
+impl InsertTextFormat {
+    fn value(&self) -> u8 {
+        match self {
+            Self::PlainText => 1,
+            Self::Snippet => 2,
+        }
+    }
+}
+
+impl Serialize for InsertTextFormat {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.value())
+    }
+}
+
+/// How the client treats whitespace in multi-line inserts
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum InsertTextMode {
+    /// Inserted as is, the server having indented the lines already
+    AsIs,
+    AdjustIndentation,
+}
+
+impl InsertTextMode {
+    fn value(&self) -> u8 {
+        match self {
+            Self::AsIs => 1,
+            Self::AdjustIndentation => 2,
+        }
+    }
+}
+
+impl Serialize for InsertTextMode {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.value())
+    }
+}
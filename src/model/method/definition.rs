@@ -0,0 +1,5 @@
+use super::{diagnostic::Location, text_document};
+
+pub type Params = text_document::PositionParams;
+
+pub type Result = Location;
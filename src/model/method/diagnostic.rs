@@ -1,10 +1,12 @@
 use bon::Builder;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de};
+
+use super::code_action::Fix;
 
 pub mod publish;
 pub mod pull;
 
-#[derive(Debug, Deserialize, Serialize, Builder)]
+#[derive(Debug, Deserialize, Serialize, Builder, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 #[serde(rename_all = "camelCase")]
 pub struct Diagnostic {
@@ -14,11 +16,49 @@ pub struct Diagnostic {
     code_description: Option<CodeDescription>,
     source: Option<String>,
     message: String,
+    #[serde(default)]
     tags: Vec<Tag>,
+    #[serde(default)]
     related_information: Vec<RelatedInformation>,
     data: Option<serde_json::Value>,
 }
 
+impl Diagnostic {
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+
+    pub fn severity(&self) -> &Severity {
+        &self.severity
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    pub fn related_information(&self) -> &[RelatedInformation] {
+        &self.related_information
+    }
+
+    /// Attaches the edits which resolve the diagnostic
+    pub fn with_fix(mut self, fix: &Fix) -> Self {
+        self.data = serde_json::to_value(fix).ok();
+        self
+    }
+
+    pub fn fix(&self) -> Option<Fix> {
+        serde_json::from_value(self.data.clone()?).ok()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct Position {
@@ -30,9 +70,17 @@ impl Position {
     pub fn new(line: usize, character: usize) -> Self {
         Self { line, character }
     }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn character(&self) -> usize {
+        self.character
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct Range {
     start: Position,
@@ -43,9 +91,17 @@ impl Range {
     pub fn new(start: Position, end: Position) -> Self {
         Self { start, end }
     }
+
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Position {
+        &self.end
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub enum Severity {
     Error,
@@ -74,7 +130,22 @@ impl Serialize for Severity {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u8::deserialize(deserializer)? {
+            1 => Ok(Self::Error),
+            2 => Ok(Self::Warning),
+            3 => Ok(Self::Information),
+            4 => Ok(Self::Hint),
+            value => Err(de::Error::custom(format!("Unknown severity '{value}'"))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct CodeDescription {
     href: String,
@@ -86,7 +157,7 @@ impl CodeDescription {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub enum Tag {
     Unnecessary,
@@ -111,7 +182,20 @@ impl Serialize for Tag {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u8::deserialize(deserializer)? {
+            1 => Ok(Self::Unnecessary),
+            2 => Ok(Self::Deprecated),
+            value => Err(de::Error::custom(format!("Unknown tag '{value}'"))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct RelatedInformation {
     location: Location,
@@ -125,9 +209,17 @@ impl RelatedInformation {
             message: message.into(),
         }
     }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct Location {
     uri: String,
@@ -141,4 +233,12 @@ impl Location {
             range,
         }
     }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
 }
@@ -1,16 +1,24 @@
 use serde::{Deserialize, Serialize};
 
+use super::diagnostic::Range;
+
 #[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct Params {
     #[serde(rename = "textDocument")]
     text_document: VersionedTextDocumentIdentifier,
+    #[serde(rename = "contentChanges")]
+    content_changes: Vec<TextDocumentContentChangeEvent>,
 }
 
 impl Params {
     pub fn text_document(&self) -> &VersionedTextDocumentIdentifier {
         &self.text_document
     }
+
+    pub fn content_changes(&self) -> &[TextDocumentContentChangeEvent] {
+        &self.content_changes
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -29,3 +37,22 @@ impl VersionedTextDocumentIdentifier {
         &self.uri
     }
 }
+
+/// A change to a document. Changes without a range replace the full content
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct TextDocumentContentChangeEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<Range>,
+    text: String,
+}
+
+impl TextDocumentContentChangeEvent {
+    pub fn range(&self) -> Option<&Range> {
+        self.range.as_ref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
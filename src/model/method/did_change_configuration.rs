@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    settings: serde_json::Value,
+}
+
+impl Params {
+    pub fn settings(&self) -> &serde_json::Value {
+        &self.settings
+    }
+}
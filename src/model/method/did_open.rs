@@ -28,7 +28,15 @@ impl TextDocumentItem {
         &self.uri
     }
 
+    pub fn language_id(&self) -> &str {
+        &self.language_id
+    }
+
     pub fn version(&self) -> usize {
         self.version
     }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
 }
@@ -0,0 +1,103 @@
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+
+use super::{diagnostic::Range, text_document::TextDocumentIdentifier};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    #[serde(rename = "textDocument")]
+    text_document: TextDocumentIdentifier,
+}
+
+impl Params {
+    pub fn uri(&self) -> &str {
+        self.text_document.uri()
+    }
+}
+
+/// A named part of a document, nested within the symbol containing it. The
+/// selection range is the part to reveal when the symbol is picked, e.g. a
+/// logical id
+#[derive(Debug, Deserialize, Serialize, Builder)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbol {
+    #[builder(into)]
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<DocumentSymbol>>,
+}
+
+impl DocumentSymbol {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn kind(&self) -> &SymbolKind {
+        &self.kind
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+
+    pub fn selection_range(&self) -> &Range {
+        &self.selection_range
+    }
+
+    pub fn children(&self) -> &[DocumentSymbol] {
+        self.children.as_deref().unwrap_or_default()
+    }
+}
+
+pub type Result = Vec<DocumentSymbol>;
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum SymbolKind {
+    Namespace,
+    Class,
+    Property,
+    Variable,
+    Constant,
+    Boolean,
+    Object,
+    Struct,
+    Event,
+}
+
+impl SymbolKind {
+    fn value(&self) -> u8 {
+        match self {
+            Self::Namespace => 3,
+            Self::Class => 5,
+            Self::Property => 7,
+            Self::Variable => 13,
+            Self::Constant => 14,
+            Self::Boolean => 17,
+            Self::Object => 19,
+            Self::Struct => 23,
+            Self::Event => 24,
+        }
+    }
+}
+
+impl Serialize for SymbolKind {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.value())
+    }
+}
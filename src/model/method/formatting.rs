@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use super::text_document::{TextDocumentIdentifier, TextEdit};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    #[serde(rename = "textDocument")]
+    text_document: TextDocumentIdentifier,
+    options: FormattingOptions,
+}
+
+impl Params {
+    pub fn uri(&self) -> &str {
+        self.text_document.uri()
+    }
+
+    pub fn options(&self) -> &FormattingOptions {
+        &self.options
+    }
+}
+
+/// The editor's preferences for the document. Templates are always indented
+/// with spaces, as YAML doesn't allow tabs
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct FormattingOptions {
+    tab_size: u32,
+    insert_spaces: bool,
+}
+
+impl FormattingOptions {
+    pub fn tab_size(&self) -> u32 {
+        self.tab_size
+    }
+}
+
+pub type Result = Vec<TextEdit>;
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use super::{diagnostic::Range, text_document};
+
+pub type Params = text_document::PositionParams;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Result {
+    contents: MarkupContent,
+    range: Option<Range>,
+}
+
+impl Result {
+    pub fn new(markdown: &str, range: Range) -> Self {
+        Self {
+            contents: MarkupContent::markdown(markdown),
+            range: Some(range),
+        }
+    }
+
+    pub fn contents(&self) -> &MarkupContent {
+        &self.contents
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct MarkupContent {
+    kind: MarkupKind,
+    value: String,
+}
+
+impl MarkupContent {
+    pub fn markdown(value: &str) -> Self {
+        Self {
+            kind: MarkupKind::Markdown,
+            value: value.into(),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "lowercase")]
+pub enum MarkupKind {
+    PlainText,
+    Markdown,
+}
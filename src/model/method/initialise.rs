@@ -1,7 +1,11 @@
-use std::fmt::Display;
+use std::{fmt::Display, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{settings::Settings, uri};
+
+use super::code_action::CodeActionKind;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct Params {
@@ -9,6 +13,14 @@ pub struct Params {
     process_id: Option<i32>,
     #[serde(rename = "clientInfo")]
     client_info: Option<ClientInfo>,
+    #[serde(default, skip_serializing)]
+    capabilities: ClientCapabilities,
+    #[serde(rename = "rootUri", default, skip_serializing)]
+    root_uri: Option<String>,
+    #[serde(rename = "workspaceFolders", default, skip_serializing)]
+    workspace_folders: Option<Vec<WorkspaceFolder>>,
+    #[serde(rename = "initializationOptions", default, skip_serializing)]
+    initialization_options: Option<serde_json::Value>,
 }
 
 impl Params {
@@ -19,6 +31,57 @@ impl Params {
     pub fn client_info(&self) -> Option<&ClientInfo> {
         self.client_info.as_ref()
     }
+
+    /// Local path of the workspace root, if the client opened a folder
+    pub fn root_path(&self) -> Option<PathBuf> {
+        self.root_uri.as_deref().and_then(uri::to_path)
+    }
+
+    /// Local paths of the workspace folders, or of the root for clients
+    /// which don't support multiple folders
+    pub fn workspace_folders(&self) -> Vec<PathBuf> {
+        match &self.workspace_folders {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|folder| uri::to_path(&folder.uri))
+                .collect(),
+            None => self.root_path().into_iter().collect(),
+        }
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.initialization_options
+            .as_ref()
+            .map(Settings::from_value)
+            .unwrap_or_default()
+    }
+
+    /// Whether completion items may insert snippets with tab stops
+    pub fn snippet_support(&self) -> bool {
+        self.capabilities
+            .text_document
+            .as_ref()
+            .and_then(|text_document| text_document.completion.as_ref())
+            .and_then(|completion| completion.completion_item.as_ref())
+            .and_then(|item| item.snippet_support)
+            .unwrap_or(false)
+    }
+
+    /// Picks the first encoding in the client's order of preference which the
+    /// server supports, falling back to the mandatory UTF-16
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.capabilities
+            .general
+            .as_ref()
+            .and_then(|general| general.position_encodings.as_ref())
+            .and_then(|encodings| {
+                encodings
+                    .iter()
+                    .find(|encoding| !matches!(encoding, PositionEncoding::Unsupported))
+                    .copied()
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -47,7 +110,7 @@ impl Default for ClientInfo {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 struct ClientCapabilities {
     #[serde(rename = "textDocument")]
@@ -59,6 +122,7 @@ struct ClientCapabilities {
 #[cfg_attr(test, derive(PartialEq, Eq))]
 struct TextDocumentClientCapabilities {
     diagnostic: Option<DiagnosticClientCapabilities>,
+    completion: Option<CompletionClientCapabilities>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -69,6 +133,20 @@ struct DiagnosticClientCapabilities {
     related_document_support: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+struct CompletionClientCapabilities {
+    #[serde(rename = "completionItem")]
+    completion_item: Option<CompletionItemClientCapabilities>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+struct CompletionItemClientCapabilities {
+    #[serde(rename = "snippetSupport")]
+    snippet_support: Option<bool>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 struct GeneralClientCapabilities {
@@ -76,21 +154,16 @@ struct GeneralClientCapabilities {
     position_encodings: Option<Vec<PositionEncoding>>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 #[serde(rename_all = "lowercase")]
 enum TraceValue {
+    #[default]
     Off,
     Messages,
     Verbose,
 }
 
-impl Default for TraceValue {
-    fn default() -> Self {
-        Self::Off
-    }
-}
-
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 struct WorkspaceFolder {
@@ -106,6 +179,32 @@ pub struct Result {
     server_info: ServerInfo,
 }
 
+impl Result {
+    pub fn new(position_encoding: PositionEncoding) -> Self {
+        Self {
+            capabilities: ServerCapabilities {
+                position_encoding,
+                hover_provider: true,
+                completion_provider: Some(CompletionOptions::default()),
+                definition_provider: true,
+                references_provider: true,
+                rename_provider: Some(RenameOptions {
+                    prepare_provider: true,
+                }),
+                document_symbol_provider: true,
+                workspace_symbol_provider: true,
+                code_action_provider: Some(CodeActionOptions {
+                    code_action_kinds: CodeActionKind::ALL.to_vec(),
+                }),
+                document_formatting_provider: true,
+                document_range_formatting_provider: true,
+                ..Default::default()
+            },
+            server_info: ServerInfo::default(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Default)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 #[serde(rename_all = "camelCase")]
@@ -113,23 +212,64 @@ struct ServerCapabilities {
     position_encoding: PositionEncoding,
     text_document_sync: TextDocumentSync,
     diagnostic_provider: DiagnosticOptions,
+    hover_provider: bool,
+    completion_provider: Option<CompletionOptions>,
+    definition_provider: bool,
+    references_provider: bool,
+    rename_provider: Option<RenameOptions>,
+    document_symbol_provider: bool,
+    workspace_symbol_provider: bool,
+    code_action_provider: Option<CodeActionOptions>,
+    document_formatting_provider: bool,
+    document_range_formatting_provider: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
-enum PositionEncoding {
-    #[serde(rename = "utf-8")]
-    Utf8,
-    #[serde(rename = "utf-16")]
-    Utf16,
+#[serde(rename_all = "camelCase")]
+struct CompletionOptions {
+    resolve_provider: bool,
+    trigger_characters: Vec<String>,
 }
 
-impl Default for PositionEncoding {
+impl Default for CompletionOptions {
     fn default() -> Self {
-        Self::Utf8
+        Self {
+            resolve_provider: false,
+            // Attributes follow the logical id in `!GetAtt Bucket.Arn`
+            trigger_characters: vec![".".into()],
+        }
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+struct RenameOptions {
+    prepare_provider: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+struct CodeActionOptions {
+    code_action_kinds: Vec<CodeActionKind>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum PositionEncoding {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[default]
+    #[serde(rename = "utf-16")]
+    Utf16,
+    #[serde(rename = "utf-32")]
+    Utf32,
+    #[serde(other, skip_serializing)]
+    Unsupported,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 struct TextDocumentSync {
@@ -149,11 +289,12 @@ impl Default for TextDocumentSync {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 enum TextDocumentSyncKind {
     None,
     Full,
+    #[default]
     Incremental,
 }
 
@@ -167,12 +308,6 @@ impl TextDocumentSyncKind {
     }
 }
 
-impl Default for TextDocumentSyncKind {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl Serialize for TextDocumentSyncKind {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -222,3 +357,105 @@ impl Default for ServerInfo {
 pub struct Error {
     retry: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(capabilities: serde_json::Value) -> Params {
+        serde_json::from_value(serde_json::json!({
+            "processId": null,
+            "capabilities": capabilities,
+        }))
+        .unwrap()
+    }
+
+    mod position_encoding_tests {
+        use super::*;
+
+        #[test]
+        fn defaults_to_utf16_without_client_preference() {
+            let actual = params(serde_json::json!({})).position_encoding();
+            assert_eq!(actual, PositionEncoding::Utf16)
+        }
+
+        #[test]
+        fn picks_first_supported_client_encoding() {
+            let actual = params(serde_json::json!({
+                "general": {"positionEncodings": ["utf-7", "utf-32", "utf-8"]}
+            }))
+            .position_encoding();
+            assert_eq!(actual, PositionEncoding::Utf32)
+        }
+
+        #[test]
+        fn defaults_to_utf16_if_no_client_encoding_supported() {
+            let actual = params(serde_json::json!({
+                "general": {"positionEncodings": ["utf-7"]}
+            }))
+            .position_encoding();
+            assert_eq!(actual, PositionEncoding::Utf16)
+        }
+    }
+
+    mod snippet_support_tests {
+        use super::*;
+
+        #[test]
+        fn reads_snippet_support() {
+            let actual = params(serde_json::json!({
+                "textDocument": {"completion": {"completionItem": {"snippetSupport": true}}}
+            }));
+            assert!(actual.snippet_support())
+        }
+
+        #[test]
+        fn defaults_to_no_snippet_support() {
+            assert!(!params(serde_json::json!({})).snippet_support())
+        }
+    }
+
+    mod settings_tests {
+        use std::path::Path;
+
+        use super::*;
+
+        #[test]
+        fn reads_initialisation_options() {
+            let params: Params = serde_json::from_value(serde_json::json!({
+                "processId": null,
+                "rootUri": "file:///workspace",
+                "initializationOptions": {"schemaDirectories": ["schemas"]},
+            }))
+            .unwrap();
+
+            let root = params.root_path();
+            assert_eq!(root.as_deref(), Some(Path::new("/workspace")));
+            assert_eq!(
+                params.settings().schema_directories(root.as_deref()),
+                vec![PathBuf::from("/workspace/schemas")]
+            )
+        }
+
+        #[test]
+        fn prefers_workspace_folders_to_root() {
+            let params: Params = serde_json::from_value(serde_json::json!({
+                "processId": null,
+                "rootUri": "file:///workspace",
+                "workspaceFolders": [
+                    {"uri": "file:///stacks/network", "name": "network"},
+                    {"uri": "file:///stacks/app", "name": "app"},
+                ],
+            }))
+            .unwrap();
+
+            assert_eq!(
+                params.workspace_folders(),
+                vec![
+                    PathBuf::from("/stacks/network"),
+                    PathBuf::from("/stacks/app")
+                ]
+            )
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use super::{diagnostic::Range, text_document::PositionParams};
+
+pub type Params = PositionParams;
+
+/// Range of the name which will be renamed, along with its current text
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Result {
+    range: Range,
+    placeholder: String,
+}
+
+impl Result {
+    pub fn new(range: Range, placeholder: &str) -> Self {
+        Self {
+            range,
+            placeholder: placeholder.into(),
+        }
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+}
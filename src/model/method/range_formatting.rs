@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    diagnostic::Range,
+    formatting::FormattingOptions,
+    text_document::{TextDocumentIdentifier, TextEdit},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    #[serde(rename = "textDocument")]
+    text_document: TextDocumentIdentifier,
+    range: Range,
+    options: FormattingOptions,
+}
+
+impl Params {
+    pub fn uri(&self) -> &str {
+        self.text_document.uri()
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+
+    pub fn options(&self) -> &FormattingOptions {
+        &self.options
+    }
+}
+
+pub type Result = Vec<TextEdit>;
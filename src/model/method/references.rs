@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    diagnostic::{Location, Position},
+    text_document::PositionParams,
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    #[serde(flatten)]
+    position: PositionParams,
+    context: Context,
+}
+
+impl Params {
+    pub fn uri(&self) -> &str {
+        self.position.uri()
+    }
+
+    pub fn position(&self) -> &Position {
+        self.position.position()
+    }
+
+    pub fn include_declaration(&self) -> bool {
+        self.context.include_declaration
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+struct Context {
+    #[serde(rename = "includeDeclaration")]
+    include_declaration: bool,
+}
+
+pub type Result = Vec<Location>;
@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    diagnostic::Position,
+    text_document::{PositionParams, TextEdit},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    #[serde(flatten)]
+    position: PositionParams,
+    #[serde(rename = "newName")]
+    new_name: String,
+}
+
+impl Params {
+    pub fn uri(&self) -> &str {
+        self.position.uri()
+    }
+
+    pub fn position(&self) -> &Position {
+        self.position.position()
+    }
+
+    pub fn new_name(&self) -> &str {
+        &self.new_name
+    }
+}
+
+/// Edits to apply across documents, keyed by document uri
+#[derive(Debug, Deserialize, Serialize, Default)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct WorkspaceEdit {
+    changes: BTreeMap<String, Vec<TextEdit>>,
+}
+
+impl WorkspaceEdit {
+    pub fn push(&mut self, uri: &str, edit: TextEdit) {
+        self.changes.entry(uri.into()).or_default().push(edit);
+    }
+
+    pub fn changes(&self) -> &BTreeMap<String, Vec<TextEdit>> {
+        &self.changes
+    }
+}
+
+pub type Result = WorkspaceEdit;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use super::diagnostic::{Position, Range};
+
+/// Identifies a document and a position within it, as sent by requests such
+/// as hover and completion
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct PositionParams {
+    #[serde(rename = "textDocument")]
+    text_document: TextDocumentIdentifier,
+    position: Position,
+}
+
+impl PositionParams {
+    pub fn uri(&self) -> &str {
+        &self.text_document.uri
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct TextDocumentIdentifier {
+    uri: String,
+}
+
+impl TextDocumentIdentifier {
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    range: Range,
+    new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range, new_text: &str) -> Self {
+        Self {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.range
+    }
+
+    pub fn new_text(&self) -> &str {
+        &self.new_text
+    }
+}
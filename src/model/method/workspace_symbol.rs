@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use super::{diagnostic::Location, document_symbol::SymbolKind};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Params {
+    query: String,
+}
+
+impl Params {
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+}
+
+/// A symbol found in any template of the workspace. The container names
+/// what the symbol belongs to, e.g. the section declaring it
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolInformation {
+    name: String,
+    kind: SymbolKind,
+    location: Location,
+    container_name: String,
+}
+
+impl SymbolInformation {
+    pub fn new(name: &str, kind: SymbolKind, location: Location, container_name: &str) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            location,
+            container_name: container_name.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &SymbolKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn container_name(&self) -> &str {
+        &self.container_name
+    }
+}
+
+pub type Result = Vec<SymbolInformation>;
@@ -0,0 +1,671 @@
+use std::{collections::HashMap, fmt::Display, path::Path};
+
+mod bundled;
+mod provider;
+
+const DOCUMENTATION_URL: &str = "https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    message: String,
+}
+
+impl SchemaError {
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Shape of the value a property accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    String {
+        allowed_values: Vec<String>,
+        pattern: Option<String>,
+        length: Bounds,
+    },
+    Integer(Bounds),
+    Number(Bounds),
+    Boolean,
+    List(Box<Shape>),
+    /// An object with named properties. A closed object rejects other keys,
+    /// an open one lets them through unchecked
+    Object {
+        properties: Vec<Property>,
+        closed: bool,
+    },
+    /// An object with arbitrary keys, all holding values of the same shape
+    Map(Box<Shape>),
+    /// Free-form JSON, e.g. a policy document
+    Json,
+    /// A named definition within the resource schema
+    Ref(String),
+    OneOf(Vec<Shape>),
+}
+
+/// Inclusive limits on a number, or on the length of a string
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bounds {
+    minimum: Option<serde_json::Number>,
+    maximum: Option<serde_json::Number>,
+}
+
+impl Bounds {
+    pub fn minimum(&self) -> Option<f64> {
+        self.minimum.as_ref().and_then(serde_json::Number::as_f64)
+    }
+
+    pub fn maximum(&self) -> Option<f64> {
+        self.maximum.as_ref().and_then(serde_json::Number::as_f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    name: String,
+    description: Option<String>,
+    shape: Shape,
+    required: bool,
+    create_only: bool,
+    deprecated: bool,
+}
+
+impl Property {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    /// Whether changing the property replaces the resource
+    pub fn create_only(&self) -> bool {
+        self.create_only
+    }
+
+    pub fn deprecated(&self) -> bool {
+        self.deprecated
+    }
+}
+
+/// A read-only value of a resource, available through `Fn::GetAtt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    name: String,
+    shape: Shape,
+}
+
+impl Attribute {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    description: Option<String>,
+    shape: Shape,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceSchema {
+    type_name: String,
+    description: Option<String>,
+    documentation_url: Option<String>,
+    properties: Vec<Property>,
+    closed: bool,
+    attributes: Vec<Attribute>,
+    definitions: HashMap<String, Definition>,
+}
+
+impl ResourceSchema {
+    /// Reads a resource provider schema, as published by the CloudFormation
+    /// registry
+    pub fn from_json(text: &str) -> Result<Self, SchemaError> {
+        provider::parse(text)
+    }
+
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Link to the documentation of the resource type, taken from the schema
+    /// or derived from the type name for AWS types
+    pub fn documentation_url(&self) -> Option<String> {
+        if let Some(url) = &self.documentation_url {
+            return Some(url.clone());
+        }
+        let mut parts = self.type_name.split("::");
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("AWS"), Some(service), Some(resource)) => Some(format!(
+                "{DOCUMENTATION_URL}/aws-resource-{}-{}.html",
+                service.to_lowercase(),
+                resource.to_lowercase()
+            )),
+            _ => None,
+        }
+    }
+
+    /// Link to the documentation of a top-level property
+    pub fn property_documentation_url(&self, property: &str) -> Option<String> {
+        let url = self.documentation_url()?;
+        if self.documentation_url.is_some() {
+            return Some(url);
+        }
+        let anchor = self.type_name.replace("::", "-").to_lowercase();
+        let anchor = anchor.trim_start_matches("aws-");
+        Some(format!("{url}#cfn-{anchor}-{}", property.to_lowercase()))
+    }
+
+    pub fn properties(&self) -> &[Property] {
+        &self.properties
+    }
+
+    /// Whether `Properties` rejects keys the schema doesn't declare
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Follows references to named definitions through to the shape they
+    /// stand for
+    pub fn resolve<'a>(&'a self, shape: &'a Shape) -> &'a Shape {
+        let mut shape = shape;
+        // Guards against definitions which refer to each other in a cycle
+        for _ in 0..self.definitions.len() + 1 {
+            match shape {
+                Shape::Ref(name) => match self.definitions.get(name) {
+                    Some(definition) => shape = &definition.shape,
+                    None => return &Shape::Json,
+                },
+                _ => return shape,
+            }
+        }
+        &Shape::Json
+    }
+
+    /// Properties accepted by an object shape, or by the items of a list of
+    /// objects
+    pub fn properties_of<'a>(&'a self, shape: &'a Shape) -> &'a [Property] {
+        match self.resolve(shape) {
+            Shape::Object { properties, .. } => properties,
+            Shape::List(item) => match self.resolve(item) {
+                Shape::Object { properties, .. } => properties,
+                _ => &[],
+            },
+            _ => &[],
+        }
+    }
+
+    /// Looks up a property by the chain of keys leading to it from
+    /// `Properties`, stepping through nested objects and lists of objects
+    pub fn property_at(&self, path: &[&str]) -> Option<&Property> {
+        let (first, rest) = path.split_first()?;
+        let mut property = self.property(first)?;
+        for name in rest {
+            property = self
+                .properties_of(&property.shape)
+                .iter()
+                .find(|property| property.name == *name)?;
+        }
+        Some(property)
+    }
+
+    /// Human readable name of a shape, e.g. `List of String`
+    pub fn describe(&self, shape: &Shape) -> String {
+        match shape {
+            Shape::String { .. } => "String".into(),
+            Shape::Integer(_) => "Integer".into(),
+            Shape::Number(_) => "Number".into(),
+            Shape::Boolean => "Boolean".into(),
+            Shape::List(item) => format!("List of {}", self.describe(item)),
+            Shape::Object { .. } => "Object".into(),
+            Shape::Map(value) => format!("Map of {}", self.describe(value)),
+            Shape::Json => "Json".into(),
+            Shape::Ref(name) => match self.resolve(shape) {
+                Shape::Object { .. } => name.clone(),
+                resolved => self.describe(resolved),
+            },
+            Shape::OneOf(shapes) => shapes
+                .iter()
+                .map(|shape| self.describe(shape))
+                .collect::<Vec<_>>()
+                .join(" | "),
+        }
+    }
+}
+
+/// Resource types known to the server, keyed by type name
+#[derive(Debug, Default)]
+pub struct SchemaRegistry {
+    resources: HashMap<String, ResourceSchema>,
+    bundled: bool,
+}
+
+impl SchemaRegistry {
+    /// Registry backed by the schemas embedded in the binary, which are only
+    /// decoded the first time a resource type is looked up
+    pub fn bundled() -> Self {
+        Self {
+            resources: HashMap::new(),
+            bundled: true,
+        }
+    }
+
+    /// Adds a resource type, replacing any existing one with the same name
+    pub fn insert(&mut self, schema: ResourceSchema) {
+        self.resources.insert(schema.type_name.clone(), schema);
+    }
+
+    /// Adds every resource provider schema (`*.json`) in a directory,
+    /// replacing existing types of the same name. Files which can't be read
+    /// are logged and skipped; returns the number of schemas added
+    pub fn load_directory(&mut self, directory: &Path) -> Result<usize, SchemaError> {
+        let entries = std::fs::read_dir(directory).map_err(|e| {
+            SchemaError::new(&format!(
+                "Failed to read schema directory '{}': {e}",
+                directory.display()
+            ))
+        })?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut count = 0;
+        for path in paths {
+            let schema = std::fs::read_to_string(&path)
+                .map_err(|e| SchemaError::new(&e.to_string()))
+                .and_then(|text| ResourceSchema::from_json(&text));
+            match schema {
+                Ok(schema) => {
+                    tracing::debug!(
+                        "Loaded schema for '{}' from '{}'",
+                        schema.type_name(),
+                        path.display()
+                    );
+                    self.insert(schema);
+                    count += 1;
+                }
+                Err(error) => {
+                    tracing::warn!("Skipping schema '{}': {error}", path.display());
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    pub fn get(&self, type_name: &str) -> Option<&ResourceSchema> {
+        self.resources
+            .get(type_name)
+            .or_else(|| self.bundled_schemas()?.get(type_name))
+    }
+
+    pub fn resource_types(&self) -> impl Iterator<Item = &ResourceSchema> {
+        let bundled = self
+            .bundled_schemas()
+            .into_iter()
+            .flat_map(HashMap::values)
+            .filter(|schema| !self.resources.contains_key(&schema.type_name));
+        self.resources.values().chain(bundled)
+    }
+
+    fn bundled_schemas(&self) -> Option<&'static HashMap<String, ResourceSchema>> {
+        self.bundled.then(bundled::schemas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET: &str = r##"{
+        "typeName": "AWS::S3::Bucket",
+        "description": "Creates a bucket",
+        "properties": {
+            "Arn": {"type": "string"},
+            "BucketName": {"type": "string", "description": "A name for the bucket"},
+            "AccessControl": {"type": "string", "enum": ["Private", "PublicRead"]},
+            "LoggingConfiguration": {"$ref": "#/definitions/LoggingConfiguration"},
+            "Tags": {"type": "array", "items": {"$ref": "#/definitions/Tag"}}
+        },
+        "definitions": {
+            "LoggingConfiguration": {
+                "type": "object",
+                "description": "Where logs are written",
+                "properties": {
+                    "DestinationBucketName": {"type": "string"},
+                    "LogFilePrefix": {"type": "string"}
+                }
+            },
+            "Tag": {
+                "type": "object",
+                "properties": {"Key": {"type": "string"}, "Value": {"type": "string"}},
+                "required": ["Key", "Value"],
+                "additionalProperties": false
+            }
+        },
+        "required": ["BucketName"],
+        "additionalProperties": false,
+        "readOnlyProperties": ["/properties/Arn"],
+        "createOnlyProperties": ["/properties/BucketName"]
+    }"##;
+
+    fn bucket() -> ResourceSchema {
+        ResourceSchema::from_json(BUCKET).unwrap()
+    }
+
+    mod schema_registry_tests {
+        use super::*;
+
+        #[test]
+        fn loads_bundled_schemas() {
+            let registry = SchemaRegistry::bundled();
+
+            let bucket = registry.get("AWS::S3::Bucket").unwrap();
+            assert!(bucket.property("BucketName").unwrap().create_only());
+            assert!(bucket.attribute("Arn").is_some());
+            assert!(registry.resource_types().count() > 1000)
+        }
+
+        #[test]
+        fn bundled_schemas_keep_constraints() {
+            let registry = SchemaRegistry::bundled();
+
+            let queue = registry.get("AWS::SQS::Queue").unwrap();
+            let Shape::Integer(bounds) = queue.property("VisibilityTimeout").unwrap().shape()
+            else {
+                panic!("VisibilityTimeout should be an integer")
+            };
+            assert_eq!(
+                (bounds.minimum(), bounds.maximum()),
+                (Some(0.0), Some(43200.0))
+            );
+            let bucket = registry.get("AWS::S3::Bucket").unwrap();
+            let Shape::String {
+                pattern, length, ..
+            } = bucket.property("BucketName").unwrap().shape()
+            else {
+                panic!("BucketName should be a string")
+            };
+            assert!(pattern.is_some());
+            assert_eq!(length.maximum(), Some(63.0))
+        }
+
+        #[test]
+        #[ignore = "the bundle was built from cfn-lint's copies, which have no descriptions"]
+        fn bundled_schemas_keep_descriptions() {
+            let registry = SchemaRegistry::bundled();
+
+            let bucket = registry.get("AWS::S3::Bucket").unwrap();
+            assert!(bucket.description().is_some());
+            assert!(
+                bucket
+                    .property("BucketName")
+                    .unwrap()
+                    .description()
+                    .is_some()
+            )
+        }
+
+        #[test]
+        fn bundled_schemas_keep_open_objects() {
+            let registry = SchemaRegistry::bundled();
+
+            let profile = registry.get("AWS::AppFlow::ConnectorProfile").unwrap();
+            let config = profile.property("ConnectorProfileConfig").unwrap();
+            assert!(matches!(
+                profile.resolve(config.shape()),
+                Shape::Object { closed: false, .. }
+            ));
+            assert!(profile.closed())
+        }
+
+        #[test]
+        fn prefers_inserted_schemas() {
+            let mut registry = SchemaRegistry::bundled();
+            registry.insert(bucket());
+
+            let actual = registry.get("AWS::S3::Bucket").unwrap();
+            assert_eq!(actual.description(), Some("Creates a bucket"));
+            assert_eq!(
+                registry
+                    .resource_types()
+                    .filter(|schema| schema.type_name() == "AWS::S3::Bucket")
+                    .count(),
+                1
+            )
+        }
+    }
+
+    mod load_directory_tests {
+        use std::path::PathBuf;
+
+        use super::*;
+
+        fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+            let directory =
+                std::env::temp_dir().join(format!("cfn-lsp-schemas-{name}-{}", std::process::id()));
+            std::fs::create_dir_all(&directory).unwrap();
+            for (file, contents) in files {
+                std::fs::write(directory.join(file), contents).unwrap();
+            }
+            directory
+        }
+
+        #[test]
+        fn overrides_bundled_schemas() {
+            let directory = directory("override", &[("aws-s3-bucket.json", BUCKET)]);
+            let mut registry = SchemaRegistry::bundled();
+
+            let count = registry.load_directory(&directory).unwrap();
+            std::fs::remove_dir_all(&directory).unwrap();
+
+            assert_eq!(count, 1);
+            assert_eq!(
+                registry.get("AWS::S3::Bucket").unwrap().description(),
+                Some("Creates a bucket")
+            )
+        }
+
+        #[test]
+        fn adds_private_types_and_skips_invalid_files() {
+            let directory = directory(
+                "private",
+                &[
+                    (
+                        "myorg-network-vpc.json",
+                        r#"{"typeName": "MyOrg::Network::Vpc"}"#,
+                    ),
+                    ("invalid.json", "{"),
+                    ("notes.txt", "not a schema"),
+                ],
+            );
+            let mut registry = SchemaRegistry::default();
+
+            let count = registry.load_directory(&directory).unwrap();
+            std::fs::remove_dir_all(&directory).unwrap();
+
+            assert_eq!(count, 1);
+            assert!(registry.get("MyOrg::Network::Vpc").is_some())
+        }
+
+        #[test]
+        fn errors_if_directory_missing() {
+            let mut registry = SchemaRegistry::default();
+            assert!(
+                registry
+                    .load_directory(Path::new("/does/not/exist"))
+                    .is_err()
+            )
+        }
+    }
+
+    mod resource_schema_tests {
+        use super::*;
+
+        #[test]
+        fn reads_properties() {
+            let schema = bucket();
+
+            let name = schema.property("BucketName").unwrap();
+            assert_eq!(name.description(), Some("A name for the bucket"));
+            assert!(name.required());
+            assert!(name.create_only());
+            assert_eq!(
+                schema.property("AccessControl").unwrap().shape(),
+                &Shape::String {
+                    allowed_values: vec!["Private".into(), "PublicRead".into()],
+                    pattern: None,
+                    length: Bounds::default(),
+                }
+            );
+            assert!(schema.property("Arn").is_none())
+        }
+
+        #[test]
+        fn reads_attributes() {
+            let schema = bucket();
+            assert_eq!(
+                schema.attribute("Arn").unwrap().shape(),
+                &Shape::String {
+                    allowed_values: Vec::new(),
+                    pattern: None,
+                    length: Bounds::default(),
+                }
+            )
+        }
+
+        #[test]
+        fn reads_constraints() {
+            let schema = ResourceSchema::from_json(
+                r#"{
+                    "typeName": "AWS::Lambda::Function",
+                    "properties": {
+                        "FunctionName": {"type": "string", "pattern": "^[a-z]+$", "maxLength": 64},
+                        "Timeout": {"type": "integer", "minimum": 1}
+                    }
+                }"#,
+            )
+            .unwrap();
+
+            let Shape::String {
+                pattern, length, ..
+            } = schema.property("FunctionName").unwrap().shape()
+            else {
+                panic!("FunctionName should be a string")
+            };
+            assert_eq!(pattern.as_deref(), Some("^[a-z]+$"));
+            assert_eq!((length.minimum(), length.maximum()), (None, Some(64.0)));
+            let Shape::Integer(bounds) = schema.property("Timeout").unwrap().shape() else {
+                panic!("Timeout should be an integer")
+            };
+            assert_eq!((bounds.minimum(), bounds.maximum()), (Some(1.0), None))
+        }
+
+        #[test]
+        fn takes_descriptions_from_definitions() {
+            let schema = bucket();
+            assert_eq!(
+                schema
+                    .property("LoggingConfiguration")
+                    .unwrap()
+                    .description(),
+                Some("Where logs are written")
+            )
+        }
+
+        #[test]
+        fn finds_nested_properties() {
+            let schema = bucket();
+
+            let key = schema.property_at(&["Tags", "Key"]).unwrap();
+            assert!(key.required());
+            assert!(
+                schema
+                    .property_at(&["LoggingConfiguration", "LogFilePrefix"])
+                    .is_some()
+            );
+            assert!(schema.property_at(&["Tags", "Nope"]).is_none())
+        }
+
+        #[test]
+        fn closes_objects_without_additional_properties() {
+            let schema = bucket();
+            let closed = |name| match schema.resolve(schema.property(name).unwrap().shape()) {
+                Shape::Object { closed, .. } => *closed,
+                Shape::List(item) => {
+                    matches!(schema.resolve(item), Shape::Object { closed: true, .. })
+                }
+                _ => panic!("{name} should be an object"),
+            };
+
+            assert!(schema.closed());
+            assert!(closed("Tags"));
+            assert!(!closed("LoggingConfiguration"))
+        }
+
+        #[test]
+        fn describes_shapes() {
+            let schema = bucket();
+
+            let tags = schema.property("Tags").unwrap();
+            assert_eq!(schema.describe(tags.shape()), "List of Tag")
+        }
+
+        #[test]
+        fn derives_documentation_urls() {
+            let schema = bucket();
+            assert_eq!(
+                schema.property_documentation_url("BucketName").unwrap(),
+                format!("{DOCUMENTATION_URL}/aws-resource-s3-bucket.html#cfn-s3-bucket-bucketname")
+            )
+        }
+
+        #[test]
+        fn errors_without_type_name() {
+            assert!(ResourceSchema::from_json(r#"{"properties": {}}"#).is_err())
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::{collections::HashMap, sync::LazyLock, time::Instant};
+
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use serde_json::Value;
+
+use super::{ResourceSchema, SchemaError, provider};
+
+/// Resource provider schemas for every public resource type, trimmed and
+/// compressed by `scripts/bundle-schemas.py`
+static BUNDLE: &[u8] = include_bytes!("../../data/resource-schemas.json.zlib");
+
+static SCHEMAS: LazyLock<HashMap<String, ResourceSchema>> = LazyLock::new(load);
+
+/// The bundled schemas, decoded on first use
+pub fn schemas() -> &'static HashMap<String, ResourceSchema> {
+    &SCHEMAS
+}
+
+fn load() -> HashMap<String, ResourceSchema> {
+    let start = Instant::now();
+    match decode() {
+        Ok(schemas) => {
+            tracing::info!(
+                "Loaded {} bundled resource schemas in {:?}",
+                schemas.len(),
+                start.elapsed()
+            );
+            schemas
+        }
+        Err(error) => {
+            tracing::error!("Failed to load bundled resource schemas: {error}");
+            HashMap::new()
+        }
+    }
+}
+
+fn decode() -> Result<HashMap<String, ResourceSchema>, SchemaError> {
+    let bytes = decompress_to_vec_zlib(BUNDLE).map_err(|e| {
+        SchemaError::new(&format!("Bundle could not be decompressed: {:?}", e.status))
+    })?;
+    let values: Vec<Value> = serde_json::from_slice(&bytes)
+        .map_err(|e| SchemaError::new(&format!("Bundle is not valid JSON: {e}")))?;
+    values
+        .iter()
+        .map(|value| {
+            let schema = provider::convert(value)?;
+            Ok((schema.type_name.clone(), schema))
+        })
+        .collect()
+}
@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Map, Value};
+
+use super::{Attribute, Bounds, Definition, Property, ResourceSchema, SchemaError, Shape};
+
+const DEFINITION_PREFIX: &str = "#/definitions/";
+const PROPERTY_PREFIX: &str = "/properties/";
+
+/// Converts a resource provider schema into the registry's typed form
+pub fn parse(text: &str) -> Result<ResourceSchema, SchemaError> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| SchemaError::new(&format!("Schema is not valid JSON: {e}")))?;
+    convert(&value)
+}
+
+pub fn convert(value: &Value) -> Result<ResourceSchema, SchemaError> {
+    let type_name = value
+        .get("typeName")
+        .and_then(Value::as_str)
+        .ok_or_else(|| SchemaError::new("Schema has no 'typeName'"))?;
+
+    let definitions: HashMap<String, Definition> = object(value, "definitions")
+        .map(|(name, definition)| {
+            let definition = Definition {
+                description: description(definition),
+                shape: shape(definition),
+            };
+            (name.clone(), definition)
+        })
+        .collect();
+
+    let read_only = paths(value, "readOnlyProperties");
+    let create_only: HashSet<_> = paths(value, "createOnlyProperties").into_iter().collect();
+    let deprecated: HashSet<_> = paths(value, "deprecatedProperties").into_iter().collect();
+    let required = names(value, "required");
+
+    let properties = object(value, "properties")
+        .filter(|(name, _)| !read_only.contains(&name.to_string()))
+        .map(|(name, schema)| {
+            let mut property = property(name, schema, &required, &definitions);
+            property.create_only = create_only.contains(name);
+            property.deprecated = deprecated.contains(name);
+            property
+        })
+        .collect();
+
+    let mut schema = ResourceSchema {
+        type_name: type_name.into(),
+        description: description(value),
+        documentation_url: value
+            .get("documentationUrl")
+            .and_then(Value::as_str)
+            .map(String::from),
+        properties,
+        closed: closed(value),
+        attributes: Vec::new(),
+        definitions,
+    };
+    schema.attributes = attributes(value, &read_only, &schema);
+    Ok(schema)
+}
+
+/// Read-only properties become attributes, with nested paths joined by dots
+/// as `Fn::GetAtt` expects
+fn attributes(value: &Value, read_only: &[String], schema: &ResourceSchema) -> Vec<Attribute> {
+    let properties = value.get("properties").and_then(Value::as_object);
+    read_only
+        .iter()
+        .map(|path| {
+            let mut segments = path.split('/');
+            let top = segments.next().unwrap_or_default();
+            let mut shape = properties
+                .and_then(|properties| properties.get(top))
+                .map(self::shape)
+                .unwrap_or(Shape::Json);
+            for segment in segments {
+                shape = schema
+                    .properties_of(&shape)
+                    .iter()
+                    .find(|property| property.name == segment)
+                    .map(|property| property.shape.clone())
+                    .unwrap_or(Shape::Json);
+            }
+            Attribute {
+                name: path.replace('/', "."),
+                shape,
+            }
+        })
+        .collect()
+}
+
+fn property(
+    name: &str,
+    schema: &Value,
+    required: &[String],
+    definitions: &HashMap<String, Definition>,
+) -> Property {
+    let shape = shape(schema);
+    let description = description(schema).or_else(|| match &shape {
+        Shape::Ref(name) => definitions
+            .get(name)
+            .and_then(|definition| definition.description.clone()),
+        _ => None,
+    });
+    Property {
+        name: name.into(),
+        description,
+        shape,
+        required: required.iter().any(|required| required == name),
+        create_only: false,
+        deprecated: false,
+    }
+}
+
+fn shape(schema: &Value) -> Shape {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return match reference.strip_prefix(DEFINITION_PREFIX) {
+            Some(name) => Shape::Ref(name.into()),
+            None => Shape::Json,
+        };
+    }
+    match schema.get("type") {
+        Some(Value::String(name)) => typed_shape(name, schema),
+        Some(Value::Array(names)) => {
+            let shapes: Vec<_> = names
+                .iter()
+                .filter_map(Value::as_str)
+                .filter(|name| *name != "null")
+                .map(|name| typed_shape(name, schema))
+                .collect();
+            one_of(shapes)
+        }
+        _ => {
+            let alternatives = ["oneOf", "anyOf"]
+                .iter()
+                .filter_map(|key| schema.get(key).and_then(Value::as_array))
+                .flatten()
+                .map(shape);
+            if schema.get("properties").is_some() {
+                typed_shape("object", schema)
+            } else {
+                one_of(alternatives.collect())
+            }
+        }
+    }
+}
+
+fn typed_shape(name: &str, schema: &Value) -> Shape {
+    match name {
+        "string" => Shape::String {
+            allowed_values: schema
+                .get("enum")
+                .and_then(Value::as_array)
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            pattern: schema
+                .get("pattern")
+                .and_then(Value::as_str)
+                .map(String::from),
+            length: bounds(schema, "minLength", "maxLength"),
+        },
+        "integer" => Shape::Integer(bounds(schema, "minimum", "maximum")),
+        "number" => Shape::Number(bounds(schema, "minimum", "maximum")),
+        "boolean" => Shape::Boolean,
+        "array" => Shape::List(Box::new(
+            schema.get("items").map(shape).unwrap_or(Shape::Json),
+        )),
+        "object" => object_shape(schema),
+        _ => Shape::Json,
+    }
+}
+
+fn bounds(schema: &Value, minimum: &str, maximum: &str) -> Bounds {
+    let number = |key| match schema.get(key) {
+        Some(Value::Number(number)) => Some(number.clone()),
+        _ => None,
+    };
+    Bounds {
+        minimum: number(minimum),
+        maximum: number(maximum),
+    }
+}
+
+fn object_shape(schema: &Value) -> Shape {
+    let required = names(schema, "required");
+    let properties: Vec<_> = object(schema, "properties")
+        .map(|(name, property)| self::property(name, property, &required, &HashMap::new()))
+        .collect();
+    if !properties.is_empty() {
+        return Shape::Object {
+            properties,
+            closed: closed(schema),
+        };
+    }
+    let values = object(schema, "patternProperties")
+        .map(|(_, value)| value)
+        .next()
+        .or_else(|| schema.get("additionalProperties").filter(|v| v.is_object()));
+    match values {
+        Some(value) => Shape::Map(Box::new(shape(value))),
+        None => Shape::Json,
+    }
+}
+
+/// JSON Schema allows keys beyond the declared properties unless
+/// `additionalProperties` is false
+fn closed(schema: &Value) -> bool {
+    schema.get("additionalProperties") == Some(&Value::Bool(false))
+}
+
+fn one_of(mut shapes: Vec<Shape>) -> Shape {
+    shapes.dedup();
+    match shapes.len() {
+        0 => Shape::Json,
+        1 => shapes.remove(0),
+        _ => Shape::OneOf(shapes),
+    }
+}
+
+fn object<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    value
+        .get(key)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(Map::iter)
+}
+
+fn names(value: &Value, key: &str) -> Vec<String> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(String::from)
+        .collect()
+}
+
+/// Property paths such as `/properties/Arn`, without the prefix
+fn paths(value: &Value, key: &str) -> Vec<String> {
+    names(value, key)
+        .iter()
+        .filter_map(|path| path.strip_prefix(PROPERTY_PREFIX))
+        .map(String::from)
+        .collect()
+}
+
+fn description(value: &Value) -> Option<String> {
+    value
+        .get("description")
+        .and_then(Value::as_str)
+        .map(String::from)
+}
@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::method::print::Options;
+
+/// Key of the client configuration section which holds the server's settings
+const SECTION: &str = "cfn-lsp";
+
+/// User settings, supplied as initialisation options or through
+/// `workspace/didChangeConfiguration`
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    schema_directories: Vec<PathBuf>,
+    formatting: Formatting,
+    /// Regions the templates are deployed to, which mappings extracted from
+    /// literals have a key for
+    regions: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            schema_directories: Vec::new(),
+            formatting: Formatting::default(),
+            regions: vec!["us-east-1".into()],
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings from the server's section of the configuration, or
+    /// from the top level if there is no such section. Invalid settings are
+    /// logged and replaced by the defaults
+    pub fn from_value(value: &Value) -> Self {
+        let value = value.get(SECTION).unwrap_or(value);
+        if value.is_null() {
+            return Self::default();
+        }
+        Self::deserialize(value).unwrap_or_else(|error| {
+            tracing::warn!("Ignoring invalid settings: {error}");
+            Self::default()
+        })
+    }
+
+    /// Directories of resource provider schemas, with relative paths resolved
+    /// against the workspace root
+    pub fn schema_directories(&self, root: Option<&Path>) -> Vec<PathBuf> {
+        self.schema_directories
+            .iter()
+            .map(|directory| match root {
+                Some(root) if directory.is_relative() => root.join(directory),
+                _ => directory.clone(),
+            })
+            .collect()
+    }
+
+    pub fn formatting(&self) -> &Formatting {
+        &self.formatting
+    }
+
+    pub fn regions(&self) -> &[String] {
+        &self.regions
+    }
+}
+
+/// How templates are laid out when formatted
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[serde(rename_all = "camelCase", default)]
+pub struct Formatting {
+    /// Spaces for each level of nesting, if not the editor's tab size
+    indent: Option<usize>,
+    sort_sections: bool,
+    short_form: bool,
+}
+
+impl Default for Formatting {
+    fn default() -> Self {
+        Self {
+            indent: None,
+            sort_sections: true,
+            short_form: false,
+        }
+    }
+}
+
+impl Formatting {
+    pub fn options(&self, tab_size: usize) -> Options {
+        Options::builder()
+            .indent(self.indent.unwrap_or(tab_size).max(1))
+            .sort_sections(self.sort_sections)
+            .short_form(self.short_form)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reads_server_section() {
+        let actual = Settings::from_value(&json!({"cfn-lsp": {"schemaDirectories": ["schemas"]}}));
+        assert_eq!(
+            actual.schema_directories(None),
+            vec![PathBuf::from("schemas")]
+        )
+    }
+
+    #[test]
+    fn reads_top_level_settings() {
+        let actual = Settings::from_value(&json!({"schemaDirectories": ["/opt/schemas"]}));
+        assert_eq!(
+            actual.schema_directories(None),
+            vec![PathBuf::from("/opt/schemas")]
+        )
+    }
+
+    #[test]
+    fn resolves_relative_directories_against_root() {
+        let settings =
+            Settings::from_value(&json!({"schemaDirectories": ["schemas", "/opt/schemas"]}));
+        assert_eq!(
+            settings.schema_directories(Some(Path::new("/workspace"))),
+            vec![
+                PathBuf::from("/workspace/schemas"),
+                PathBuf::from("/opt/schemas")
+            ]
+        )
+    }
+
+    #[test]
+    fn reads_formatting_settings() {
+        let settings = Settings::from_value(&json!({"formatting": {"shortForm": true}}));
+        let expected = Formatting {
+            indent: None,
+            sort_sections: true,
+            short_form: true,
+        };
+        assert_eq!(settings.formatting(), &expected)
+    }
+
+    #[test]
+    fn reads_regions() {
+        let settings = Settings::from_value(&json!({"regions": ["eu-west-1", "eu-central-1"]}));
+        assert_eq!(settings.regions(), ["eu-west-1", "eu-central-1"]);
+        assert_eq!(Settings::default().regions(), ["us-east-1"])
+    }
+
+    #[test]
+    fn defaults_invalid_settings() {
+        let actual = Settings::from_value(&json!({"schemaDirectories": "schemas"}));
+        assert_eq!(actual, Settings::default())
+    }
+}
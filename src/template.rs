@@ -0,0 +1,907 @@
+use std::fmt::Display;
+
+pub use node::{Entry, Function, Key, Node, Segment, Value};
+pub use reference::{Reference, ReferenceKind};
+
+mod json;
+mod node;
+mod reference;
+mod yaml;
+
+/// Byte range into the source text of a template
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Span from the start of this span to the end of another
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.start, other.end.max(self.start))
+    }
+
+    /// Whether an offset lies within the span, counting its end as inside so
+    /// that a cursor just after a token still refers to it
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Yaml,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    span: Span,
+}
+
+impl ParseError {
+    pub fn new(message: &str, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Intrinsic functions supported by CloudFormation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Intrinsic {
+    And,
+    Base64,
+    Cidr,
+    Condition,
+    Equals,
+    FindInMap,
+    GetAZs,
+    GetAtt,
+    If,
+    ImportValue,
+    Join,
+    Length,
+    Not,
+    Or,
+    Ref,
+    Select,
+    Split,
+    Sub,
+    ToJsonString,
+    Transform,
+}
+
+impl Intrinsic {
+    pub const ALL: [Intrinsic; 20] = [
+        Self::And,
+        Self::Base64,
+        Self::Cidr,
+        Self::Condition,
+        Self::Equals,
+        Self::FindInMap,
+        Self::GetAZs,
+        Self::GetAtt,
+        Self::If,
+        Self::ImportValue,
+        Self::Join,
+        Self::Length,
+        Self::Not,
+        Self::Or,
+        Self::Ref,
+        Self::Select,
+        Self::Split,
+        Self::Sub,
+        Self::ToJsonString,
+        Self::Transform,
+    ];
+
+    /// Name used in the long form, e.g. `Fn::GetAtt`
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::And => "Fn::And",
+            Self::Base64 => "Fn::Base64",
+            Self::Cidr => "Fn::Cidr",
+            Self::Condition => "Condition",
+            Self::Equals => "Fn::Equals",
+            Self::FindInMap => "Fn::FindInMap",
+            Self::GetAZs => "Fn::GetAZs",
+            Self::GetAtt => "Fn::GetAtt",
+            Self::If => "Fn::If",
+            Self::ImportValue => "Fn::ImportValue",
+            Self::Join => "Fn::Join",
+            Self::Length => "Fn::Length",
+            Self::Not => "Fn::Not",
+            Self::Or => "Fn::Or",
+            Self::Ref => "Ref",
+            Self::Select => "Fn::Select",
+            Self::Split => "Fn::Split",
+            Self::Sub => "Fn::Sub",
+            Self::ToJsonString => "Fn::ToJsonString",
+            Self::Transform => "Fn::Transform",
+        }
+    }
+
+    /// Name used in the short form YAML tag, e.g. `!GetAtt`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Self::And => "!And",
+            Self::Base64 => "!Base64",
+            Self::Cidr => "!Cidr",
+            Self::Condition => "!Condition",
+            Self::Equals => "!Equals",
+            Self::FindInMap => "!FindInMap",
+            Self::GetAZs => "!GetAZs",
+            Self::GetAtt => "!GetAtt",
+            Self::If => "!If",
+            Self::ImportValue => "!ImportValue",
+            Self::Join => "!Join",
+            Self::Length => "!Length",
+            Self::Not => "!Not",
+            Self::Or => "!Or",
+            Self::Ref => "!Ref",
+            Self::Select => "!Select",
+            Self::Split => "!Split",
+            Self::Sub => "!Sub",
+            Self::ToJsonString => "!ToJsonString",
+            Self::Transform => "!Transform",
+        }
+    }
+
+    /// One line summary of what the function does
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::And => "Returns `true` if all the specified conditions evaluate to true",
+            Self::Base64 => "Returns the Base64 representation of the input string",
+            Self::Cidr => "Returns an array of CIDR address blocks",
+            Self::Condition => "Refers to a condition declared in the `Conditions` section",
+            Self::Equals => "Compares if two values are equal",
+            Self::FindInMap => {
+                "Returns the value corresponding to keys in a two-level map declared in the `Mappings` section"
+            }
+            Self::GetAZs => "Returns an array that lists Availability Zones for a specified region",
+            Self::GetAtt => "Returns the value of an attribute from a resource in the template",
+            Self::If => {
+                "Returns one value if the specified condition evaluates to true and another value if it evaluates to false"
+            }
+            Self::ImportValue => "Returns the value of an output exported by another stack",
+            Self::Join => {
+                "Appends a set of values into a single value, separated by the specified delimiter"
+            }
+            Self::Length => "Returns the number of elements within an array",
+            Self::Not => {
+                "Returns `true` for a condition that evaluates to false, and `false` for one that evaluates to true"
+            }
+            Self::Or => "Returns `true` if any one of the specified conditions evaluates to true",
+            Self::Ref => "Returns the value of the specified parameter or resource",
+            Self::Select => "Returns a single object from a list of objects by index",
+            Self::Split => "Splits a string into a list of string values by a delimiter",
+            Self::Sub => "Substitutes variables in an input string with values that you specify",
+            Self::ToJsonString => "Converts an object or array to its corresponding JSON string",
+            Self::Transform => {
+                "Specifies a macro to perform custom processing on part of a stack template"
+            }
+        }
+    }
+
+    /// Example of the short form syntax
+    pub fn syntax(&self) -> &'static str {
+        match self {
+            Self::And => "!And [condition, ...]",
+            Self::Base64 => "!Base64 valueToEncode",
+            Self::Cidr => "!Cidr [ipBlock, count, cidrBits]",
+            Self::Condition => "Condition: conditionName",
+            Self::Equals => "!Equals [value_1, value_2]",
+            Self::FindInMap => "!FindInMap [MapName, TopLevelKey, SecondLevelKey]",
+            Self::GetAZs => "!GetAZs region",
+            Self::GetAtt => "!GetAtt logicalNameOfResource.attributeName",
+            Self::If => "!If [condition_name, value_if_true, value_if_false]",
+            Self::ImportValue => "!ImportValue sharedValueToImport",
+            Self::Join => "!Join [delimiter, [comma-delimited list of values]]",
+            Self::Length => "!Length [list]",
+            Self::Not => "!Not [condition]",
+            Self::Or => "!Or [condition, ...]",
+            Self::Ref => "!Ref logicalName",
+            Self::Select => "!Select [index, listOfObjects]",
+            Self::Split => "!Split [delimiter, source string]",
+            Self::Sub => "!Sub 'String with ${Variable}'",
+            Self::ToJsonString => "!ToJsonString {object}",
+            Self::Transform => "!Transform {Name: macro name, Parameters: {key: value}}",
+        }
+    }
+
+    pub fn documentation_url(&self) -> String {
+        let page = match self {
+            Self::And | Self::Condition | Self::Equals | Self::If | Self::Not | Self::Or => {
+                "conditions".into()
+            }
+            Self::GetAZs => "getavailabilityzones".into(),
+            Self::ToJsonString => "ToJsonString".into(),
+            _ => self.key().trim_start_matches("Fn::").to_lowercase(),
+        };
+        format!(
+            "https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/intrinsic-function-reference-{page}.html"
+        )
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|intrinsic| intrinsic.key() == key)
+    }
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|intrinsic| intrinsic.tag() == tag)
+    }
+}
+
+/// Parameters predefined by CloudFormation, which `Ref` resolves to values
+/// of the stack being deployed
+pub const PSEUDO_PARAMETERS: [(&str, &str); 8] = [
+    (
+        "AWS::AccountId",
+        "The ID of the AWS account in which the stack is being created",
+    ),
+    (
+        "AWS::NotificationARNs",
+        "The list of Amazon SNS topic ARNs which receive notifications for the stack",
+    ),
+    (
+        "AWS::NoValue",
+        "Removes the property it is assigned to, typically as a branch of `Fn::If`",
+    ),
+    (
+        "AWS::Partition",
+        "The partition the resource is in, e.g. `aws`, `aws-cn` or `aws-us-gov`",
+    ),
+    (
+        "AWS::Region",
+        "The Region in which the resource is being created",
+    ),
+    ("AWS::StackId", "The ID of the stack"),
+    ("AWS::StackName", "The name of the stack"),
+    (
+        "AWS::URLSuffix",
+        "The suffix for domains in the partition, usually `amazonaws.com`",
+    ),
+];
+
+/// Sections whose entries can be referred to from elsewhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeclarationKind {
+    Parameter,
+    Resource,
+    Condition,
+    Mapping,
+    Output,
+}
+
+impl DeclarationKind {
+    pub const ALL: [DeclarationKind; 5] = [
+        Self::Parameter,
+        Self::Resource,
+        Self::Condition,
+        Self::Mapping,
+        Self::Output,
+    ];
+
+    /// Key of the top-level section, e.g. `Resources`
+    pub fn section(&self) -> &'static str {
+        match self {
+            Self::Parameter => "Parameters",
+            Self::Resource => "Resources",
+            Self::Condition => "Conditions",
+            Self::Mapping => "Mappings",
+            Self::Output => "Outputs",
+        }
+    }
+}
+
+/// A named entry within a top-level section, e.g. a single resource
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Declaration {
+    logical_id: Key,
+    body: Node,
+}
+
+impl Declaration {
+    fn new(entry: &Entry) -> Self {
+        Self {
+            logical_id: entry.key().clone(),
+            body: entry.value().clone(),
+        }
+    }
+
+    pub fn logical_id(&self) -> &Key {
+        &self.logical_id
+    }
+
+    pub fn body(&self) -> &Node {
+        &self.body
+    }
+
+    pub fn span(&self) -> Span {
+        self.logical_id.span().to(self.body.span())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.body.get(key)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Parameter(Declaration);
+
+impl Parameter {
+    pub fn parameter_type(&self) -> Option<&Node> {
+        self.0.get("Type")
+    }
+
+    pub fn default(&self) -> Option<&Node> {
+        self.0.get("Default")
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Mapping(Declaration);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Condition(Declaration);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Rule(Declaration);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Resource(Declaration);
+
+impl Resource {
+    pub fn resource_type(&self) -> Option<&Node> {
+        self.0.get("Type")
+    }
+
+    pub fn properties(&self) -> Option<&Node> {
+        self.0.get("Properties")
+    }
+
+    pub fn condition(&self) -> Option<&Node> {
+        self.0.get("Condition")
+    }
+
+    /// Logical ids listed under `DependsOn`, which may be a single name or a
+    /// list of names
+    pub fn depends_on(&self) -> Vec<&Node> {
+        match self.0.get("DependsOn") {
+            Some(node) => match node.as_sequence() {
+                Some(items) => items.iter().collect(),
+                None => vec![node],
+            },
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Output(Declaration);
+
+impl Output {
+    pub fn value(&self) -> Option<&Node> {
+        self.0.get("Value")
+    }
+
+    pub fn condition(&self) -> Option<&Node> {
+        self.0.get("Condition")
+    }
+
+    pub fn export_name(&self) -> Option<&Node> {
+        self.0.get("Export")?.get("Name")
+    }
+}
+
+macro_rules! declaration {
+    ($($kind:ty),*) => {
+        $(
+            impl std::ops::Deref for $kind {
+                type Target = Declaration;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+declaration!(Parameter, Mapping, Condition, Rule, Resource, Output);
+
+/// A top-level section of a template, e.g. `Resources`
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Section<T> {
+    key: Key,
+    span: Span,
+    items: Vec<T>,
+}
+
+impl<T> Section<T>
+where
+    T: std::ops::Deref<Target = Declaration>,
+{
+    fn new(entry: &Entry, item: fn(Declaration) -> T) -> Self {
+        let items = entry
+            .value()
+            .as_mapping()
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| item(Declaration::new(entry)))
+            .collect();
+        Self {
+            key: entry.key().clone(),
+            span: entry.span(),
+            items,
+        }
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn get(&self, logical_id: &str) -> Option<&T> {
+        self.items
+            .iter()
+            .find(|item| item.logical_id().name() == logical_id)
+    }
+}
+
+/// A YAML comment, kept so that rewriting a template can preserve it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    text: String,
+    span: Span,
+}
+
+impl Comment {
+    pub fn new(text: &str, span: Span) -> Self {
+        Self {
+            text: text.into(),
+            span,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Template {
+    format: Format,
+    root: Node,
+    comments: Vec<Comment>,
+    anchors: Vec<Span>,
+    errors: Vec<ParseError>,
+    format_version: Option<Node>,
+    description: Option<Node>,
+    transform: Option<Node>,
+    metadata: Option<Node>,
+    parameters: Option<Section<Parameter>>,
+    mappings: Option<Section<Mapping>>,
+    conditions: Option<Section<Condition>>,
+    rules: Option<Section<Rule>>,
+    resources: Option<Section<Resource>>,
+    outputs: Option<Section<Output>>,
+    references: Vec<Reference>,
+}
+
+impl Template {
+    fn new(
+        format: Format,
+        text: &str,
+        root: Node,
+        comments: Vec<Comment>,
+        anchors: Vec<Span>,
+        errors: Vec<ParseError>,
+    ) -> Self {
+        let mut template = Self {
+            format,
+            root: Node::new(Value::Null, Span::default()),
+            comments,
+            anchors,
+            errors,
+            format_version: None,
+            description: None,
+            transform: None,
+            metadata: None,
+            parameters: None,
+            mappings: None,
+            conditions: None,
+            rules: None,
+            resources: None,
+            outputs: None,
+            references: Vec::new(),
+        };
+        for entry in root.as_mapping().unwrap_or_default() {
+            let value = Some(entry.value().clone());
+            match entry.key().name() {
+                "AWSTemplateFormatVersion" => template.format_version = value,
+                "Description" => template.description = value,
+                "Transform" => template.transform = value,
+                "Metadata" => template.metadata = value,
+                "Parameters" => template.parameters = Some(Section::new(entry, Parameter)),
+                "Mappings" => template.mappings = Some(Section::new(entry, Mapping)),
+                "Conditions" => template.conditions = Some(Section::new(entry, Condition)),
+                "Rules" => template.rules = Some(Section::new(entry, Rule)),
+                "Resources" => template.resources = Some(Section::new(entry, Resource)),
+                "Outputs" => template.outputs = Some(Section::new(entry, Output)),
+                _ => {}
+            }
+        }
+        template.references = reference::collect(
+            &root,
+            template.resources.as_ref(),
+            template.outputs.as_ref(),
+            text,
+        );
+        template.root = root;
+        template
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
+    /// Spans of the YAML anchors and aliases in the template, whose nodes
+    /// are copied into each alias when parsed
+    pub fn anchors(&self) -> &[Span] {
+        &self.anchors
+    }
+
+    /// Syntax errors the template was recovered from, in the order they were
+    /// found
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// Name of the top-level section an offset falls in, which runs up to the
+    /// key of the next section
+    pub fn section_at(&self, offset: usize) -> Option<&str> {
+        self.root
+            .as_mapping()?
+            .iter()
+            .take_while(|entry| entry.key().span().start() <= offset)
+            .last()
+            .map(|entry| entry.key().name())
+    }
+
+    pub fn format_version(&self) -> Option<&Node> {
+        self.format_version.as_ref()
+    }
+
+    pub fn description(&self) -> Option<&Node> {
+        self.description.as_ref()
+    }
+
+    pub fn transform(&self) -> Option<&Node> {
+        self.transform.as_ref()
+    }
+
+    pub fn metadata(&self) -> Option<&Node> {
+        self.metadata.as_ref()
+    }
+
+    pub fn parameters(&self) -> Option<&Section<Parameter>> {
+        self.parameters.as_ref()
+    }
+
+    pub fn mappings(&self) -> Option<&Section<Mapping>> {
+        self.mappings.as_ref()
+    }
+
+    pub fn conditions(&self) -> Option<&Section<Condition>> {
+        self.conditions.as_ref()
+    }
+
+    pub fn rules(&self) -> Option<&Section<Rule>> {
+        self.rules.as_ref()
+    }
+
+    pub fn resources(&self) -> Option<&Section<Resource>> {
+        self.resources.as_ref()
+    }
+
+    pub fn outputs(&self) -> Option<&Section<Output>> {
+        self.outputs.as_ref()
+    }
+
+    /// Uses of parameters, resources, conditions and mappings, in the order
+    /// they appear
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// The declaration a reference refers to. Names are unique across
+    /// parameters and resources, so `Ref` can look in both
+    pub fn declaration(&self, reference: &Reference) -> Option<(DeclarationKind, &Declaration)> {
+        let name = reference.name();
+        match reference.kind() {
+            ReferenceKind::Ref => self
+                .find(DeclarationKind::Parameter, name)
+                .or_else(|| self.find(DeclarationKind::Resource, name)),
+            ReferenceKind::Resource => self.find(DeclarationKind::Resource, name),
+            ReferenceKind::Condition => self.find(DeclarationKind::Condition, name),
+            ReferenceKind::Mapping => self.find(DeclarationKind::Mapping, name),
+            ReferenceKind::Import => self.outputs()?.items().iter().find_map(|output| {
+                let export = output.export_name()?.as_str()?;
+                (export == name).then_some((DeclarationKind::Output, &**output))
+            }),
+        }
+    }
+
+    /// The declaration whose logical id is under an offset
+    pub fn declaration_at(&self, offset: usize) -> Option<(DeclarationKind, &Declaration)> {
+        DeclarationKind::ALL.into_iter().find_map(|kind| {
+            self.declarations(kind)
+                .find(|declaration| declaration.logical_id().span().contains(offset))
+                .map(|declaration| (kind, declaration))
+        })
+    }
+
+    /// References which refer to a declaration. Outputs are referred to by
+    /// `Fn::ImportValue` of their export name
+    pub fn references_to<'a>(
+        &'a self,
+        kind: DeclarationKind,
+        declaration: &'a Declaration,
+    ) -> impl Iterator<Item = &'a Reference> {
+        let export = match kind {
+            DeclarationKind::Output => declaration
+                .get("Export")
+                .and_then(|export| export.get("Name")?.as_str()),
+            _ => None,
+        };
+        let name = declaration.logical_id().name();
+        self.references
+            .iter()
+            .filter(move |reference| match (kind, reference.kind()) {
+                (DeclarationKind::Parameter, ReferenceKind::Ref)
+                | (DeclarationKind::Resource, ReferenceKind::Ref | ReferenceKind::Resource)
+                | (DeclarationKind::Condition, ReferenceKind::Condition)
+                | (DeclarationKind::Mapping, ReferenceKind::Mapping) => reference.name() == name,
+                (DeclarationKind::Output, ReferenceKind::Import) => {
+                    Some(reference.name()) == export
+                }
+                _ => false,
+            })
+    }
+
+    /// Every declaration within a section, in the order written
+    pub fn declarations(
+        &self,
+        kind: DeclarationKind,
+    ) -> Box<dyn Iterator<Item = &Declaration> + '_> {
+        fn items<T: std::ops::Deref<Target = Declaration>>(
+            section: Option<&Section<T>>,
+        ) -> Box<dyn Iterator<Item = &Declaration> + '_> {
+            Box::new(
+                section
+                    .map(Section::items)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|item| &**item),
+            )
+        }
+        match kind {
+            DeclarationKind::Parameter => items(self.parameters()),
+            DeclarationKind::Resource => items(self.resources()),
+            DeclarationKind::Condition => items(self.conditions()),
+            DeclarationKind::Mapping => items(self.mappings()),
+            DeclarationKind::Output => items(self.outputs()),
+        }
+    }
+
+    /// The declaration of a name within a section
+    pub fn find(
+        &self,
+        kind: DeclarationKind,
+        name: &str,
+    ) -> Option<(DeclarationKind, &Declaration)> {
+        self.declarations(kind)
+            .find(|declaration| declaration.logical_id().name() == name)
+            .map(|declaration| (kind, declaration))
+    }
+}
+
+/// Parses a template, treating it as JSON if it starts with an opening brace
+/// and as YAML otherwise. Syntax errors don't stop parsing: whatever could be
+/// recovered is returned along with the errors
+pub fn parse(text: &str) -> Template {
+    if text.trim_start().starts_with('{') {
+        let (root, errors) = json::parse(text);
+        Template::new(Format::Json, text, root, Vec::new(), Vec::new(), errors)
+    } else {
+        let (root, comments, anchors, errors) = yaml::parse(text);
+        Template::new(Format::Yaml, text, root, comments, anchors, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    DependsOn: Queue
+    Properties:
+      BucketName: !Ref Env
+  Queue:
+    Type: AWS::SQS::Queue
+Outputs:
+  Arn:
+    Value: !GetAtt Bucket.Arn
+    Export:
+      Name: bucket-arn
+";
+
+    #[test]
+    fn parses_yaml_sections() {
+        let template = parse(YAML);
+
+        assert_eq!(template.format(), Format::Yaml);
+        assert!(template.errors().is_empty());
+        let parameters = template.parameters().unwrap();
+        assert_eq!(parameters.key().span(), Span::new(0, 10));
+        assert_eq!(
+            parameters
+                .get("Env")
+                .unwrap()
+                .parameter_type()
+                .unwrap()
+                .as_str(),
+            Some("String")
+        );
+
+        let resources = template.resources().unwrap();
+        assert_eq!(resources.items().len(), 2);
+        let bucket = resources.get("Bucket").unwrap();
+        assert_eq!(bucket.logical_id().span(), Span::new(49, 55));
+        assert_eq!(
+            bucket.resource_type().unwrap().as_str(),
+            Some("AWS::S3::Bucket")
+        );
+        assert_eq!(bucket.depends_on()[0].as_str(), Some("Queue"));
+        assert_eq!(
+            bucket
+                .properties()
+                .unwrap()
+                .get("BucketName")
+                .unwrap()
+                .as_function()
+                .unwrap()
+                .intrinsic(),
+            Intrinsic::Ref
+        );
+
+        let output = template.outputs().unwrap().get("Arn").unwrap();
+        assert_eq!(output.export_name().unwrap().as_str(), Some("bucket-arn"));
+        assert!(template.conditions().is_none())
+    }
+
+    #[test]
+    fn parses_json_sections() {
+        let template = parse(r#"{"Resources": {"Queue": {"Type": "AWS::SQS::Queue"}}}"#);
+
+        assert_eq!(template.format(), Format::Json);
+        let queue = template.resources().unwrap().get("Queue").unwrap();
+        assert_eq!(queue.logical_id().span(), Span::new(15, 22))
+    }
+
+    #[test]
+    fn keeps_sections_of_broken_template() {
+        let template = parse("Resources:\n  Bucket:\n    Type: AWS::S3::Bucket\n    Properties\n");
+
+        assert_eq!(template.errors().len(), 1);
+        assert!(template.resources().unwrap().get("Bucket").is_some())
+    }
+
+    #[test]
+    fn finds_section_at_offset() {
+        let template = parse(YAML);
+
+        assert_eq!(template.section_at(5), Some("Parameters"));
+        assert_eq!(
+            template.section_at(YAML.find("!Ref").unwrap()),
+            Some("Resources")
+        );
+        assert_eq!(template.section_at(YAML.len()), Some("Outputs"))
+    }
+
+    #[test]
+    fn finds_references_to_declaration() {
+        let template = parse(YAML);
+
+        let (kind, queue) = template
+            .declaration_at(YAML.find("Queue:").unwrap())
+            .unwrap();
+        assert_eq!(kind, DeclarationKind::Resource);
+        let starts: Vec<_> = template
+            .references_to(kind, queue)
+            .map(|reference| reference.span().start())
+            .collect();
+        assert_eq!(starts, vec![YAML.find("Queue").unwrap()]);
+        assert!(
+            template
+                .declaration_at(YAML.find("String").unwrap())
+                .is_none()
+        )
+    }
+
+    #[test]
+    fn maps_intrinsic_names() {
+        assert_eq!(Intrinsic::from_tag("!GetAtt"), Some(Intrinsic::GetAtt));
+        assert_eq!(Intrinsic::from_key("Fn::GetAtt"), Some(Intrinsic::GetAtt));
+        assert_eq!(Intrinsic::from_key("Ref"), Some(Intrinsic::Ref));
+        assert_eq!(Intrinsic::from_tag("!Nope"), None)
+    }
+}
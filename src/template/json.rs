@@ -0,0 +1,484 @@
+use super::{Entry, Key, Node, ParseError, Span, Value};
+
+/// Parses a JSON template into the same node tree produced for YAML,
+/// recovering from syntax errors so that a partial tree is always returned
+pub fn parse(text: &str) -> (Node, Vec<ParseError>) {
+    let mut parser = Parser {
+        text,
+        position: 0,
+        errors: Vec::new(),
+    };
+    parser.skip_whitespace();
+    let root = parser.value();
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        let span = Span::new(parser.position, text.len());
+        parser.error(ParseError::new(
+            "Unexpected content after end of template",
+            span,
+        ));
+    }
+    (root, parser.errors)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    errors: Vec<ParseError>,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Node {
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => {
+                let start = self.position;
+                let value = self.string();
+                Node::new(Value::String(value), Span::new(start, self.position))
+            }
+            Some('-' | '0'..='9') => self.number(),
+            Some(',' | '}' | ']') => {
+                self.error_here("Expected a value");
+                self.empty()
+            }
+            Some(_) => self.literal(),
+            None => {
+                self.error_here("Unexpected end of template, expected a value");
+                self.empty()
+            }
+        }
+    }
+
+    fn object(&mut self) -> Node {
+        let start = self.position;
+        self.position += 1;
+        let mut entries = Vec::new();
+        let mut after_comma = false;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('}') => {
+                    if after_comma {
+                        self.error_here("Expected a string key");
+                    }
+                    self.position += 1;
+                    return Node::mapping(entries, Span::new(start, self.position));
+                }
+                None => {
+                    self.unterminated(start, "object", '}');
+                    return Node::mapping(entries, Span::new(start, self.position));
+                }
+                Some('"') => entries.push(self.entry()),
+                Some(_) => {
+                    self.error_here("Expected a string key");
+                    self.recover('}');
+                }
+            }
+
+            loop {
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(',') => {
+                        self.position += 1;
+                        after_comma = true;
+                        break;
+                    }
+                    Some('}') => {
+                        self.position += 1;
+                        return Node::mapping(entries, Span::new(start, self.position));
+                    }
+                    None => {
+                        self.unterminated(start, "object", '}');
+                        return Node::mapping(entries, Span::new(start, self.position));
+                    }
+                    // A missing comma is reported, then parsing carries on
+                    // with the next key
+                    Some('"') => {
+                        self.error_here("Expected ',' or '}' in object");
+                        after_comma = false;
+                        break;
+                    }
+                    Some(_) => {
+                        self.error_here("Expected ',' or '}' in object");
+                        self.recover('}');
+                    }
+                }
+            }
+        }
+    }
+
+    fn entry(&mut self) -> Entry {
+        let key_start = self.position;
+        let name = self.string();
+        let key = Key::new(&name, Span::new(key_start, self.position));
+        self.skip_whitespace();
+        if self.peek() == Some(':') {
+            self.position += 1;
+            self.skip_whitespace();
+            return Entry::new(key, self.value());
+        }
+        self.error_here("Expected ':'");
+        let value = match self.peek() {
+            Some(',' | '}' | ']') | None => self.empty(),
+            Some(_) => self.value(),
+        };
+        Entry::new(key, value)
+    }
+
+    fn array(&mut self) -> Node {
+        let start = self.position;
+        self.position += 1;
+        let mut items = Vec::new();
+        let mut after_comma = false;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(']') => {
+                    if after_comma {
+                        self.error_here("Expected a value");
+                    }
+                    self.position += 1;
+                    return Node::new(Value::Sequence(items), Span::new(start, self.position));
+                }
+                None => {
+                    self.unterminated(start, "array", ']');
+                    return Node::new(Value::Sequence(items), Span::new(start, self.position));
+                }
+                Some(_) => items.push(self.value()),
+            }
+
+            loop {
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(',') => {
+                        self.position += 1;
+                        after_comma = true;
+                        break;
+                    }
+                    Some(']') => {
+                        self.position += 1;
+                        return Node::new(Value::Sequence(items), Span::new(start, self.position));
+                    }
+                    None => {
+                        self.unterminated(start, "array", ']');
+                        return Node::new(Value::Sequence(items), Span::new(start, self.position));
+                    }
+                    Some('"' | '{' | '[') => {
+                        self.error_here("Expected ',' or ']' in array");
+                        after_comma = false;
+                        break;
+                    }
+                    Some(_) => {
+                        self.error_here("Expected ',' or ']' in array");
+                        self.recover(']');
+                    }
+                }
+            }
+        }
+    }
+
+    // Strings are cut short at the end of the line if left unterminated, so
+    // that a half-typed value doesn't swallow the rest of the template
+    fn string(&mut self) -> String {
+        let start = self.position;
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\r' | '\n') => {
+                    self.error(ParseError::new(
+                        "Unterminated string",
+                        Span::new(start, self.position),
+                    ));
+                    return value;
+                }
+                Some('"') => {
+                    self.position += 1;
+                    return value;
+                }
+                Some('\\') => {
+                    let escape_start = self.position;
+                    self.position += 1;
+                    let escaped = self.peek().filter(|c| !matches!(c, '\r' | '\n'));
+                    self.position += escaped.map(char::len_utf8).unwrap_or(0);
+                    match escaped {
+                        Some('"') => value.push('"'),
+                        Some('\\') => value.push('\\'),
+                        Some('/') => value.push('/'),
+                        Some('b') => value.push('\x08'),
+                        Some('f') => value.push('\x0c'),
+                        Some('n') => value.push('\n'),
+                        Some('r') => value.push('\r'),
+                        Some('t') => value.push('\t'),
+                        Some('u') => value.push(self.unicode_escape(escape_start)),
+                        _ => self.error(ParseError::new(
+                            "Invalid escape sequence",
+                            Span::new(escape_start, self.position),
+                        )),
+                    }
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.position += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self, start: usize) -> char {
+        let Some(high) = self.hex4(start) else {
+            return char::REPLACEMENT_CHARACTER;
+        };
+        let code =
+            if (0xD800..0xDC00).contains(&high) && self.text[self.position..].starts_with("\\u") {
+                self.position += 2;
+                let Some(low) = self.hex4(start) else {
+                    return char::REPLACEMENT_CHARACTER;
+                };
+                0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+            } else {
+                high
+            };
+        char::from_u32(code).unwrap_or_else(|| {
+            self.error(ParseError::new(
+                "Invalid unicode escape",
+                Span::new(start, self.position),
+            ));
+            char::REPLACEMENT_CHARACTER
+        })
+    }
+
+    fn hex4(&mut self, start: usize) -> Option<u32> {
+        let digits = self.text[self.position..]
+            .chars()
+            .take(4)
+            .take_while(char::is_ascii_hexdigit)
+            .count();
+        let code = u32::from_str_radix(&self.text[self.position..self.position + digits], 16);
+        self.position += digits;
+        match code {
+            Ok(code) if digits == 4 => Some(code),
+            _ => {
+                self.error(ParseError::new(
+                    "Invalid unicode escape",
+                    Span::new(start, self.position),
+                ));
+                None
+            }
+        }
+    }
+
+    fn number(&mut self) -> Node {
+        let start = self.position;
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+        let integer = self.digits();
+        let fraction = if self.peek() == Some('.') {
+            self.position += 1;
+            Some(self.digits())
+        } else {
+            None
+        };
+        let exponent = if matches!(self.peek(), Some('e' | 'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some('-' | '+')) {
+                self.position += 1;
+            }
+            Some(self.digits())
+        } else {
+            None
+        };
+        let span = Span::new(start, self.position);
+        if integer == 0 || fraction == Some(0) || exponent == Some(0) {
+            self.error(ParseError::new("Invalid number", span));
+            return Node::new(Value::Null, span);
+        }
+        Node::new(Value::Number(self.text[start..self.position].into()), span)
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn literal(&mut self) -> Node {
+        let start = self.position;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric()) {
+            self.position += 1;
+        }
+        if self.position == start {
+            self.position += self.peek().map(char::len_utf8).unwrap_or(0);
+        }
+        let span = Span::new(start, self.position);
+        match &self.text[start..self.position] {
+            "true" => Node::new(Value::Bool(true), span),
+            "false" => Node::new(Value::Bool(false), span),
+            "null" => Node::new(Value::Null, span),
+            _ => {
+                self.error(ParseError::new("Expected a value", span));
+                Node::new(Value::Null, span)
+            }
+        }
+    }
+
+    /// Skips over unexpected content up to the next comma or closing
+    /// bracket of the enclosing collection, stepping over nested collections
+    /// and strings
+    fn recover(&mut self, close: char) {
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            match c {
+                ',' if depth == 0 => return,
+                _ if c == close && depth == 0 => return,
+                '{' | '[' => depth += 1,
+                '}' | ']' => depth = depth.saturating_sub(1),
+                '"' => {
+                    let errors = self.errors.len();
+                    self.string();
+                    self.errors.truncate(errors);
+                    continue;
+                }
+                _ => {}
+            }
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+            self.position += 1;
+        }
+    }
+
+    fn empty(&self) -> Node {
+        Node::new(Value::Null, Span::new(self.position, self.position))
+    }
+
+    fn error(&mut self, error: ParseError) {
+        self.errors.push(error);
+    }
+
+    fn error_here(&mut self, message: &str) {
+        let end = self.position + self.peek().map(char::len_utf8).unwrap_or(0);
+        self.error(ParseError::new(message, Span::new(self.position, end)));
+    }
+
+    fn unterminated(&mut self, start: usize, kind: &str, close: char) {
+        self.error(ParseError::new(
+            &format!("Unterminated {kind}, expected '{close}'"),
+            Span::new(start, start + 1),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::template::Intrinsic;
+
+    use super::*;
+
+    #[test]
+    fn parses_nested_values_with_spans() {
+        let text =
+            r#"{"Resources": {"Bucket": {"Type": "AWS::S3::Bucket", "Count": [1, true, null]}}}"#;
+        let actual = parse(text).0;
+
+        let bucket = actual.get("Resources").unwrap().entry("Bucket").unwrap();
+        assert_eq!(bucket.key().span(), Span::new(15, 23));
+        assert_eq!(
+            bucket.value().get("Type").unwrap(),
+            &Node::new(Value::String("AWS::S3::Bucket".into()), Span::new(34, 51))
+        );
+        assert_eq!(
+            bucket.value().get("Count").unwrap().as_sequence().unwrap()[0].value(),
+            &Value::Number("1".into())
+        )
+    }
+
+    #[test]
+    fn parses_long_form_functions() {
+        let actual = parse(r#"{"a": {"Fn::GetAtt": ["B", "Arn"]}}"#).0;
+
+        let function = actual.get("a").unwrap().as_function().unwrap();
+        assert_eq!(function.intrinsic(), Intrinsic::GetAtt);
+        assert_eq!(function.name_span(), Span::new(7, 19))
+    }
+
+    #[test]
+    fn decodes_escapes() {
+        let actual = parse(r#"{"a": "\"x\"\n\u00e9\ud83d\ude00"}"#).0;
+        assert_eq!(
+            actual.get("a").unwrap().as_str(),
+            Some("\"x\"\n\u{e9}\u{1F600}")
+        )
+    }
+
+    #[test]
+    fn recovers_from_trailing_comma() {
+        let (root, errors) = parse(r#"{"a": 1,}"#);
+        assert_eq!(
+            errors,
+            vec![ParseError::new("Expected a string key", Span::new(8, 9))]
+        );
+        assert_eq!(root.get("a").unwrap().value(), &Value::Number("1".into()))
+    }
+
+    #[test]
+    fn recovers_from_missing_comma() {
+        let (root, errors) = parse("{\"a\": 1\n \"b\": 2}");
+        assert_eq!(
+            errors,
+            vec![ParseError::new(
+                "Expected ',' or '}' in object",
+                Span::new(9, 10)
+            )]
+        );
+        assert_eq!(root.get("b").unwrap().value(), &Value::Number("2".into()))
+    }
+
+    #[test]
+    fn recovers_from_unterminated_string() {
+        let (root, errors) = parse("{\"a\": {\"Type\": \"AWS::\n  \"b\": true\n}}");
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::new("Unterminated string", Span::new(15, 21)),
+                ParseError::new("Expected ',' or '}' in object", Span::new(24, 25)),
+            ]
+        );
+        let a = root.get("a").unwrap();
+        assert_eq!(a.get("Type").unwrap().as_str(), Some("AWS::"));
+        assert_eq!(a.get("b").unwrap().value(), &Value::Bool(true))
+    }
+
+    #[test]
+    fn recovers_from_unterminated_object() {
+        let (root, errors) = parse(r#"{"a": {"b": 1"#);
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::new("Unterminated object, expected '}'", Span::new(6, 7)),
+                ParseError::new("Unterminated object, expected '}'", Span::new(0, 1)),
+            ]
+        );
+        assert_eq!(root.span(), Span::new(0, 13));
+        assert!(root.get("a").unwrap().get("b").is_some())
+    }
+
+    #[test]
+    fn skips_invalid_values() {
+        let (root, errors) = parse(r#"{"a": nope, "b": [1, ], "c": 3}"#);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(root.get("a").unwrap().value(), &Value::Null);
+        assert_eq!(root.get("c").unwrap().value(), &Value::Number("3".into()))
+    }
+}
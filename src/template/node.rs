@@ -0,0 +1,255 @@
+use super::{Intrinsic, Span};
+
+/// A value in a template together with the span of source text it came from
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Node {
+    value: Value,
+    span: Span,
+}
+
+impl Node {
+    pub fn new(value: Value, span: Span) -> Self {
+        Self { value, span }
+    }
+
+    /// Builds a mapping node, recognising single-key mappings such as
+    /// `Fn::GetAtt: [Bucket, Arn]` as long form intrinsic function calls
+    pub fn mapping(entries: Vec<Entry>, span: Span) -> Self {
+        if let [entry] = entries.as_slice()
+            && let Some(intrinsic) = Intrinsic::from_key(entry.key().name())
+            && (intrinsic != Intrinsic::Condition || entry.value().as_str().is_some())
+        {
+            let function =
+                Function::new(intrinsic, entry.key().span(), false, entry.value().clone());
+            return Self::new(Value::Function(Box::new(function)), span);
+        }
+        Self::new(Value::Mapping(entries), span)
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[Entry]> {
+        match &self.value {
+            Value::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn as_function(&self) -> Option<&Function> {
+        match &self.value {
+            Value::Function(function) => Some(function),
+            _ => None,
+        }
+    }
+
+    /// Looks up the value of a key if this node is a mapping
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.entry(key).map(Entry::value)
+    }
+
+    pub fn entry(&self, key: &str) -> Option<&Entry> {
+        self.as_mapping()?
+            .iter()
+            .find(|entry| entry.key().name() == key)
+    }
+
+    /// Scalar value rendered as a string, as CloudFormation does for
+    /// numbers and booleans used where strings are expected
+    pub fn scalar_string(&self) -> Option<String> {
+        match &self.value {
+            Value::String(value) | Value::Number(value) => Some(value.clone()),
+            Value::Bool(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Path of keys, items and function calls leading to the innermost node
+    /// containing an offset
+    pub fn path_to(&self, offset: usize) -> Vec<Segment<'_>> {
+        let mut path = Vec::new();
+        let mut node = self;
+        loop {
+            match &node.value {
+                Value::Mapping(entries) => {
+                    if let Some(entry) = entries.iter().find(|e| e.key.span.contains(offset)) {
+                        path.push(Segment::Key(entry));
+                        return path;
+                    }
+                    match entries.iter().find(|e| e.value.span.contains(offset)) {
+                        Some(entry) => {
+                            path.push(Segment::Value(entry));
+                            node = &entry.value;
+                        }
+                        None => return path,
+                    }
+                }
+                Value::Sequence(items) => {
+                    match items
+                        .iter()
+                        .enumerate()
+                        .find(|(_, item)| item.span.contains(offset))
+                    {
+                        Some((index, item)) => {
+                            path.push(Segment::Item(index, item));
+                            node = item;
+                        }
+                        None => return path,
+                    }
+                }
+                Value::Function(function) => {
+                    path.push(Segment::Function(function));
+                    if function.name_span.contains(offset)
+                        || !function.argument.span.contains(offset)
+                    {
+                        return path;
+                    }
+                    node = &function.argument;
+                }
+                _ => return path,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Sequence(Vec<Node>),
+    Mapping(Vec<Entry>),
+    Function(Box<Function>),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Entry {
+    key: Key,
+    value: Node,
+}
+
+impl Entry {
+    pub fn new(key: Key, value: Node) -> Self {
+        Self { key, value }
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn value(&self) -> &Node {
+        &self.value
+    }
+
+    /// Span covering both the key and its value
+    pub fn span(&self) -> Span {
+        self.key.span().to(self.value.span())
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Key {
+    name: String,
+    span: Span,
+}
+
+impl Key {
+    pub fn new(name: &str, span: Span) -> Self {
+        Self {
+            name: name.into(),
+            span,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Span of the name alone, excluding any quotes around it
+    pub fn name_span(&self) -> Span {
+        if self.span.end() - self.span.start() == self.name.len() + 2 {
+            Span::new(self.span.start() + 1, self.span.end() - 1)
+        } else {
+            self.span
+        }
+    }
+}
+
+/// An intrinsic function call, written either in short form (`!Ref Name`) or
+/// long form (`Ref: Name`)
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct Function {
+    intrinsic: Intrinsic,
+    name_span: Span,
+    short_form: bool,
+    argument: Node,
+}
+
+impl Function {
+    pub fn new(intrinsic: Intrinsic, name_span: Span, short_form: bool, argument: Node) -> Self {
+        Self {
+            intrinsic,
+            name_span,
+            short_form,
+            argument,
+        }
+    }
+
+    pub fn intrinsic(&self) -> Intrinsic {
+        self.intrinsic
+    }
+
+    pub fn name_span(&self) -> Span {
+        self.name_span
+    }
+
+    pub fn short_form(&self) -> bool {
+        self.short_form
+    }
+
+    pub fn argument(&self) -> &Node {
+        &self.argument
+    }
+}
+
+/// A step on the way down from a node to the innermost part of it lying
+/// under an offset
+#[derive(Debug, Clone, Copy)]
+pub enum Segment<'a> {
+    /// The offset is on the key of an entry
+    Key(&'a Entry),
+    /// The offset is within the value of an entry
+    Value(&'a Entry),
+    Item(usize, &'a Node),
+    /// The offset is on the name of a function call or within its argument
+    Function(&'a Function),
+}
@@ -0,0 +1,363 @@
+use super::{Function, Intrinsic, Node, Output, Resource, Section, Span, Value};
+
+/// How a name is referred to, which decides the sections it may be declared
+/// in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    /// `Ref` or a `${Name}` variable of `Fn::Sub`, to a parameter, resource
+    /// or pseudo parameter
+    Ref,
+    /// `Fn::GetAtt`, a `${Name.Attribute}` variable of `Fn::Sub` or
+    /// `DependsOn`, to a resource
+    Resource,
+    /// The `Condition` of a resource or output, `Fn::If` or the `Condition`
+    /// function, to a condition
+    Condition,
+    /// `Fn::FindInMap`, to a mapping
+    Mapping,
+    /// `Fn::ImportValue`, to an output exported by another stack
+    Import,
+}
+
+/// A use of a logical id or other declared name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    kind: ReferenceKind,
+    name: String,
+    span: Span,
+    attribute: Option<(String, Span)>,
+}
+
+impl Reference {
+    fn new(kind: ReferenceKind, name: &str, span: Span) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            span,
+            attribute: None,
+        }
+    }
+
+    fn with_attribute(mut self, name: &str, span: Span) -> Self {
+        self.attribute = Some((name.into(), span));
+        self
+    }
+
+    pub fn kind(&self) -> ReferenceKind {
+        self.kind
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Span of the name alone, excluding quotes and any attribute
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Name and span of the attribute of a resource, e.g. `Arn` of
+    /// `!GetAtt Bucket.Arn`
+    pub fn attribute(&self) -> Option<(&str, Span)> {
+        self.attribute
+            .as_ref()
+            .map(|(name, span)| (name.as_str(), *span))
+    }
+}
+
+/// Finds every reference in a template, in the order they appear. The
+/// source text is needed to locate `Fn::Sub` variables, which may be inside
+/// quoted or block strings
+pub fn collect(
+    root: &Node,
+    resources: Option<&Section<Resource>>,
+    outputs: Option<&Section<Output>>,
+    text: &str,
+) -> Vec<Reference> {
+    let mut references = Vec::new();
+    for resource in resources.map(Section::items).unwrap_or_default() {
+        if let Some(condition) = resource.condition() {
+            push_name(&mut references, ReferenceKind::Condition, condition);
+        }
+        for dependency in resource.depends_on() {
+            push_name(&mut references, ReferenceKind::Resource, dependency);
+        }
+    }
+    for output in outputs.map(Section::items).unwrap_or_default() {
+        if let Some(condition) = output.condition() {
+            push_name(&mut references, ReferenceKind::Condition, condition);
+        }
+    }
+    functions(root, text, &mut references);
+    references.sort_by_key(|reference| reference.span.start());
+    references
+}
+
+fn functions(node: &Node, text: &str, references: &mut Vec<Reference>) {
+    match node.value() {
+        Value::Mapping(entries) => entries
+            .iter()
+            .for_each(|entry| functions(entry.value(), text, references)),
+        Value::Sequence(items) => items
+            .iter()
+            .for_each(|item| functions(item, text, references)),
+        Value::Function(function) => {
+            function_references(function, text, references);
+            functions(function.argument(), text, references);
+        }
+        _ => {}
+    }
+}
+
+fn function_references(function: &Function, text: &str, references: &mut Vec<Reference>) {
+    let argument = function.argument();
+    let first = argument.as_sequence().and_then(|items| items.first());
+    match function.intrinsic() {
+        Intrinsic::Ref => push_name(references, ReferenceKind::Ref, argument),
+        Intrinsic::GetAtt => match (argument.as_str(), first) {
+            (Some(name), _) => {
+                let span = name_span(argument);
+                let (resource, attribute) = match name.split_once('.') {
+                    Some((resource, attribute)) => (resource, Some(attribute)),
+                    None => (name, None),
+                };
+                let mut reference = Reference::new(
+                    ReferenceKind::Resource,
+                    resource,
+                    Span::new(span.start(), span.start() + resource.len()),
+                );
+                if let Some(attribute) = attribute {
+                    let start = span.start() + resource.len() + 1;
+                    reference = reference
+                        .with_attribute(attribute, Span::new(start, start + attribute.len()));
+                }
+                references.push(reference);
+            }
+            (None, Some(resource)) => {
+                let Some(name) = resource.as_str() else {
+                    return;
+                };
+                let mut reference =
+                    Reference::new(ReferenceKind::Resource, name, name_span(resource));
+                let attribute = argument.as_sequence().and_then(|items| items.get(1));
+                if let Some(attribute) = attribute
+                    && let Some(name) = attribute.as_str()
+                {
+                    reference = reference.with_attribute(name, name_span(attribute));
+                }
+                references.push(reference);
+            }
+            (None, None) => {}
+        },
+        Intrinsic::Sub => {
+            let (template, variables) = match (argument.value(), first) {
+                (Value::String(_), _) => (argument, None),
+                (_, Some(template)) => (
+                    template,
+                    argument.as_sequence().and_then(|items| items.get(1)),
+                ),
+                _ => return,
+            };
+            if template.as_str().is_some() {
+                sub_variables(template, variables, text, references);
+            }
+        }
+        Intrinsic::If => {
+            if let Some(condition) = first {
+                push_name(references, ReferenceKind::Condition, condition);
+            }
+        }
+        Intrinsic::Condition => push_name(references, ReferenceKind::Condition, argument),
+        Intrinsic::FindInMap => {
+            if let Some(mapping) = first {
+                push_name(references, ReferenceKind::Mapping, mapping);
+            }
+        }
+        Intrinsic::ImportValue => push_name(references, ReferenceKind::Import, argument),
+        _ => {}
+    }
+}
+
+/// `${Name}` and `${Name.Attribute}` variables of a `Fn::Sub` string which
+/// aren't defined by its variable map. `${!Literal}` is not a variable
+fn sub_variables(
+    template: &Node,
+    variables: Option<&Node>,
+    text: &str,
+    references: &mut Vec<Reference>,
+) {
+    let span = template.span();
+    let Some(source) = text.get(span.start()..span.end()) else {
+        return;
+    };
+    let mut rest = source;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find(['}', '\n']) else {
+            break;
+        };
+        let variable = &after[..end];
+        let offset = span.start() + (source.len() - after.len());
+        rest = &after[end..];
+        if after[end..].starts_with('\n') || variable.starts_with('!') {
+            continue;
+        }
+        let (name, attribute, kind) = match variable.split_once('.') {
+            Some((name, attribute)) => (name, Some(attribute.trim_end()), ReferenceKind::Resource),
+            None => (variable, None, ReferenceKind::Ref),
+        };
+        let name = name.trim();
+        if name.is_empty() || variables.is_some_and(|variables| variables.get(name).is_some()) {
+            continue;
+        }
+        let leading = variable.len() - variable.trim_start().len();
+        let start = offset + leading;
+        let mut reference = Reference::new(kind, name, Span::new(start, start + name.len()));
+        if let Some(attribute) = attribute {
+            let start = start + name.len() + 1;
+            reference =
+                reference.with_attribute(attribute, Span::new(start, start + attribute.len()));
+        }
+        references.push(reference);
+    }
+}
+
+fn push_name(references: &mut Vec<Reference>, kind: ReferenceKind, node: &Node) {
+    if let Some(name) = node.as_str() {
+        references.push(Reference::new(kind, name, name_span(node)));
+    }
+}
+
+/// Span of a string without its quotes, which names never need escaping
+/// within
+fn name_span(node: &Node) -> Span {
+    let span = node.span();
+    let length = node.as_str().map(str::len).unwrap_or_default();
+    if span.end() - span.start() == length + 2 {
+        Span::new(span.start() + 1, span.end() - 1)
+    } else {
+        span
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::template::parse;
+
+    use super::*;
+
+    fn references(text: &str) -> Vec<(ReferenceKind, String, &str)> {
+        parse(text)
+            .references()
+            .iter()
+            .map(|reference| {
+                let span = reference.span();
+                (
+                    reference.kind(),
+                    reference.name().to_string(),
+                    &text[span.start()..span.end()],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_ref_and_get_att() {
+        let actual = references(
+            "Outputs:\n  A:\n    Value: !Ref Env\n  B:\n    Value: !GetAtt Bucket.Arn\n  C:\n    Value:\n      Fn::GetAtt: [\"Queue\", Arn]\n",
+        );
+        assert_eq!(
+            actual,
+            vec![
+                (ReferenceKind::Ref, "Env".into(), "Env"),
+                (ReferenceKind::Resource, "Bucket".into(), "Bucket"),
+                (ReferenceKind::Resource, "Queue".into(), "Queue"),
+            ]
+        )
+    }
+
+    #[test]
+    fn finds_attributes_of_resources() {
+        let text = "Outputs:\n  A:\n    Value: !GetAtt Db.Endpoint.Address\n  B:\n    Value: !GetAtt [Queue, 'Arn']\n  C:\n    Value: !Sub '${Bucket.Arn}'\n";
+        let actual: Vec<_> = parse(text)
+            .references()
+            .iter()
+            .filter_map(|reference| {
+                let (name, span) = reference.attribute()?;
+                Some((name.to_string(), &text[span.start()..span.end()]))
+            })
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("Endpoint.Address".into(), "Endpoint.Address"),
+                ("Arn".into(), "Arn"),
+                ("Arn".into(), "Arn"),
+            ]
+        )
+    }
+
+    #[test]
+    fn finds_sub_variables() {
+        let actual = references(
+            "Outputs:\n  A:\n    Value: !Sub \"arn:${AWS::Partition}:${Queue.Arn}/${!Literal}/${Name}\"\n  B:\n    Value: !Sub\n      - ${Bucket}-${Suffix}\n      - Suffix: x\n",
+        );
+        assert_eq!(
+            actual,
+            vec![
+                (
+                    ReferenceKind::Ref,
+                    "AWS::Partition".into(),
+                    "AWS::Partition"
+                ),
+                (ReferenceKind::Resource, "Queue".into(), "Queue"),
+                (ReferenceKind::Ref, "Name".into(), "Name"),
+                (ReferenceKind::Ref, "Bucket".into(), "Bucket"),
+            ]
+        )
+    }
+
+    #[test]
+    fn finds_conditions_mappings_and_dependencies() {
+        let actual = references(
+            "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Condition: IsProd
+    DependsOn: [Queue]
+    Properties:
+      BucketName: !If [IsProd, !FindInMap [Names, !Ref AWS::Region, prod], dev]
+Outputs:
+  Name:
+    Condition: IsProd
+    Value: x
+",
+        );
+        assert_eq!(
+            actual,
+            vec![
+                (ReferenceKind::Condition, "IsProd".into(), "IsProd"),
+                (ReferenceKind::Resource, "Queue".into(), "Queue"),
+                (ReferenceKind::Condition, "IsProd".into(), "IsProd"),
+                (ReferenceKind::Mapping, "Names".into(), "Names"),
+                (ReferenceKind::Ref, "AWS::Region".into(), "AWS::Region"),
+                (ReferenceKind::Condition, "IsProd".into(), "IsProd"),
+            ]
+        )
+    }
+
+    #[test]
+    fn finds_condition_function_and_imports() {
+        let actual = references(
+            "Conditions:\n  A: !Equals [a, b]\n  B: !Not [{Condition: A}]\nOutputs:\n  C:\n    Value: !ImportValue shared-vpc\n",
+        );
+        assert_eq!(
+            actual,
+            vec![
+                (ReferenceKind::Condition, "A".into(), "A"),
+                (ReferenceKind::Import, "shared-vpc".into(), "shared-vpc"),
+            ]
+        )
+    }
+}
@@ -0,0 +1,1494 @@
+use std::collections::HashMap;
+
+use super::{Comment, Entry, Function, Intrinsic, Key, Node, ParseError, Span, Value};
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// Most syntax errors a single template is recovered from before the rest of
+/// the text after the last one is given up on
+const MAX_ERRORS: usize = 100;
+
+/// Parses the block and flow YAML subset used by CloudFormation templates,
+/// returning the root node, any comments and anchors or aliases found along
+/// the way and any syntax errors recovered from
+///
+/// Recovery blanks out the text from each error to the end of its line and
+/// parses again, which keeps byte offsets intact so that spans still refer to
+/// the original text
+pub fn parse(text: &str) -> (Node, Vec<Comment>, Vec<Span>, Vec<ParseError>) {
+    let mut source = text.to_string();
+    let mut errors = Vec::new();
+    loop {
+        let mut parser = Parser::new(&source);
+        let error = match parser.document() {
+            Ok(root) => return (root, parser.comments, parser.anchored, errors),
+            Err(error) => error,
+        };
+        let blanked = if errors.len() < MAX_ERRORS {
+            blank_line(&mut source, error.span().start())
+        } else {
+            blank_rest(&mut source, error.span().start())
+        };
+        errors.push(error);
+        if !blanked {
+            let root = Node::new(Value::Null, Span::default());
+            return (root, Vec::new(), Vec::new(), errors);
+        }
+    }
+}
+
+/// Replaces the text from an offset to the end of its line with spaces,
+/// falling back to the previous line with content if there's nothing left to
+/// blank on this one. Returns whether anything was blanked
+fn blank_line(source: &mut String, offset: usize) -> bool {
+    let mut start = offset.min(source.len());
+    loop {
+        let end = source[start..]
+            .find(['\r', '\n'])
+            .map(|i| start + i)
+            .unwrap_or(source.len());
+        if !source[start..end].trim().is_empty() {
+            blank(source, start, end);
+            return true;
+        }
+        let Some(line_end) = source[..start].rfind('\n') else {
+            return false;
+        };
+        start = source[..line_end].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    }
+}
+
+/// Replaces everything from the start of an offset's line onwards
+fn blank_rest(source: &mut String, offset: usize) -> bool {
+    let start = source[..offset.min(source.len())]
+        .rfind('\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let end = source.len();
+    let blanked = !source[start..].trim().is_empty();
+    blank(source, start, end);
+    blanked || blank_line(source, start)
+}
+
+fn blank(source: &mut String, start: usize, end: usize) {
+    let blanked: String = source[start..end]
+        .chars()
+        .map(|c| match c {
+            '\r' | '\n' => c.to_string(),
+            _ => " ".repeat(c.len_utf8()),
+        })
+        .collect();
+    source.replace_range(start..end, &blanked);
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    comments: Vec<Comment>,
+    anchors: HashMap<String, Node>,
+    /// Spans of each `&anchor` and `*alias`
+    anchored: Vec<Span>,
+}
+
+/// Properties which may precede a node, e.g. `!Ref` or `&anchor`
+#[derive(Default)]
+struct Properties {
+    tag: Option<(String, Span)>,
+    anchor: Option<String>,
+    start: Option<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            position: 0,
+            comments: Vec::new(),
+            anchors: HashMap::new(),
+            anchored: Vec::new(),
+        }
+    }
+
+    fn document(&mut self) -> Result<Node> {
+        self.skip_directives();
+        let Some(indent) = self.next_content()? else {
+            return Ok(self.empty());
+        };
+        if self.at_document_marker("---") {
+            self.position += 3;
+            return match self.inline_content()? {
+                true => self.node_after_indicator(-1, false),
+                false => self.document_body(),
+            };
+        }
+        let root = self.block(indent as isize, -1)?;
+        self.document_end(root)
+    }
+
+    fn document_body(&mut self) -> Result<Node> {
+        let Some(indent) = self.next_content()? else {
+            return Ok(self.empty());
+        };
+        if self.at_document_marker("...") {
+            return Ok(self.empty());
+        }
+        let root = self.block(indent as isize, -1)?;
+        self.document_end(root)
+    }
+
+    fn document_end(&mut self, root: Node) -> Result<Node> {
+        match self.next_content()? {
+            None => Ok(root),
+            Some(_) if self.at_document_marker("...") || self.at_document_marker("---") => Ok(root),
+            Some(_) => Err(self.error_at_line("Unexpected content after end of template")),
+        }
+    }
+
+    fn skip_directives(&mut self) {
+        while self.text[self.position..].starts_with('%') {
+            self.skip_line();
+        }
+    }
+
+    // Block context
+
+    /// Parses a block node whose first token is at the current position, on a
+    /// line indented by `indent`, belonging to a parent indented by `parent`
+    fn block(&mut self, indent: isize, parent: isize) -> Result<Node> {
+        if self.at_sequence_entry() {
+            self.block_sequence(indent)
+        } else if self.at_mapping_key() {
+            self.block_mapping(indent)
+        } else {
+            self.inline_node(parent, Properties::default())
+        }
+    }
+
+    fn block_sequence(&mut self, indent: isize) -> Result<Node> {
+        let start = self.position;
+        let mut items = Vec::new();
+        loop {
+            self.position += 1;
+            items.push(self.node_after_indicator(indent, false)?);
+
+            match self.next_content()? {
+                Some(next) if next as isize == indent && self.at_sequence_entry() => {}
+                Some(next) if next as isize > indent => {
+                    return Err(self.error_at_line("Unexpected indentation"));
+                }
+                Some(next) if next as isize == indent && !self.at_document_end() => {
+                    if self.at_mapping_key() {
+                        break;
+                    }
+                    return Err(self.error_at_line("Expected a sequence entry"));
+                }
+                _ => break,
+            }
+        }
+        let end = items.last().map(|item| item.span().end()).unwrap_or(start);
+        Ok(Node::new(Value::Sequence(items), Span::new(start, end)))
+    }
+
+    fn block_mapping(&mut self, indent: isize) -> Result<Node> {
+        let start = self.position;
+        let mut entries = Vec::new();
+        loop {
+            if self.at_explicit_key() {
+                entries.push(self.explicit_entry(indent)?);
+            } else {
+                let key = self.block_key()?;
+                // The indicator itself
+                self.position += 1;
+                let value = self.node_after_indicator(indent, true)?;
+                entries.push(Entry::new(key, value));
+            }
+
+            match self.next_content()? {
+                Some(next) if next as isize == indent && !self.at_document_end() => {
+                    if !self.at_mapping_key() {
+                        return Err(self.error_at_line("Expected a mapping key"));
+                    }
+                }
+                Some(next) if next as isize > indent => {
+                    return Err(self.error_at_line("Unexpected indentation"));
+                }
+                _ => break,
+            }
+        }
+        let end = entries
+            .last()
+            .map(|entry| entry.value().span().end())
+            .unwrap_or(start);
+        Ok(Node::mapping(entries, Span::new(start, end)))
+    }
+
+    fn block_key(&mut self) -> Result<Key> {
+        let start = self.position;
+        let name = match self.peek() {
+            Some('"') => self.double_quoted()?,
+            Some('\'') => self.single_quoted()?,
+            _ => self.plain_key(),
+        };
+        let key = Key::new(&name, Span::new(start, self.position));
+        self.skip_space();
+        match self.peek() {
+            Some(':') => Ok(key),
+            _ => Err(ParseError::new(
+                "Expected ':' after mapping key",
+                Span::new(start, self.position),
+            )),
+        }
+    }
+
+    /// Parses an entry whose key follows a `?` indicator, with its value on
+    /// a following `:` line at the same indentation, if there is one. Only
+    /// scalar keys are supported, as templates have no use for others
+    fn explicit_entry(&mut self, indent: isize) -> Result<Entry> {
+        let indicator_end = self.position + 1;
+        self.position = indicator_end;
+        let node = self.node_after_indicator(indent, false)?;
+        let Some(name) = node.scalar_string() else {
+            return Err(ParseError::new(
+                "Expected a scalar mapping key",
+                Span::new(indicator_end - 1, node.span().end().max(indicator_end)),
+            ));
+        };
+        let key = Key::new(&name, node.span());
+
+        let checkpoint = self.position;
+        let value = match self.next_content()? {
+            Some(next)
+                if next as isize == indent
+                    && self.peek() == Some(':')
+                    && self.is_separator(self.position + 1) =>
+            {
+                self.position += 1;
+                self.node_after_indicator(indent, true)?
+            }
+            _ => {
+                self.position = checkpoint;
+                let end = node.span().end().max(indicator_end);
+                Node::new(Value::Null, Span::new(end, end))
+            }
+        };
+        Ok(Entry::new(key, value))
+    }
+
+    fn plain_key(&mut self) -> String {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c == ':' && self.is_separator(self.position + 1) {
+                break;
+            }
+            if c == '\n' || c == '\r' || (c == '#' && self.preceded_by_space()) {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        let key = self.text[start..self.position].trim_end();
+        self.position = start + key.len();
+        key.into()
+    }
+
+    /// Parses the value following a `-` or `:` indicator, which may be on the
+    /// same line or an indented block on the following lines
+    fn node_after_indicator(&mut self, parent: isize, mapping_value: bool) -> Result<Node> {
+        let indicator_end = self.position;
+        self.skip_space();
+        if !self.inline_content()? {
+            return self.node_below(parent, mapping_value, Properties::default(), indicator_end);
+        }
+
+        let column = self.column() as isize;
+        if !mapping_value && self.at_sequence_entry() {
+            return self.block_sequence(column);
+        }
+        if !mapping_value && self.at_mapping_key() {
+            return self.block_mapping(column);
+        }
+
+        let properties = self.properties()?;
+        if properties.start.is_some() && !self.inline_content()? {
+            return self.node_below(parent, mapping_value, properties, indicator_end);
+        }
+        self.inline_node(parent, properties)
+    }
+
+    /// Parses a node on the lines following an indicator, or an empty node if
+    /// nothing more indented follows
+    fn node_below(
+        &mut self,
+        parent: isize,
+        mapping_value: bool,
+        properties: Properties,
+        indicator_end: usize,
+    ) -> Result<Node> {
+        let checkpoint = self.position;
+        let node = match self.next_content()? {
+            Some(indent)
+                if !self.at_document_end()
+                    && (indent as isize > parent
+                        || (mapping_value
+                            && indent as isize == parent
+                            && self.at_sequence_entry())) =>
+            {
+                self.block(indent as isize, parent)?
+            }
+            _ => {
+                self.position = checkpoint;
+                let end = properties
+                    .tag
+                    .as_ref()
+                    .map(|(_, span)| span.end())
+                    .unwrap_or(indicator_end);
+                Node::new(Value::Null, Span::new(end, end))
+            }
+        };
+        self.apply(properties, node)
+    }
+
+    /// Parses a scalar, flow collection or block scalar starting on the
+    /// current line
+    fn inline_node(&mut self, parent: isize, properties: Properties) -> Result<Node> {
+        let properties = match properties.start {
+            Some(_) => properties,
+            None => self.properties()?,
+        };
+        if properties.start.is_some() && !self.inline_content()? {
+            let position = self.position;
+            return self.node_below(parent, true, properties, position);
+        }
+
+        let node = match self.peek() {
+            Some('*') => self.alias()?,
+            Some('|') | Some('>') => self.block_scalar(parent)?,
+            Some('[') | Some('{') => self.flow_node()?,
+            Some('"') => {
+                let start = self.position;
+                let value = self.double_quoted()?;
+                Node::new(Value::String(value), Span::new(start, self.position))
+            }
+            Some('\'') => {
+                let start = self.position;
+                let value = self.single_quoted()?;
+                Node::new(Value::String(value), Span::new(start, self.position))
+            }
+            _ => self.plain_scalar(parent)?,
+        };
+        self.apply(properties, node)
+    }
+
+    fn plain_scalar(&mut self, parent: isize) -> Result<Node> {
+        let start = self.position;
+        let mut value = self.plain_line();
+        let mut end = self.position;
+        if value.is_empty() {
+            return Err(self.error_at_line("Expected a value"));
+        }
+
+        // Plain scalars continue onto following lines which are indented more
+        // than their parent, folding each line break into a space
+        loop {
+            let checkpoint = self.position;
+            let mut breaks = 0;
+            let indent = loop {
+                self.skip_space();
+                match self.peek() {
+                    Some('\r') | Some('\n') => {
+                        self.skip_line();
+                        breaks += 1;
+                    }
+                    _ => break self.indentation(),
+                }
+            };
+            let continues = breaks > 0
+                && indent as isize > parent
+                && !matches!(self.peek(), None | Some('#'))
+                && !self.at_mapping_key()
+                && !self.at_document_end();
+            if !continues {
+                self.position = checkpoint;
+                break;
+            }
+            let line = self.plain_line();
+            if breaks > 1 {
+                value.push_str(&"\n".repeat(breaks - 1));
+            } else {
+                value.push(' ');
+            }
+            value.push_str(&line);
+            end = self.position;
+        }
+        self.position = end;
+        Ok(Node::new(scalar(&value), Span::new(start, end)))
+    }
+
+    fn plain_line(&mut self) -> String {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c == ':' && self.is_separator(self.position + 1) {
+                break;
+            }
+            if c == '\n' || c == '\r' || (c == '#' && self.preceded_by_space()) {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        let line = self.text[start..self.position].trim_end();
+        self.position = start + line.len();
+        line.into()
+    }
+
+    fn block_scalar(&mut self, parent: isize) -> Result<Node> {
+        let start = self.position;
+        let literal = self.peek() == Some('|');
+        self.position += 1;
+
+        let mut chomping = Chomping::Clip;
+        let mut explicit_indent = None;
+        for _ in 0..2 {
+            match self.peek() {
+                Some('-') => chomping = Chomping::Strip,
+                Some('+') => chomping = Chomping::Keep,
+                Some(c @ '1'..='9') => explicit_indent = c.to_digit(10).map(|d| d as usize),
+                _ => break,
+            }
+            self.position += 1;
+        }
+        self.skip_space();
+        self.skip_comment();
+        if !matches!(self.peek(), None | Some('\r') | Some('\n')) {
+            return Err(self.error_at_line("Unexpected content after block scalar indicator"));
+        }
+        let header_end = self.position;
+        self.skip_line();
+
+        let base = parent.max(0) as usize;
+        let mut indent = explicit_indent.map(|indent| base + indent);
+        let mut lines = Vec::new();
+        let mut end = header_end;
+        while self.position < self.text.len() {
+            let line_start = self.position;
+            let line_end = self.text[line_start..]
+                .find('\n')
+                .map(|i| line_start + i)
+                .unwrap_or(self.text.len());
+            let line = self.text[line_start..line_end].trim_end_matches('\r');
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+
+            if line.trim().is_empty() {
+                lines.push(line.get(indent.unwrap_or(usize::MAX)..).unwrap_or(""));
+                self.position = (line_end + 1).min(self.text.len());
+                continue;
+            }
+            let required = *indent.get_or_insert(spaces);
+            if spaces < required || spaces as isize <= parent {
+                break;
+            }
+            lines.push(&line[required..]);
+            end = line_start + line.len();
+            self.position = (line_end + 1).min(self.text.len());
+        }
+        // Trailing blank lines belong to the scalar only for chomping
+        let content_lines = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let trailing = lines.len() - content_lines;
+        lines.truncate(content_lines);
+        self.position = end.max(header_end);
+
+        let mut value = if literal {
+            lines.join("\n")
+        } else {
+            fold(&lines)
+        };
+        if !lines.is_empty() {
+            match chomping {
+                Chomping::Strip => {}
+                Chomping::Clip => value.push('\n'),
+                Chomping::Keep => value.push_str(&"\n".repeat(trailing + 1)),
+            }
+        }
+        Ok(Node::new(
+            Value::String(value),
+            Span::new(start, self.position),
+        ))
+    }
+
+    fn properties(&mut self) -> Result<Properties> {
+        let mut properties = Properties::default();
+        loop {
+            match self.peek() {
+                Some('!') => {
+                    let start = self.position;
+                    let tag = self.token();
+                    properties.start.get_or_insert(start);
+                    properties.tag = Some((tag, Span::new(start, self.position)));
+                }
+                Some('&') => {
+                    let start = self.position;
+                    self.position += 1;
+                    let anchor = self.token();
+                    self.anchored(Span::new(start, self.position));
+                    properties.start.get_or_insert(start);
+                    properties.anchor = Some(anchor);
+                }
+                _ => return Ok(properties),
+            }
+            self.skip_space();
+        }
+    }
+
+    fn alias(&mut self) -> Result<Node> {
+        let start = self.position;
+        self.position += 1;
+        let name = self.token();
+        let span = Span::new(start, self.position);
+        self.anchored(span);
+        match self.anchors.get(&name) {
+            Some(node) => Ok(Node::new(node.value().clone(), span)),
+            None => Err(ParseError::new(&format!("Unknown alias '{name}'"), span)),
+        }
+    }
+
+    // Anchors are seen again when backtracking over lookahead
+    fn anchored(&mut self, span: Span) {
+        if self
+            .anchored
+            .last()
+            .is_none_or(|last| last.start() < span.start())
+        {
+            self.anchored.push(span);
+        }
+    }
+
+    /// Applies a tag and anchor to a parsed node
+    fn apply(&mut self, properties: Properties, node: Node) -> Result<Node> {
+        let node = match properties.tag {
+            None => node,
+            Some((tag, span)) => tagged(&tag, span, node)?,
+        };
+        if let Some(anchor) = properties.anchor {
+            self.anchors.insert(anchor, node.clone());
+        }
+        Ok(node)
+    }
+
+    // Flow context
+
+    fn flow_node(&mut self) -> Result<Node> {
+        let properties = self.properties()?;
+        self.skip_flow_space()?;
+        let node = match self.peek() {
+            Some('[') => self.flow_sequence()?,
+            Some('{') => self.flow_mapping()?,
+            Some('*') => self.alias()?,
+            Some('"') => {
+                let start = self.position;
+                let value = self.double_quoted()?;
+                Node::new(Value::String(value), Span::new(start, self.position))
+            }
+            Some('\'') => {
+                let start = self.position;
+                let value = self.single_quoted()?;
+                Node::new(Value::String(value), Span::new(start, self.position))
+            }
+            Some(',') | Some(']') | Some('}') | None => {
+                let position = self.position;
+                Node::new(Value::Null, Span::new(position, position))
+            }
+            _ => {
+                let start = self.position;
+                let value = self.flow_plain();
+                if value.is_empty() {
+                    return Err(self.error_at_line("Expected a value"));
+                }
+                Node::new(scalar(&value), Span::new(start, self.position))
+            }
+        };
+        self.apply(properties, node)
+    }
+
+    fn flow_sequence(&mut self) -> Result<Node> {
+        let start = self.position;
+        self.position += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_flow_space()?;
+            match self.peek() {
+                Some(']') => break,
+                None => return Err(self.unterminated(start, "sequence", ']')),
+                _ => {}
+            }
+            items.push(self.flow_node()?);
+            self.skip_flow_space()?;
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => break,
+                None => return Err(self.unterminated(start, "sequence", ']')),
+                _ => {
+                    return Err(self.flow_error(
+                        start,
+                        "sequence",
+                        "Expected ',' or ']' in flow sequence",
+                    ));
+                }
+            }
+        }
+        self.position += 1;
+        Ok(Node::new(
+            Value::Sequence(items),
+            Span::new(start, self.position),
+        ))
+    }
+
+    fn flow_mapping(&mut self) -> Result<Node> {
+        let start = self.position;
+        self.position += 1;
+        let mut entries = Vec::new();
+        loop {
+            self.skip_flow_space()?;
+            match self.peek() {
+                Some('}') => break,
+                None => return Err(self.unterminated(start, "mapping", '}')),
+                _ => {}
+            }
+            let key_start = self.position;
+            let name = match self.peek() {
+                Some('"') => self.double_quoted()?,
+                Some('\'') => self.single_quoted()?,
+                _ => self.flow_plain(),
+            };
+            let key = Key::new(&name, Span::new(key_start, self.position));
+            self.skip_flow_space()?;
+            let value = match self.peek() {
+                Some(':') => {
+                    self.position += 1;
+                    self.skip_flow_space()?;
+                    self.flow_node()?
+                }
+                Some(',') | Some('}') => {
+                    let position = self.position;
+                    Node::new(Value::Null, Span::new(position, position))
+                }
+                _ => return Err(self.flow_error(start, "mapping", "Expected ':' in flow mapping")),
+            };
+            entries.push(Entry::new(key, value));
+            self.skip_flow_space()?;
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => break,
+                None => return Err(self.unterminated(start, "mapping", '}')),
+                _ => {
+                    return Err(self.flow_error(
+                        start,
+                        "mapping",
+                        "Expected ',' or '}' in flow mapping",
+                    ));
+                }
+            }
+        }
+        self.position += 1;
+        Ok(Node::mapping(entries, Span::new(start, self.position)))
+    }
+
+    fn flow_plain(&mut self) -> String {
+        let mut value = self.flow_plain_line();
+        // Plain scalars in flow collections may also span lines
+        loop {
+            let checkpoint = self.position;
+            let mut breaks = 0;
+            while let Some(c) = self.peek() {
+                match c {
+                    '\n' => breaks += 1,
+                    ' ' | '\t' | '\r' => {}
+                    _ => break,
+                }
+                self.position += 1;
+            }
+            let continues = breaks > 0
+                && !matches!(self.peek(), None | Some(',' | '[' | ']' | '{' | '}' | '#'))
+                && !(self.peek() == Some(':') && self.is_separator(self.position + 1));
+            if !continues {
+                self.position = checkpoint;
+                return value;
+            }
+            if breaks > 1 {
+                value.push_str(&"\n".repeat(breaks - 1));
+            } else {
+                value.push(' ');
+            }
+            value.push_str(&self.flow_plain_line());
+        }
+    }
+
+    fn flow_plain_line(&mut self) -> String {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            let indicator = c == ':'
+                && (self.is_separator(self.position + 1)
+                    || matches!(self.peek_at(1), Some(',' | ']' | '}')));
+            if indicator
+                || matches!(c, ',' | '[' | ']' | '{' | '}' | '\n' | '\r')
+                || (c == '#' && self.preceded_by_space())
+            {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        let value = self.text[start..self.position].trim_end();
+        self.position = start + value.len();
+        value.into()
+    }
+
+    fn skip_flow_space(&mut self) -> Result<()> {
+        loop {
+            match self.peek() {
+                Some(' ') | Some('\t') | Some('\r') | Some('\n') => self.position += 1,
+                Some('#') => self.skip_comment(),
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    // Quoted scalars
+
+    fn double_quoted(&mut self) -> Result<String> {
+        let start = self.position;
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(ParseError::new(
+                    "Unterminated double-quoted string",
+                    Span::new(start, self.position),
+                ));
+            };
+            match c {
+                '"' => {
+                    self.position += 1;
+                    return Ok(value);
+                }
+                '\\' => {
+                    let escape_start = self.position;
+                    self.position += 1;
+                    let Some(escaped) = self.peek() else {
+                        continue;
+                    };
+                    self.position += escaped.len_utf8();
+                    match escaped {
+                        'n' => value.push('\n'),
+                        't' | '\t' => value.push('\t'),
+                        'r' => value.push('\r'),
+                        '0' => value.push('\0'),
+                        'a' => value.push('\x07'),
+                        'b' => value.push('\x08'),
+                        'e' => value.push('\x1b'),
+                        'f' => value.push('\x0c'),
+                        'v' => value.push('\x0b'),
+                        ' ' => value.push(' '),
+                        '"' => value.push('"'),
+                        '/' => value.push('/'),
+                        '\\' => value.push('\\'),
+                        'N' => value.push('\u{85}'),
+                        '_' => value.push('\u{a0}'),
+                        'L' => value.push('\u{2028}'),
+                        'P' => value.push('\u{2029}'),
+                        'x' => value.push(self.hex_escape(2, escape_start)?),
+                        'u' => value.push(self.hex_escape(4, escape_start)?),
+                        'U' => value.push(self.hex_escape(8, escape_start)?),
+                        '\r' | '\n' => {
+                            // Escaped line break joins lines without a space
+                            if escaped == '\r' && self.peek() == Some('\n') {
+                                self.position += 1;
+                            }
+                            self.skip_space();
+                        }
+                        _ => {
+                            return Err(ParseError::new(
+                                &format!("Invalid escape sequence '\\{escaped}'"),
+                                Span::new(escape_start, self.position),
+                            ));
+                        }
+                    }
+                }
+                '\r' | '\n' => self.fold_quoted_line(&mut value),
+                _ => {
+                    value.push(c);
+                    self.position += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn single_quoted(&mut self) -> Result<String> {
+        let start = self.position;
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(ParseError::new(
+                    "Unterminated single-quoted string",
+                    Span::new(start, self.position),
+                ));
+            };
+            match c {
+                '\'' if self.peek_at(1) == Some('\'') => {
+                    value.push('\'');
+                    self.position += 2;
+                }
+                '\'' => {
+                    self.position += 1;
+                    return Ok(value);
+                }
+                '\r' | '\n' => self.fold_quoted_line(&mut value),
+                _ => {
+                    value.push(c);
+                    self.position += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Folds a line break inside a quoted scalar into a space, or into line
+    /// feeds if followed by empty lines
+    fn fold_quoted_line(&mut self, value: &mut String) {
+        let trimmed = value.trim_end_matches([' ', '\t']).len();
+        value.truncate(trimmed);
+        let mut breaks = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                '\n' => {
+                    breaks += 1;
+                    self.position += 1;
+                }
+                ' ' | '\t' | '\r' => self.position += 1,
+                _ => break,
+            }
+        }
+        if breaks > 1 {
+            value.push_str(&"\n".repeat(breaks - 1));
+        } else {
+            value.push(' ');
+        }
+    }
+
+    fn hex_escape(&mut self, digits: usize, start: usize) -> Result<char> {
+        let end = (self.position + digits).min(self.text.len());
+        let code = self
+            .text
+            .get(self.position..end)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32);
+        self.position = end;
+        code.ok_or_else(|| {
+            ParseError::new("Invalid escape sequence", Span::new(start, self.position))
+        })
+    }
+
+    // Lexical helpers
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.text[self.position..].chars().nth(offset)
+    }
+
+    fn empty(&self) -> Node {
+        Node::new(Value::Null, Span::new(self.position, self.position))
+    }
+
+    fn skip_space(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.position += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() != Some('#') {
+            return;
+        }
+        let start = self.position;
+        let end = self.text[start..]
+            .find(['\r', '\n'])
+            .map(|i| start + i)
+            .unwrap_or(self.text.len());
+        // Comments are seen again when backtracking over lookahead
+        if self
+            .comments
+            .last()
+            .is_none_or(|comment| comment.span().start() < start)
+        {
+            self.comments.push(Comment::new(
+                &self.text[start + 1..end],
+                Span::new(start, end),
+            ));
+        }
+        self.position = end;
+    }
+
+    fn skip_line(&mut self) {
+        self.position = self.text[self.position..]
+            .find('\n')
+            .map(|i| self.position + i + 1)
+            .unwrap_or(self.text.len());
+    }
+
+    /// Whether the rest of the current line holds content, skipping any
+    /// trailing comment
+    fn inline_content(&mut self) -> Result<bool> {
+        self.skip_space();
+        self.skip_comment();
+        Ok(!matches!(self.peek(), None | Some('\r') | Some('\n')))
+    }
+
+    /// Moves to the first token on the next line with content, returning its
+    /// indentation, or `None` at the end of the text
+    fn next_content(&mut self) -> Result<Option<usize>> {
+        if !self.at_line_indentation() && self.inline_content()? {
+            return Err(self.error_at_line("Unexpected content"));
+        }
+        loop {
+            if self.column() == 0 || self.at_line_indentation() {
+                let line_start = self.line_start();
+                let indentation = &self.text[line_start..];
+                let spaces = indentation.len() - indentation.trim_start_matches(' ').len();
+                self.position = line_start + spaces;
+                if self.peek() == Some('\t') {
+                    return Err(ParseError::new(
+                        "Tabs are not allowed for indentation",
+                        Span::new(self.position, self.position + 1),
+                    ));
+                }
+            } else {
+                self.skip_space();
+            }
+            self.skip_comment();
+            match self.peek() {
+                None => return Ok(None),
+                Some('\r') | Some('\n') => self.skip_line(),
+                Some(_) => return Ok(Some(self.indentation())),
+            }
+        }
+    }
+
+    fn token(&mut self) -> String {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}') {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        self.text[start..self.position].into()
+    }
+
+    fn line_start(&self) -> usize {
+        self.text[..self.position]
+            .rfind('\n')
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    }
+
+    fn column(&self) -> usize {
+        self.position - self.line_start()
+    }
+
+    /// Indentation of the line the current position is on
+    fn indentation(&self) -> usize {
+        let line = &self.text[self.line_start()..];
+        line.len() - line.trim_start_matches(' ').len()
+    }
+
+    fn at_line_indentation(&self) -> bool {
+        self.text[self.line_start()..self.position]
+            .chars()
+            .all(|c| c == ' ')
+    }
+
+    fn preceded_by_space(&self) -> bool {
+        self.position == 0
+            || self.text[..self.position]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_whitespace)
+    }
+
+    fn is_separator(&self, position: usize) -> bool {
+        matches!(
+            self.text
+                .get(position..)
+                .and_then(|rest| rest.chars().next()),
+            None | Some(' ') | Some('\t') | Some('\r') | Some('\n')
+        )
+    }
+
+    fn at_sequence_entry(&self) -> bool {
+        self.peek() == Some('-') && self.is_separator(self.position + 1)
+    }
+
+    fn at_document_marker(&self, marker: &str) -> bool {
+        self.column() == 0
+            && self.text[self.position..].starts_with(marker)
+            && self.is_separator(self.position + marker.len())
+    }
+
+    fn at_document_end(&self) -> bool {
+        self.at_document_marker("---") || self.at_document_marker("...")
+    }
+
+    fn at_explicit_key(&self) -> bool {
+        self.peek() == Some('?') && self.is_separator(self.position + 1)
+    }
+
+    /// Looks ahead to see whether the current line starts a mapping entry
+    fn at_mapping_key(&self) -> bool {
+        if self.at_explicit_key() {
+            return true;
+        }
+        let mut lookahead = Parser::new(self.text);
+        lookahead.position = self.position;
+        match lookahead.peek() {
+            Some('"') if lookahead.double_quoted().is_err() => return false,
+            Some('\'') if lookahead.single_quoted().is_err() => return false,
+            Some('"') | Some('\'') => {}
+            Some('-') if lookahead.is_separator(lookahead.position + 1) => return false,
+            Some('!' | '&' | '*' | '[' | '{' | '|' | '>' | '#') | None => return false,
+            _ => {
+                lookahead.plain_key();
+            }
+        }
+        lookahead.skip_space();
+        lookahead.peek() == Some(':') && lookahead.is_separator(lookahead.position + 1)
+    }
+
+    fn error_here(&self, message: &str) -> ParseError {
+        let end = self.position + self.peek().map(char::len_utf8).unwrap_or(0);
+        ParseError::new(message, Span::new(self.position, end))
+    }
+
+    fn error_at_line(&self, message: &str) -> ParseError {
+        let end = self.text[self.position..]
+            .find(['\r', '\n'])
+            .map(|i| self.position + i)
+            .unwrap_or(self.text.len());
+        ParseError::new(message, Span::new(self.position, end))
+    }
+
+    /// Error for unexpected content in a flow collection. Once the collection
+    /// runs onto another line the likely cause is a missing closing bracket,
+    /// so the error is reported against the opening one instead
+    fn flow_error(&self, start: usize, kind: &str, message: &str) -> ParseError {
+        if self.text[start..self.position].contains('\n') {
+            let close = if kind == "sequence" { ']' } else { '}' };
+            return self.unterminated(start, kind, close);
+        }
+        self.error_here(message)
+    }
+
+    fn unterminated(&self, start: usize, kind: &str, close: char) -> ParseError {
+        ParseError::new(
+            &format!("Unterminated flow {kind}, expected '{close}'"),
+            Span::new(start, start + 1),
+        )
+    }
+}
+
+enum Chomping {
+    Strip,
+    Clip,
+    Keep,
+}
+
+/// Folds the lines of a `>` block scalar, keeping line breaks around empty
+/// and more-indented lines
+fn fold(lines: &[&str]) -> String {
+    let indented = |line: &str| line.starts_with([' ', '\t']);
+    let mut value = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(previous) = i.checked_sub(1).map(|i| lines[i]) {
+            if line.is_empty() || indented(line) || indented(previous) {
+                value.push('\n');
+            } else if !previous.is_empty() {
+                value.push(' ');
+            }
+        }
+        value.push_str(line);
+    }
+    value
+}
+
+/// Resolves a plain scalar to a null, boolean, number or string
+fn scalar(value: &str) -> Value {
+    match value {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ if is_number(value) => Value::Number(value.into()),
+        _ => Value::String(value.into()),
+    }
+}
+
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if digits.starts_with("0x") || digits.starts_with("0o") {
+        return digits.len() > 2 && digits[2..].chars().all(|c| c.is_ascii_hexdigit());
+    }
+    let (mantissa, exponent) = match digits.find(['e', 'E']) {
+        Some(i) => (&digits[..i], Some(&digits[i + 1..])),
+        None => (digits, None),
+    };
+    let mut parts = mantissa.splitn(2, '.');
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next();
+    let valid_mantissa = (!integer.is_empty() || fraction.is_some_and(|f| !f.is_empty()))
+        && integer.chars().all(|c| c.is_ascii_digit())
+        && fraction.is_none_or(|f| f.chars().all(|c| c.is_ascii_digit()));
+    let valid_exponent = exponent.is_none_or(|e| {
+        let e = e.strip_prefix(['-', '+']).unwrap_or(e);
+        !e.is_empty() && e.chars().all(|c| c.is_ascii_digit())
+    });
+    valid_mantissa && valid_exponent
+}
+
+fn tagged(tag: &str, span: Span, node: Node) -> Result<Node> {
+    let node_span = span.to(node.span());
+    if let Some(intrinsic) = Intrinsic::from_tag(tag) {
+        let function = Function::new(intrinsic, span, true, node);
+        return Ok(Node::new(Value::Function(Box::new(function)), node_span));
+    }
+    match tag {
+        "!!str" => {
+            let value = node.scalar_string().unwrap_or_default();
+            Ok(Node::new(Value::String(value), node_span))
+        }
+        "!!int" | "!!float" | "!!bool" | "!!null" | "!!seq" | "!!map" => {
+            Ok(Node::new(node.value().clone(), node_span))
+        }
+        _ => Err(ParseError::new(&format!("Unsupported tag '{tag}'"), span)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str, start: usize, end: usize) -> Node {
+        Node::new(Value::String(value.into()), Span::new(start, end))
+    }
+
+    fn root(text: &str) -> Node {
+        let (root, _, _, errors) = parse(text);
+        assert_eq!(errors, Vec::new());
+        root
+    }
+
+    fn errors(text: &str) -> Vec<ParseError> {
+        parse(text).3
+    }
+
+    mod block_tests {
+        use super::*;
+
+        #[test]
+        fn parses_empty_document() {
+            assert_eq!(root("# nothing\n").value(), &Value::Null)
+        }
+
+        #[test]
+        fn parses_nested_mappings_with_spans() {
+            let text = "Resources:\n  Bucket:\n    Type: AWS::S3::Bucket\n";
+            let actual = root(text);
+
+            let resources = actual.entry("Resources").unwrap();
+            assert_eq!(resources.key().span(), Span::new(0, 9));
+            let bucket = resources.value().entry("Bucket").unwrap();
+            assert_eq!(bucket.key().span(), Span::new(13, 19));
+            assert_eq!(
+                bucket.value().get("Type").unwrap(),
+                &string("AWS::S3::Bucket", 31, 46)
+            );
+            assert_eq!(actual.span(), Span::new(0, 46))
+        }
+
+        #[test]
+        fn parses_sequences_of_compact_mappings() {
+            let text = "Tags:\n  - Key: a\n    Value: b\n  - Key: c\n    Value: d\n";
+            let actual = root(text);
+
+            let tags = actual.get("Tags").unwrap().as_sequence().unwrap();
+            assert_eq!(tags.len(), 2);
+            assert_eq!(tags[1].get("Value").unwrap(), &string("d", 52, 53))
+        }
+
+        #[test]
+        fn parses_sequence_at_same_indentation_as_key() {
+            let text = "DependsOn:\n- A\n- B\nType: X\n";
+            let actual = root(text);
+
+            let depends_on = actual.get("DependsOn").unwrap().as_sequence().unwrap();
+            assert_eq!(depends_on.len(), 2);
+            assert_eq!(actual.get("Type").unwrap(), &string("X", 25, 26))
+        }
+
+        #[test]
+        fn parses_scalar_types() {
+            let actual = root("a: 1\nb: -2.5e3\nc: true\nd: ~\ne: '1'\nf:\n");
+
+            assert_eq!(actual.get("a").unwrap().value(), &Value::Number("1".into()));
+            assert_eq!(
+                actual.get("b").unwrap().value(),
+                &Value::Number("-2.5e3".into())
+            );
+            assert_eq!(actual.get("c").unwrap().value(), &Value::Bool(true));
+            assert_eq!(actual.get("d").unwrap().value(), &Value::Null);
+            assert_eq!(actual.get("e").unwrap().value(), &Value::String("1".into()));
+            assert_eq!(actual.get("f").unwrap().value(), &Value::Null)
+        }
+
+        #[test]
+        fn parses_multiline_plain_scalar() {
+            let actual = root("Description: a long\n  description\n\n  here\nNext: x\n");
+            assert_eq!(
+                actual.get("Description").unwrap().as_str(),
+                Some("a long description\nhere")
+            )
+        }
+
+        #[test]
+        fn parses_quoted_scalars() {
+            let actual = root("a: \"x\\ty \\\"z\\\"\"\nb: 'it''s'\nc: \"a # not comment\"\n");
+
+            assert_eq!(actual.get("a").unwrap().as_str(), Some("x\ty \"z\""));
+            assert_eq!(actual.get("b").unwrap().as_str(), Some("it's"));
+            assert_eq!(actual.get("c").unwrap().as_str(), Some("a # not comment"))
+        }
+
+        #[test]
+        fn parses_literal_block_scalar() {
+            let text = "UserData: |\n  #!/bin/bash\n    echo hi\n\nNext: x\n";
+            let actual = root(text);
+            assert_eq!(
+                actual.get("UserData").unwrap().as_str(),
+                Some("#!/bin/bash\n  echo hi\n")
+            )
+        }
+
+        #[test]
+        fn parses_folded_block_scalar_with_chomping() {
+            let actual = root("a: >-\n  one\n  two\n\n  three\nb: x\n");
+            assert_eq!(actual.get("a").unwrap().as_str(), Some("one two\nthree"))
+        }
+
+        #[test]
+        fn records_comments() {
+            let (_, comments, _, _) = parse("# header\na: b # trailing\n");
+            assert_eq!(
+                comments,
+                vec![
+                    Comment::new(" header", Span::new(0, 8)),
+                    Comment::new(" trailing", Span::new(14, 24)),
+                ]
+            )
+        }
+
+        #[test]
+        fn resolves_aliases() {
+            let actual = root("a: &tags\n  - x\nb: *tags\n");
+            assert_eq!(
+                actual.get("b").unwrap().value(),
+                actual.get("a").unwrap().value()
+            )
+        }
+
+        #[test]
+        fn parses_explicit_keys() {
+            let actual = root("? a\n: b\n? c\nd:\n  ? e\n  :\n    - f\n");
+
+            let keys: Vec<_> = actual
+                .as_mapping()
+                .unwrap()
+                .iter()
+                .map(|entry| entry.key().clone())
+                .collect();
+            assert_eq!(
+                keys,
+                vec![
+                    Key::new("a", Span::new(2, 3)),
+                    Key::new("c", Span::new(10, 11)),
+                    Key::new("d", Span::new(12, 13)),
+                ]
+            );
+            assert_eq!(actual.get("a").unwrap(), &string("b", 6, 7));
+            assert_eq!(actual.get("c").unwrap().value(), &Value::Null);
+            let items = actual.get("d").unwrap().get("e").unwrap();
+            assert_eq!(items.as_sequence().unwrap(), &[string("f", 31, 32)])
+        }
+
+        #[test]
+        fn errors_on_collection_keys() {
+            assert_eq!(
+                errors("? [a, b]\n: c\n")[0],
+                ParseError::new("Expected a scalar mapping key", Span::new(0, 8))
+            )
+        }
+
+        #[test]
+        fn records_anchors_and_aliases() {
+            let (_, _, anchored, _) = parse("a: &tags\n  - x\nb: [*tags]\n");
+            assert_eq!(anchored, vec![Span::new(3, 8), Span::new(19, 24)])
+        }
+
+        #[test]
+        fn skips_document_markers() {
+            let actual = root("---\na: b\n...\n");
+            assert_eq!(actual.get("a").unwrap().as_str(), Some("b"))
+        }
+
+        #[test]
+        fn errors_on_bad_indentation() {
+            assert_eq!(
+                errors("a:\n  b: c\n    d: e\n")[0],
+                ParseError::new("Unexpected indentation", Span::new(14, 18))
+            )
+        }
+
+        #[test]
+        fn errors_on_content_after_value() {
+            assert_eq!(
+                errors("a: b: c\n")[0],
+                ParseError::new("Unexpected content", Span::new(4, 7))
+            )
+        }
+
+        #[test]
+        fn errors_on_tab_indentation() {
+            assert_eq!(errors("a:\n\tb: c\n").len(), 1)
+        }
+    }
+
+    mod flow_tests {
+        use super::*;
+
+        #[test]
+        fn parses_flow_collections() {
+            let actual = root("a: [x, 'y', {k: v, l: [1, 2]}]\n");
+
+            let items = actual.get("a").unwrap().as_sequence().unwrap();
+            assert_eq!(items.len(), 3);
+            assert_eq!(items[0], string("x", 4, 5));
+            assert_eq!(items[2].get("l").unwrap().as_sequence().unwrap().len(), 2)
+        }
+
+        #[test]
+        fn parses_multiline_flow_collections() {
+            let actual = root("a: [\n  x, # comment\n  y\n]\nb: c\n");
+            assert_eq!(actual.get("a").unwrap().as_sequence().unwrap().len(), 2);
+            assert_eq!(actual.get("b").unwrap().as_str(), Some("c"))
+        }
+
+        #[test]
+        fn errors_on_unterminated_flow_sequence() {
+            assert_eq!(
+                errors("a: [x, y\n")[0],
+                ParseError::new("Unterminated flow sequence, expected ']'", Span::new(3, 4))
+            )
+        }
+    }
+
+    mod intrinsic_tests {
+        use super::*;
+
+        #[test]
+        fn parses_short_form_tags() {
+            let actual = root("a: !Ref Bucket\n");
+
+            let function = actual.get("a").unwrap().as_function().unwrap();
+            assert_eq!(function.intrinsic(), Intrinsic::Ref);
+            assert!(function.short_form());
+            assert_eq!(function.name_span(), Span::new(3, 7));
+            assert_eq!(function.argument(), &string("Bucket", 8, 14))
+        }
+
+        #[test]
+        fn parses_tags_on_block_collections() {
+            let actual = root("a: !If\n  - IsProd\n  - !GetAtt [B, Arn]\n  - !Ref AWS::NoValue\n");
+
+            let function = actual.get("a").unwrap().as_function().unwrap();
+            assert_eq!(function.intrinsic(), Intrinsic::If);
+            let arguments = function.argument().as_sequence().unwrap();
+            assert_eq!(
+                arguments[1].as_function().unwrap().intrinsic(),
+                Intrinsic::GetAtt
+            )
+        }
+
+        #[test]
+        fn parses_long_form_functions() {
+            let actual = root("a:\n  Fn::Sub: ${AWS::Region}\n");
+
+            let function = actual.get("a").unwrap().as_function().unwrap();
+            assert_eq!(function.intrinsic(), Intrinsic::Sub);
+            assert!(!function.short_form());
+            assert_eq!(function.name_span(), Span::new(5, 12))
+        }
+
+        #[test]
+        fn parses_tags_in_sequence_entries() {
+            let actual = root("a:\n  - !Sub '${A}-x'\n  - b\n");
+
+            let items = actual.get("a").unwrap().as_sequence().unwrap();
+            assert_eq!(
+                items[0].as_function().unwrap().argument().as_str(),
+                Some("${A}-x")
+            )
+        }
+
+        #[test]
+        fn errors_on_unknown_tag() {
+            assert_eq!(errors("a: !Nope b\n").len(), 1)
+        }
+    }
+
+    mod recovery_tests {
+        use super::*;
+
+        #[test]
+        fn keeps_content_around_half_typed_key() {
+            let text = "Resources:\n  Bucket:\n    Type: AWS::S3::Bucket\n    Prop\n  Queue:\n    Type: AWS::SQS::Queue\n";
+            let (root, _, _, errors) = parse(text);
+
+            assert_eq!(
+                errors,
+                vec![ParseError::new("Expected a mapping key", Span::new(51, 55))]
+            );
+            let resources = root.get("Resources").unwrap();
+            assert!(resources.get("Bucket").unwrap().get("Type").is_some());
+            assert_eq!(
+                resources.get("Queue").unwrap().get("Type").unwrap(),
+                &string("AWS::SQS::Queue", 75, 90)
+            )
+        }
+
+        #[test]
+        fn keeps_key_before_unterminated_string() {
+            let (root, _, _, errors) = parse("a: \"b\nc: d\n");
+
+            assert_eq!(errors.len(), 1);
+            assert_eq!(root.get("a").unwrap().value(), &Value::Null);
+            assert_eq!(root.get("c").unwrap().as_str(), Some("d"))
+        }
+
+        #[test]
+        fn reports_each_error() {
+            let (root, _, _, errors) = parse("a:\n  b: [1\n  c: !Nope\n  d: e\n");
+
+            assert_eq!(errors.len(), 2);
+            assert_eq!(root.get("a").unwrap().get("d").unwrap().as_str(), Some("e"))
+        }
+    }
+}
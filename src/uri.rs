@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+const FILE_SCHEME: &str = "file://";
+
+/// Local path of a `file` URI, decoding any percent-escaped characters
+pub fn to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix(FILE_SCHEME)?;
+    let path = decode(path)?;
+    // Windows paths are written with a leading slash, e.g. `/C:/templates`
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// `file` URI of a local path, percent-encoding any characters which aren't
+/// allowed unescaped
+pub fn from_path(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from(FILE_SCHEME);
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            // Windows drive letters are conventionally left unescaped
+            b':' if uri.len() == FILE_SCHEME.len() + 2 => uri.push(':'),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+fn decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let high = (iter.next()? as char).to_digit(16)?;
+            let low = (iter.next()? as char).to_digit(16)?;
+            bytes.push((high * 16 + low) as u8);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_file_uri() {
+        assert_eq!(
+            to_path("file:///home/user/my%20templates"),
+            Some(PathBuf::from("/home/user/my templates"))
+        )
+    }
+
+    #[test]
+    fn strips_slash_before_windows_drive() {
+        assert_eq!(
+            to_path("file:///c%3A/templates"),
+            Some(PathBuf::from("c:/templates"))
+        )
+    }
+
+    #[test]
+    fn converts_path_to_uri() {
+        assert_eq!(
+            from_path(Path::new("/home/user/my templates/cf.yaml")),
+            "file:///home/user/my%20templates/cf.yaml"
+        );
+        assert_eq!(
+            to_path(&from_path(Path::new("/home/user/my templates"))),
+            Some(PathBuf::from("/home/user/my templates"))
+        )
+    }
+
+    #[test]
+    fn keeps_windows_drive_in_uri() {
+        assert_eq!(from_path(Path::new("C:/templates")), "file:///C:/templates")
+    }
+
+    #[test]
+    fn ignores_other_schemes() {
+        assert_eq!(to_path("untitled:Untitled-1"), None)
+    }
+}
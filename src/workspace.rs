@@ -0,0 +1,260 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    document::Document,
+    method::workspace_symbol,
+    model::method::{initialise::PositionEncoding, workspace_symbol::SymbolInformation},
+    uri,
+};
+
+const EXTENSIONS: [&str; 4] = ["yaml", "yml", "json", "template"];
+
+/// Directories which never hold templates of our own, and are often large
+const SKIPPED_DIRECTORIES: [&str; 6] = [
+    "node_modules",
+    "target",
+    "cdk.out",
+    "venv",
+    "vendor",
+    "__pycache__",
+];
+
+/// CloudFormation rejects templates larger than this, so bigger files are
+/// data of some other kind
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+type Index = BTreeMap<PathBuf, Arc<[SymbolInformation]>>;
+
+/// Symbols of the templates found under the workspace folders, so that they
+/// can be searched without being open
+#[derive(Debug, Default)]
+pub struct Workspace {
+    folders: Vec<PathBuf>,
+    encoding: PositionEncoding,
+    symbols: Arc<Mutex<Index>>,
+    indexing: Option<JoinHandle<()>>,
+}
+
+impl Workspace {
+    /// Starts reading every CloudFormation template under the folders on a
+    /// thread of its own, so that large workspaces don't hold up
+    /// initialising. Searches see the templates indexed so far
+    pub fn index(folders: &[PathBuf], encoding: PositionEncoding) -> Self {
+        let symbols = Arc::new(Mutex::new(BTreeMap::new()));
+        let indexing = {
+            let folders = folders.to_vec();
+            let symbols = Arc::clone(&symbols);
+            thread::Builder::new()
+                .name("workspace-index".into())
+                .spawn(move || {
+                    for folder in &folders {
+                        let mut paths = Vec::new();
+                        find_templates(folder, &mut paths);
+                        let count = paths
+                            .iter()
+                            .filter(|path| index_file(&symbols, path, encoding))
+                            .count();
+                        tracing::info!("Indexed {count} templates under '{}'", folder.display());
+                    }
+                })
+                .inspect_err(|e| tracing::error!("Failed to start indexing workspace: {e}"))
+                .ok()
+        };
+        Self {
+            folders: folders.to_vec(),
+            encoding,
+            symbols,
+            indexing,
+        }
+    }
+
+    /// Re-reads a template from disk, e.g. once it's been saved or closed.
+    /// Files outside the workspace folders are ignored
+    pub fn refresh(&mut self, path: &Path) {
+        if !self.folders.iter().any(|folder| path.starts_with(folder)) {
+            return;
+        }
+        index_file(&self.symbols, path, self.encoding);
+    }
+
+    /// The symbols of each template indexed so far
+    pub fn symbols(&self) -> Vec<(PathBuf, Arc<[SymbolInformation]>)> {
+        lock(&self.symbols)
+            .iter()
+            .map(|(path, symbols)| (path.clone(), Arc::clone(symbols)))
+            .collect()
+    }
+
+    #[cfg(test)]
+    fn wait(&mut self) {
+        if let Some(indexing) = self.indexing.take() {
+            indexing.join().unwrap();
+        }
+    }
+}
+
+fn lock(symbols: &Mutex<Index>) -> MutexGuard<'_, Index> {
+    // The index is only ever replaced an entry at a time, so it's whole
+    // even if a thread panicked holding the lock
+    symbols.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Reads a file into the index, or drops it from the index if it's no longer
+/// a template. The lock is held throughout, so that a file refreshed while
+/// the workspace is being indexed is left with its latest contents. Returns
+/// whether the file is a template
+fn index_file(symbols: &Mutex<Index>, path: &Path, encoding: PositionEncoding) -> bool {
+    let mut symbols = lock(symbols);
+    match read(path, encoding) {
+        Some(read) => {
+            symbols.insert(path.to_path_buf(), read);
+            true
+        }
+        None => {
+            symbols.remove(path);
+            false
+        }
+    }
+}
+
+fn read(path: &Path, encoding: PositionEncoding) -> Option<Arc<[SymbolInformation]>> {
+    let size = fs::metadata(path).ok()?.len();
+    if size > MAX_FILE_SIZE {
+        tracing::debug!("Skipped indexing '{}' of {size} bytes", path.display());
+        return None;
+    }
+    let text = fs::read_to_string(path).ok()?;
+    let language_id = match path.extension().is_some_and(|ext| ext == "json") {
+        true => "json",
+        false => "yaml",
+    };
+    let document = Document::new(&uri::from_path(path), language_id, 0, &text, encoding);
+    is_template(&document).then(|| workspace_symbol::symbols(&document).into())
+}
+
+fn find_templates(directory: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        tracing::warn!("Failed to read workspace folder '{}'", directory.display());
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        // Symbolic links to directories aren't followed, as they can loop
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            if !name.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&name) {
+                find_templates(&path, paths);
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.contains(&ext))
+        {
+            paths.push(path);
+        }
+    }
+}
+
+/// Other YAML and JSON files, such as CI configuration, have neither key
+fn is_template(document: &Document) -> bool {
+    let root = document.template().root();
+    root.get("Resources").is_some() || root.get("AWSTemplateFormatVersion").is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("cfn-lsp-workspace-{name}-{}", std::process::id()));
+        for (file, contents) in files {
+            let path = directory.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        directory
+    }
+
+    fn indexed(workspace: &Workspace, root: &Path) -> Vec<PathBuf> {
+        workspace
+            .symbols()
+            .iter()
+            .map(|(path, _)| path.strip_prefix(root).unwrap().to_path_buf())
+            .collect()
+    }
+
+    #[test]
+    fn indexes_templates_only() {
+        let root = directory(
+            "index",
+            &[
+                (
+                    "network/vpc.yaml",
+                    "Resources:\n  Vpc:\n    Type: AWS::EC2::VPC\n",
+                ),
+                ("app.json", r#"{"AWSTemplateFormatVersion": "2010-09-09"}"#),
+                (".github/workflows/ci.yml", "Resources: []\n"),
+                ("node_modules/lib/cf.yaml", "Resources: {}\n"),
+                ("package.json", r#"{"name": "app"}"#),
+                ("README.md", "Resources:"),
+                (
+                    "large.json",
+                    &format!(
+                        r#"{{"Resources": {{}}, "Data": "{}"}}"#,
+                        "x".repeat(1 << 20)
+                    ),
+                ),
+            ],
+        );
+        let mut workspace = Workspace::index(std::slice::from_ref(&root), PositionEncoding::Utf16);
+        workspace.wait();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            indexed(&workspace, &root),
+            vec![PathBuf::from("app.json"), PathBuf::from("network/vpc.yaml")]
+        )
+    }
+
+    #[test]
+    fn refreshes_changed_files() {
+        let root = directory("refresh", &[("cf.yaml", "Resources: {}\n")]);
+        let mut workspace = Workspace::index(std::slice::from_ref(&root), PositionEncoding::Utf16);
+        workspace.wait();
+        assert_eq!(indexed(&workspace, &root).len(), 1);
+
+        std::fs::write(root.join("cf.yaml"), "name: not a template\n").unwrap();
+        workspace.refresh(&root.join("cf.yaml"));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(indexed(&workspace, &root).is_empty())
+    }
+
+    #[test]
+    fn keeps_symbols_only() {
+        let root = directory(
+            "symbols",
+            &[(
+                "cf.yaml",
+                "Resources:\n  Queue:\n    Type: AWS::SQS::Queue\n",
+            )],
+        );
+        let mut workspace = Workspace::index(std::slice::from_ref(&root), PositionEncoding::Utf16);
+        workspace.wait();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let symbols = workspace.symbols();
+        let names: Vec<_> = symbols[0].1.iter().map(|symbol| symbol.name()).collect();
+        assert_eq!(names, vec!["Queue", "AWS::SQS::Queue"])
+    }
+}
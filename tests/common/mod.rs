@@ -1,3 +1,5 @@
+#![allow(clippy::unwrap_used)]
+
 use std::{
     fs::File,
     io::{BufRead, BufReader, Read},
@@ -27,8 +29,7 @@ where
     reader.read_line(&mut buffer).unwrap();
     reader.read_line(&mut buffer).unwrap();
 
-    let mut buffer = Vec::new();
-    buffer.resize(content_length, 0);
+    let mut buffer = vec![0; content_length];
     reader.read_exact(&mut buffer).unwrap();
 
     String::from_utf8(buffer).unwrap()
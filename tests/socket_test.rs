@@ -1,7 +1,7 @@
 use std::{
     io::{BufRead, BufReader, Write},
     net::TcpStream,
-    process::Command,
+    process::{Child, Command},
 };
 
 mod common;
@@ -9,7 +9,7 @@ mod common;
 #[test]
 fn lifecycle() {
     let port = "32770"; // TODO: automatically assign available port
-    launch_server(port.to_string());
+    let mut server = launch_server(port);
 
     let mut connection = TcpStream::connect(format!("127.0.0.1:{port}")).unwrap();
     let mut reader = BufReader::new(connection.try_clone().unwrap());
@@ -28,22 +28,21 @@ fn lifecycle() {
         .write_all(&common::message(r#"{"jsonrpc":"2.0","method":"exit"}"#))
         .unwrap();
     connection.flush().unwrap();
+    server.wait().unwrap();
 
     let expected: Vec<String> = common::file_reader("./tests/resources/full/output.txt")
         .lines()
-        .into_iter()
         .map(Result::unwrap)
         .collect();
 
     assert_eq!(responses, expected)
 }
 
-fn launch_server(port: String) {
-    std::thread::spawn(move || {
-        Command::new(env!("CARGO_BIN_EXE_cfn-lsp"))
-            .args(["socket", "--port", &port])
-            .spawn()
-            .unwrap();
-    });
+fn launch_server(port: &str) -> Child {
+    let server = Command::new(env!("CARGO_BIN_EXE_cfn-lsp"))
+        .args(["socket", "--port", port])
+        .spawn()
+        .expect("Server process spawned");
     std::thread::sleep(std::time::Duration::new(1, 0));
+    server
 }
@@ -33,7 +33,6 @@ fn lifecycle() {
 
     let expected: Vec<String> = common::file_reader("./tests/resources/full/output.txt")
         .lines()
-        .into_iter()
         .map(Result::unwrap)
         .collect();
 